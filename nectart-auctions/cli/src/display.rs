@@ -0,0 +1,119 @@
+//! Human-readable output of auctions.
+
+use nectart_auctions_client::{Auction, AuctionKind, AuctionStatus, NftStandard, PriceCurve};
+use solana_sdk::native_token::lamports_to_sol;
+use solana_sdk::pubkey::Pubkey;
+
+pub fn is_active(auction: &Auction) -> bool {
+    matches!(
+        auction.status,
+        AuctionStatus::Scheduled | AuctionStatus::Live | AuctionStatus::Revealing
+    )
+}
+
+/// Amount in the currency of the auction.
+pub fn amount(amount: u64, auction: &Auction) -> String {
+    match auction.currency {
+        Some(mint) => format!("{amount} (base units of {mint})"),
+        None => format!("{} SOL", lamports_to_sol(amount)),
+    }
+}
+
+/// Unix timestamp along with how far it is from `now`.
+fn time(timestamp: i64, now: i64) -> String {
+    let delta = timestamp - now;
+    let span = duration(delta.unsigned_abs());
+    if delta >= 0 {
+        format!("{timestamp} (in {span})")
+    } else {
+        format!("{timestamp} ({span} ago)")
+    }
+}
+
+fn duration(seconds: u64) -> String {
+    match seconds {
+        0..=59 => format!("{seconds}s"),
+        60..=3599 => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        3600..=86_399 => format!("{}h{:02}m", seconds / 3600, seconds % 3600 / 60),
+        _ => format!("{}d{:02}h", seconds / 86_400, seconds % 86_400 / 3600),
+    }
+}
+
+fn kind(kind: &AuctionKind) -> String {
+    match kind {
+        AuctionKind::English => "English".to_string(),
+        AuctionKind::Dutch { start_price, curve: PriceCurve::Linear } => {
+            format!("Dutch from {start_price}, linear")
+        }
+        AuctionKind::Dutch { start_price, curve: PriceCurve::Exponential { half_life } } => {
+            format!("Dutch from {start_price}, half-life {}", duration(half_life.unsigned_abs()))
+        }
+        AuctionKind::Sealed { reveal_deadline, forfeit_unrevealed } => format!(
+            "Sealed-bid, reveal until {reveal_deadline}{}",
+            if *forfeit_unrevealed { ", unrevealed deposits forfeited" } else { "" }
+        ),
+    }
+}
+
+fn standard(standard: &NftStandard) -> &'static str {
+    match standard {
+        NftStandard::Legacy => "Token Metadata NFT",
+        NftStandard::Programmable => "programmable NFT",
+        NftStandard::Token2022 => "Token-2022 NFT",
+        NftStandard::Core => "Core asset",
+        NftStandard::Compressed => "compressed NFT",
+    }
+}
+
+/// Prints every detail of an auction.
+pub fn auction(address: &Pubkey, auction: &Auction, now: i64) {
+    println!("Auction        {address}");
+    println!("Status         {:?}", auction.status);
+    println!("NFT            {} ({})", auction.mint, standard(&auction.standard));
+    println!("Id             {}", auction.id);
+    println!("Maker          {}", auction.maker);
+    println!("Kind           {}", kind(&auction.kind));
+    println!("Start          {}", time(auction.start_time, now));
+    println!("Deadline       {}", time(auction.deadline, now));
+    if auction.extended_by > 0 {
+        println!("Extended by    {}", duration(auction.extended_by.unsigned_abs()));
+    }
+    if let Some(soft_close) = auction.soft_close {
+        println!(
+            "Soft close     +{} for bids in the last {}",
+            duration(soft_close.extension.unsigned_abs()),
+            duration(soft_close.window.unsigned_abs()),
+        );
+    }
+    println!("Minimum price  {}", amount(auction.min_price, auction));
+    println!("Increment      {}", amount(auction.min_increment, auction));
+    if let Some(price) = auction.buy_now_price {
+        println!("Buy now        {}", amount(price, auction));
+    }
+    match (auction.current_bid, auction.current_bidder) {
+        (Some(bid), Some(bidder)) => println!("Leading bid    {} by {bidder}", amount(bid, auction)),
+        _ => println!("Leading bid    none"),
+    }
+    if let Some(price) = auction.clearing_price() {
+        println!("Clearing price {}", amount(price, auction));
+    }
+    if auction.seller_fee_basis_points > 0 {
+        println!("Royalties      {}%", f64::from(auction.seller_fee_basis_points) / 100.0);
+    }
+    if auction.lot_items > 0 {
+        println!("Lot items      {}", auction.lot_items);
+    }
+}
+
+/// Prints an auction on one line.
+pub fn summary(address: &Pubkey, auction: &Auction, now: i64) {
+    let bid = match auction.current_bid {
+        Some(bid) => amount(bid, auction),
+        None => "no bid".to_string(),
+    };
+    println!(
+        "{address}  {:?}  ends {}  {bid}",
+        auction.status,
+        time(auction.deadline, now),
+    );
+}
@@ -0,0 +1,465 @@
+//! `nectart`: command-line tool to operate auctions of the nectart-auctions program.
+
+mod display;
+
+use std::path::PathBuf;
+
+use anchor_lang::Discriminator;
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use nectart_auctions_client::errors::decode_error;
+use nectart_auctions_client::instructions::{self, Approval, AuctionParams, Currency};
+use nectart_auctions_client::{accounts, pda, Auction, AuctionKind, NftStandard, SoftClose, ID};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::client_error::ClientError;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::clock::Clock;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use solana_sdk::sysvar;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+const RPC_URL: &str = "https://api.devnet.solana.com";
+
+/// Items of a lot delivered per `claim_nft` transaction, to stay within the transaction size.
+const LOT_ITEMS_PER_CLAIM: usize = 3;
+
+#[derive(Parser)]
+#[command(name = "nectart", version, about = "Operate nectart NFT auctions")]
+struct Cli {
+    /// RPC URL of the cluster.
+    #[arg(long, short = 'u', env = "NECTART_RPC_URL", default_value = RPC_URL, global = true)]
+    url: String,
+    /// Keypair file signing and paying for transactions. Defaults to the Solana CLI keypair.
+    #[arg(long, short = 'k', env = "NECTART_KEYPAIR", global = true)]
+    keypair: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Put a Token Metadata NFT of the keypair up for auction.
+    CreateAuction(CreateAuctionArgs),
+    /// Bid on an English auction, or win a Dutch auction at its current price.
+    Bid {
+        auction: Pubkey,
+        /// Bid in lamports, or in base units of the currency of the auction.
+        amount: u64,
+    },
+    /// Claim the NFT of an ended auction, as its winner or as its maker if there were no bids.
+    /// Only Legacy NFTs are supported.
+    ClaimNft { auction: Pubkey },
+    /// Claim the proceeds of an ended auction as its maker, whatever the standard of its NFT.
+    ClaimSol { auction: Pubkey },
+    /// Deliver the NFT to the winner and pay the maker of an ended auction, for the crank reward
+    /// once the crank delay has elapsed. Only Legacy NFTs are supported, and lots are left to the
+    /// parties.
+    Settle { auction: Pubkey },
+    /// Print the state of an auction.
+    Show { auction: Pubkey },
+    /// List the auctions that are scheduled or still running.
+    ListActive,
+}
+
+#[derive(Args)]
+struct CreateAuctionArgs {
+    /// Mint of the NFT.
+    mint: Pubkey,
+    /// Start of the auction, as a Unix timestamp. Defaults to now.
+    #[arg(long)]
+    start: Option<i64>,
+    /// End of the auction, as a Unix timestamp.
+    #[arg(long)]
+    deadline: i64,
+    /// Lowest accepted bid, in lamports or in base units of the currency.
+    #[arg(long, default_value_t = 0)]
+    min_price: u64,
+    /// Minimum raise over the current bid.
+    #[arg(long, default_value_t = 0)]
+    min_increment: u64,
+    /// Price at which the NFT can be bought outright, which ends the auction at that price.
+    /// Buying is rejected once a bid reaches the price.
+    #[arg(long)]
+    buy_now_price: Option<u64>,
+    /// Anti-sniping window in seconds: bids placed this close to the deadline extend it.
+    #[arg(long, requires = "extension")]
+    soft_close_window: Option<i64>,
+    /// Seconds a late bid adds to the deadline.
+    #[arg(long, requires = "soft_close_window")]
+    extension: Option<i64>,
+    /// Approved collection of the NFT, by its mint.
+    #[arg(long, conflicts_with = "creator", required_unless_present = "creator")]
+    collection: Option<Pubkey>,
+    /// Approved first verified creator of the NFT.
+    #[arg(long)]
+    creator: Option<Pubkey>,
+    /// Mint of the token bids are made in, instead of SOL.
+    #[arg(long)]
+    currency: Option<Pubkey>,
+}
+
+struct Session {
+    client: RpcClient,
+    payer: Keypair,
+}
+
+impl Session {
+    fn auction(&self, address: &Pubkey) -> Result<Auction> {
+        let data = self
+            .client
+            .get_account_data(address)
+            .with_context(|| format!("auction {address} not found"))?;
+        accounts::auction(&data).map_err(|error| anyhow!("{address} is not an auction: {error}"))
+    }
+
+    /// Currency of an auction, whose token program is the owner of its mint.
+    fn currency(&self, mint: Option<Pubkey>) -> Result<Option<Currency>> {
+        let Some(mint) = mint else {
+            return Ok(None);
+        };
+        let account = self
+            .client
+            .get_account(&mint)
+            .with_context(|| format!("currency mint {mint} not found"))?;
+        Ok(Some(Currency {
+            mint,
+            token_program: account.owner,
+        }))
+    }
+
+    fn now(&self) -> Result<i64> {
+        let account = self.client.get_account(&sysvar::clock::ID)?;
+        let clock: Clock = solana_sdk::account::from_account(&account)
+            .ok_or_else(|| anyhow!("cannot read the clock of the cluster"))?;
+        Ok(clock.unix_timestamp)
+    }
+
+    fn send(&self, instructions: &[Instruction]) -> Result<Signature> {
+        let blockhash = self.client.get_latest_blockhash()?;
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            blockhash,
+        );
+        self.client
+            .send_and_confirm_transaction(&transaction)
+            .map_err(explain)
+    }
+}
+
+/// Turns a failed transaction into the error of the program behind it, when there is one.
+fn explain(error: ClientError) -> anyhow::Error {
+    match error.get_transaction_error() {
+        Some(TransactionError::InstructionError(_, ref instruction_error)) => {
+            match decode_error(instruction_error) {
+                Some(auction_error) => anyhow!("{auction_error}"),
+                None => error.into(),
+            }
+        }
+        _ => error.into(),
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let keypair = match cli.keypair {
+        Some(path) => path,
+        None => default_keypair()?,
+    };
+    let payer = read_keypair_file(&keypair)
+        .map_err(|error| anyhow!("cannot read keypair {}: {error}", keypair.display()))?;
+    let session = Session {
+        client: RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed()),
+        payer,
+    };
+
+    match cli.command {
+        Command::CreateAuction(args) => create_auction(&session, args),
+        Command::Bid { auction, amount } => bid(&session, &auction, amount),
+        Command::ClaimNft { auction } => claim_nft(&session, &auction),
+        Command::ClaimSol { auction } => claim_sol(&session, &auction),
+        Command::Settle { auction } => settle(&session, &auction),
+        Command::Show { auction } => show(&session, &auction),
+        Command::ListActive => list_active(&session),
+    }
+}
+
+/// Keypair of the Solana CLI.
+fn default_keypair() -> Result<PathBuf> {
+    std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join(".config/solana/id.json"))
+        .ok_or_else(|| anyhow!("HOME is not set, pass --keypair"))
+}
+
+fn create_auction(session: &Session, args: CreateAuctionArgs) -> Result<()> {
+    let maker = session.payer.pubkey();
+    let id = match session.client.get_account_data(&pda::auction_counter(&args.mint)) {
+        Ok(data) => accounts::auction_counter(&data)
+            .map_err(|error| anyhow!("cannot read the auction counter: {error}"))?
+            .count,
+        Err(_) => 0,
+    };
+    let approval = match (args.collection, args.creator) {
+        (Some(collection), _) => Approval::Collection(collection),
+        (None, Some(creator)) => Approval::Creator(creator),
+        (None, None) => bail!("pass --collection or --creator"),
+    };
+    let soft_close = match (args.soft_close_window, args.extension) {
+        (Some(window), Some(extension)) => Some(SoftClose {
+            window,
+            extension,
+            max_extension: None,
+        }),
+        _ => None,
+    };
+    let params = AuctionParams {
+        start_time: match args.start {
+            Some(start) => start,
+            None => session.now()?,
+        },
+        deadline: args.deadline,
+        min_price: args.min_price,
+        min_increment: args.min_increment,
+        soft_close,
+        kind: AuctionKind::English,
+        buy_now_price: args.buy_now_price,
+    };
+    let currency = session.currency(args.currency)?;
+    let instruction = instructions::create_auction(&maker, &args.mint, id, approval, params, currency.as_ref());
+    let signature = session.send(&[instruction])?;
+    println!("Created auction {}", pda::auction(&args.mint, id));
+    println!("Signature: {signature}");
+    Ok(())
+}
+
+fn bid(session: &Session, address: &Pubkey, amount: u64) -> Result<()> {
+    let auction = session.auction(address)?;
+    let currency = session.currency(auction.currency)?;
+    let instruction = instructions::bid(&session.payer.pubkey(), &auction.mint, auction.id, amount, currency.as_ref());
+    let signature = session.send(&[instruction])?;
+    println!("Bid {} on auction {address}", display::amount(amount, &auction));
+    println!("Signature: {signature}");
+    Ok(())
+}
+
+/// Checks the CLI can derive the accounts moving the NFT of the auction at `address`.
+fn require_legacy(address: &Pubkey, standard: NftStandard) -> Result<()> {
+    if standard != NftStandard::Legacy {
+        bail!("auction {address} is of a {standard:?} NFT, the CLI can only move Legacy NFTs");
+    }
+    Ok(())
+}
+
+fn claim_nft(session: &Session, address: &Pubkey) -> Result<()> {
+    let auction = session.auction(address)?;
+    require_legacy(address, auction.standard)?;
+    let signer = session.payer.pubkey();
+    let claim = |lot_items: Option<&[Pubkey]>| -> Result<()> {
+        let instruction = instructions::claim_nft(&signer, &auction.maker, &auction.mint, auction.id, auction.standard, lot_items)?;
+        let signature = session.send(&[instruction])?;
+        println!("Signature: {signature}");
+        Ok(())
+    };
+
+    if auction.lot_items == 0 {
+        claim(None)?;
+    } else {
+        let data = session.client.get_account_data(&pda::lot(address))?;
+        let lot = accounts::lot(&data).map_err(|error| anyhow!("cannot read the lot: {error}"))?;
+        let pending: Vec<Pubkey> = lot
+            .items
+            .iter()
+            .filter(|item| !item.delivered)
+            .map(|item| item.mint)
+            .collect();
+        // the NFT of the auction goes with the first batch of items
+        if pending.is_empty() {
+            claim(Some(&[]))?;
+        }
+        for batch in pending.chunks(LOT_ITEMS_PER_CLAIM) {
+            claim(Some(batch))?;
+        }
+    }
+    println!("Claimed the NFT of auction {address}");
+    Ok(())
+}
+
+fn claim_sol(session: &Session, address: &Pubkey) -> Result<()> {
+    let auction = session.auction(address)?;
+    let currency = session.currency(auction.currency)?;
+    let creators: Vec<Pubkey> = auction.creators.iter().map(|creator| creator.address).collect();
+    let instruction = instructions::claim_proceeds(&session.payer.pubkey(), &auction.mint, auction.id, &creators, currency.as_ref());
+    let signature = session.send(&[instruction])?;
+    println!("Claimed the proceeds of auction {address}");
+    println!("Signature: {signature}");
+    Ok(())
+}
+
+fn settle(session: &Session, address: &Pubkey) -> Result<()> {
+    let auction = session.auction(address)?;
+    require_legacy(address, auction.standard)?;
+    let winner = auction.current_bidder.ok_or_else(|| anyhow!("auction {address} has no winner"))?;
+    let currency = session.currency(auction.currency)?;
+    let creators: Vec<Pubkey> = auction.creators.iter().map(|creator| creator.address).collect();
+    let instruction = instructions::settle(
+        &session.payer.pubkey(),
+        &winner,
+        &auction.maker,
+        &auction.mint,
+        auction.id,
+        auction.standard,
+        &creators,
+        currency.as_ref(),
+    )?;
+    let signature = session.send(&[instruction])?;
+    println!("Settled auction {address}");
+    println!("Signature: {signature}");
+    Ok(())
+}
+
+fn show(session: &Session, address: &Pubkey) -> Result<()> {
+    let mut auction = session.auction(address)?;
+    let now = session.now()?;
+    auction.refresh_status(now);
+    display::auction(address, &auction, now);
+    Ok(())
+}
+
+fn list_active(session: &Session) -> Result<()> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            &Auction::DISCRIMINATOR,
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let now = session.now()?;
+    let mut active: Vec<(Pubkey, Auction)> = session
+        .client
+        .get_program_accounts_with_config(&ID, config)?
+        .into_iter()
+        .filter_map(|(address, account)| {
+            let mut auction = accounts::auction(&account.data).ok()?;
+            auction.refresh_status(now);
+            display::is_active(&auction).then_some((address, auction))
+        })
+        .collect();
+    active.sort_by_key(|(_, auction)| auction.deadline);
+
+    if active.is_empty() {
+        println!("No active auction");
+    }
+    for (address, auction) in &active {
+        display::summary(address, auction, now);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("nectart").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn create_auction() {
+        let mint = Pubkey::new_unique();
+        let collection = Pubkey::new_unique();
+        let cli = parse(&[
+            "create-auction",
+            &mint.to_string(),
+            "--deadline",
+            "1700000000",
+            "--collection",
+            &collection.to_string(),
+            "--buy-now-price",
+            "5000",
+            "--soft-close-window",
+            "300",
+            "--extension",
+            "60",
+        ])
+        .unwrap();
+        let Command::CreateAuction(args) = cli.command else {
+            panic!("not create-auction");
+        };
+        assert_eq!(args.mint, mint);
+        assert_eq!(args.start, None);
+        assert_eq!(args.deadline, 1_700_000_000);
+        assert_eq!(args.min_price, 0);
+        assert_eq!(args.buy_now_price, Some(5000));
+        assert_eq!(args.soft_close_window, Some(300));
+        assert_eq!(args.extension, Some(60));
+        assert_eq!(args.collection, Some(collection));
+    }
+
+    #[test]
+    fn create_auction_needs_one_approval() {
+        let mint = Pubkey::new_unique().to_string();
+        let approval = Pubkey::new_unique().to_string();
+        assert!(parse(&["create-auction", &mint, "--deadline", "1"]).is_err());
+        assert!(parse(&["create-auction", &mint, "--deadline", "1", "--collection", &approval, "--creator", &approval]).is_err());
+        assert!(parse(&["create-auction", &mint, "--deadline", "1", "--creator", &approval]).is_ok());
+    }
+
+    #[test]
+    fn soft_close_needs_both_parameters() {
+        let mint = Pubkey::new_unique().to_string();
+        let creator = Pubkey::new_unique().to_string();
+        let args = ["create-auction", &mint, "--deadline", "1", "--creator", &creator];
+        assert!(parse(&[&args[..], &["--soft-close-window", "300"]].concat()).is_err());
+        assert!(parse(&[&args[..], &["--extension", "60"]].concat()).is_err());
+    }
+
+    #[test]
+    fn auction_commands() {
+        let auction = Pubkey::new_unique();
+        let address = auction.to_string();
+
+        let cli = parse(&["bid", &address, "100"]).unwrap();
+        assert!(matches!(cli.command, Command::Bid { auction: a, amount: 100 } if a == auction));
+        assert!(parse(&["bid", &address]).is_err());
+        assert!(parse(&["bid", &address, "-1"]).is_err());
+
+        assert!(matches!(parse(&["claim-nft", &address]).unwrap().command, Command::ClaimNft { auction: a } if a == auction));
+        assert!(matches!(parse(&["claim-sol", &address]).unwrap().command, Command::ClaimSol { auction: a } if a == auction));
+        assert!(matches!(parse(&["settle", &address]).unwrap().command, Command::Settle { auction: a } if a == auction));
+        assert!(matches!(parse(&["show", &address]).unwrap().command, Command::Show { auction: a } if a == auction));
+        assert!(matches!(parse(&["list-active"]).unwrap().command, Command::ListActive));
+        assert!(parse(&["show", "not-a-key"]).is_err());
+    }
+
+    #[test]
+    fn global_options() {
+        let cli = parse(&["list-active", "--url", "http://localhost:8899", "-k", "id.json"]).unwrap();
+        assert_eq!(cli.url, "http://localhost:8899");
+        assert_eq!(cli.keypair, Some(PathBuf::from("id.json")));
+    }
+
+    #[test]
+    fn only_legacy_nfts_are_moved() {
+        let address = Pubkey::new_unique();
+        assert!(require_legacy(&address, NftStandard::Legacy).is_ok());
+        let error = require_legacy(&address, NftStandard::Core).unwrap_err();
+        assert_eq!(error.to_string(), format!("auction {address} is of a Core NFT, the CLI can only move Legacy NFTs"));
+    }
+}
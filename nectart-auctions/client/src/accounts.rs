@@ -0,0 +1,49 @@
+//! Decoders of the accounts of the program, from the data returned by the RPC.
+
+use anchor_lang::{AccountDeserialize, Result};
+
+use nectart_auctions::state::{
+    ApprovedCollection, ApprovedCreator, Auction, AuctionCounter, Config, Lot, RefundLedger,
+    SealedBid, VaultState,
+};
+
+/// Decodes an account, checking its discriminator.
+pub fn decode<T: AccountDeserialize>(mut data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut data)
+}
+
+pub fn auction(data: &[u8]) -> Result<Auction> {
+    decode(data)
+}
+
+pub fn vault_state(data: &[u8]) -> Result<VaultState> {
+    decode(data)
+}
+
+pub fn config(data: &[u8]) -> Result<Config> {
+    decode(data)
+}
+
+pub fn auction_counter(data: &[u8]) -> Result<AuctionCounter> {
+    decode(data)
+}
+
+pub fn lot(data: &[u8]) -> Result<Lot> {
+    decode(data)
+}
+
+pub fn refund_ledger(data: &[u8]) -> Result<RefundLedger> {
+    decode(data)
+}
+
+pub fn sealed_bid(data: &[u8]) -> Result<SealedBid> {
+    decode(data)
+}
+
+pub fn approved_collection(data: &[u8]) -> Result<ApprovedCollection> {
+    decode(data)
+}
+
+pub fn approved_creator(data: &[u8]) -> Result<ApprovedCreator> {
+    decode(data)
+}
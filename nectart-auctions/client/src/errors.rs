@@ -0,0 +1,182 @@
+//! Decoder of the errors of the program from failed transactions.
+
+use anchor_lang::solana_program::instruction::InstructionError;
+
+pub use nectart_auctions::errors::AuctionError;
+
+macro_rules! auction_errors {
+    ($($variant:ident),* $(,)?) => {
+        /// Every error of the program.
+        pub const AUCTION_ERRORS: &[AuctionError] = &[$(AuctionError::$variant),*];
+
+        // fails to compile when an error is added to the program but not listed here
+        #[allow(dead_code)]
+        fn exhaustive(error: AuctionError) {
+            match error {
+                $(AuctionError::$variant => ()),*
+            }
+        }
+    };
+}
+
+auction_errors!(
+    AuctionNotStarted,
+    AuctionEnded,
+    AuctionNotEnded,
+    BidTooLow,
+    BadPrecedingBidder,
+    BadAccount,
+    AuctionCancelled,
+    AuctionHasBids,
+    AlreadyClaimed,
+    AuctionNotSettled,
+    InvalidAuctionStatus,
+    InvalidSoftClose,
+    InvalidAuctionKind,
+    InvalidPriceCurve,
+    PriceTooHigh,
+    RevealNotStarted,
+    RevealEnded,
+    RevealMismatch,
+    AlreadyRevealed,
+    BidExceedsDeposit,
+    InvalidSealedBid,
+    BadCurrency,
+    NothingToRefund,
+    InvalidBuyNowPrice,
+    InvalidFee,
+    InsufficientTreasury,
+    BadCreator,
+    NotEligible,
+    NonTransferable,
+    MissingLeafProof,
+    NftNotDelivered,
+    InvalidLot,
+    LotFull,
+    RoyaltiesMismatch,
+    MissingLotItems,
+    InvalidCrankReward,
+    NoWinner,
+    NotSettleable,
+    DeadlineBeforeStart,
+    AuctionTooShort,
+    AuctionTooLong,
+    StartTooFar,
+    IncrementTooLow,
+    InvalidBounds,
+    ArithmeticOverflow,
+    DeductionsTooHigh,
+    InvalidGracePeriod,
+    NotAbortable,
+    DeliveryPending,
+    InvalidCrankDelay,
+    DeadlineInPast,
+    RevealTooLong,
+);
+
+/// Error of the program with the given custom error code.
+pub fn from_code(code: u32) -> Option<AuctionError> {
+    AUCTION_ERRORS
+        .iter()
+        .copied()
+        .find(|error| u32::from(*error) == code)
+}
+
+/// Error of the program behind a failed instruction, as found in
+/// `TransactionError::InstructionError`. Errors raised by Anchor itself or by other programs are
+/// not decoded.
+pub fn decode_error(error: &InstructionError) -> Option<AuctionError> {
+    match error {
+        InstructionError::Custom(code) => from_code(*code),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // codes of deployed errors must never change: new errors are appended to the program
+    const CODES: &[(AuctionError, u32)] = &[
+        (AuctionError::AuctionNotStarted, 6000),
+        (AuctionError::AuctionEnded, 6001),
+        (AuctionError::AuctionNotEnded, 6002),
+        (AuctionError::BidTooLow, 6003),
+        (AuctionError::BadPrecedingBidder, 6004),
+        (AuctionError::BadAccount, 6005),
+        (AuctionError::AuctionCancelled, 6006),
+        (AuctionError::AuctionHasBids, 6007),
+        (AuctionError::AlreadyClaimed, 6008),
+        (AuctionError::AuctionNotSettled, 6009),
+        (AuctionError::InvalidAuctionStatus, 6010),
+        (AuctionError::InvalidSoftClose, 6011),
+        (AuctionError::InvalidAuctionKind, 6012),
+        (AuctionError::InvalidPriceCurve, 6013),
+        (AuctionError::PriceTooHigh, 6014),
+        (AuctionError::RevealNotStarted, 6015),
+        (AuctionError::RevealEnded, 6016),
+        (AuctionError::RevealMismatch, 6017),
+        (AuctionError::AlreadyRevealed, 6018),
+        (AuctionError::BidExceedsDeposit, 6019),
+        (AuctionError::InvalidSealedBid, 6020),
+        (AuctionError::BadCurrency, 6021),
+        (AuctionError::NothingToRefund, 6022),
+        (AuctionError::InvalidBuyNowPrice, 6023),
+        (AuctionError::InvalidFee, 6024),
+        (AuctionError::InsufficientTreasury, 6025),
+        (AuctionError::BadCreator, 6026),
+        (AuctionError::NotEligible, 6027),
+        (AuctionError::NonTransferable, 6028),
+        (AuctionError::MissingLeafProof, 6029),
+        (AuctionError::NftNotDelivered, 6030),
+        (AuctionError::InvalidLot, 6031),
+        (AuctionError::LotFull, 6032),
+        (AuctionError::RoyaltiesMismatch, 6033),
+        (AuctionError::MissingLotItems, 6034),
+        (AuctionError::InvalidCrankReward, 6035),
+        (AuctionError::NoWinner, 6036),
+        (AuctionError::NotSettleable, 6037),
+        (AuctionError::DeadlineBeforeStart, 6038),
+        (AuctionError::AuctionTooShort, 6039),
+        (AuctionError::AuctionTooLong, 6040),
+        (AuctionError::StartTooFar, 6041),
+        (AuctionError::IncrementTooLow, 6042),
+        (AuctionError::InvalidBounds, 6043),
+        (AuctionError::ArithmeticOverflow, 6044),
+        (AuctionError::DeductionsTooHigh, 6045),
+        (AuctionError::InvalidGracePeriod, 6046),
+        (AuctionError::NotAbortable, 6047),
+        (AuctionError::DeliveryPending, 6048),
+        (AuctionError::InvalidCrankDelay, 6049),
+        (AuctionError::DeadlineInPast, 6050),
+        (AuctionError::RevealTooLong, 6051),
+    ];
+
+    #[test]
+    fn codes_are_stable() {
+        assert_eq!(CODES.len(), AUCTION_ERRORS.len());
+        for (error, code) in CODES {
+            assert_eq!(u32::from(*error), *code, "{error:?}");
+            assert!(matches!(from_code(*code), Some(decoded) if u32::from(decoded) == *code));
+        }
+    }
+
+    #[test]
+    fn custom_codes_are_decoded() {
+        assert!(matches!(decode_error(&InstructionError::Custom(6000)), Some(AuctionError::AuctionNotStarted)));
+        assert!(matches!(
+            decode_error(&InstructionError::Custom(AuctionError::BidTooLow.into())),
+            Some(AuctionError::BidTooLow),
+        ));
+        let last = AUCTION_ERRORS[AUCTION_ERRORS.len() - 1];
+        assert!(matches!(from_code(last.into()), Some(error) if u32::from(error) == u32::from(last)));
+    }
+
+    #[test]
+    fn other_errors_are_not_decoded() {
+        // raised by Anchor when an account is not initialized
+        assert!(decode_error(&InstructionError::Custom(3012)).is_none());
+        assert!(decode_error(&InstructionError::Custom(6000 + AUCTION_ERRORS.len() as u32)).is_none());
+        assert!(decode_error(&InstructionError::InvalidArgument).is_none());
+    }
+}
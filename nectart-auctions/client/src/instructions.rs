@@ -0,0 +1,868 @@
+//! Builders of the instructions of the program.
+//!
+//! The builders derive every account from the keys they are given, for Token Metadata NFTs and
+//! auctions in SOL or in a token. Other kinds of NFT need accounts the builders cannot derive:
+//! fill the [`accounts`](nectart_auctions::accounts) of the instruction and pass them to
+//! [`instruction`] instead. Builders moving the NFT of an existing auction take its
+//! [`NftStandard`] and return [`UnsupportedStandard`] for those other kinds.
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::{associated_token, metadata, token};
+
+use nectart_auctions::{accounts, instruction as data, AuctionBounds, AuctionKind, NftStandard, SoftClose, ID};
+
+use crate::pda;
+
+/// Token an auction is denominated in. Auctions without one are in SOL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Currency {
+    pub mint: Pubkey,
+    /// Token program of the mint, either SPL Token or Token-2022.
+    pub token_program: Pubkey,
+}
+
+impl Currency {
+    fn account(&self, owner: &Pubkey) -> Pubkey {
+        pda::associated_token_account(owner, &self.mint, &self.token_program)
+    }
+}
+
+/// Approval making an NFT eligible for auction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Approval {
+    /// The verified collection of the NFT, by its mint.
+    Collection(Pubkey),
+    /// The first verified creator of the NFT.
+    Creator(Pubkey),
+}
+
+impl Approval {
+    fn accounts(&self) -> (Option<Pubkey>, Option<Pubkey>) {
+        match self {
+            Approval::Collection(collection_mint) => (Some(pda::approved_collection(collection_mint)), None),
+            Approval::Creator(creator) => (None, Some(pda::approved_creator(creator))),
+        }
+    }
+}
+
+/// Standard of an NFT whose accounts the builders cannot derive. Build the instruction with
+/// [`instruction`] instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnsupportedStandard(pub NftStandard);
+
+impl std::fmt::Display for UnsupportedStandard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the accounts of {:?} NFTs cannot be derived, only those of Legacy NFTs", self.0)
+    }
+}
+
+impl std::error::Error for UnsupportedStandard {}
+
+fn require_legacy(standard: NftStandard) -> Result<(), UnsupportedStandard> {
+    match standard {
+        NftStandard::Legacy => Ok(()),
+        _ => Err(UnsupportedStandard(standard)),
+    }
+}
+
+/// Parameters of a new auction, as taken by `create_auction`.
+#[derive(Clone, Debug)]
+pub struct AuctionParams {
+    pub start_time: i64,
+    pub deadline: i64,
+    pub min_price: u64,
+    pub min_increment: u64,
+    pub soft_close: Option<SoftClose>,
+    pub kind: AuctionKind,
+    pub buy_now_price: Option<u64>,
+}
+
+/// Builds an instruction of the program from its accounts, its arguments and its remaining
+/// accounts.
+pub fn instruction(accounts: impl ToAccountMetas, args: impl InstructionData, remaining_accounts: &[AccountMeta]) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend_from_slice(remaining_accounts);
+    Instruction {
+        program_id: ID,
+        accounts: metas,
+        data: args.data(),
+    }
+}
+
+/// Accounts only used by programmable NFTs.
+pub fn no_programmable() -> accounts::ProgrammableNft {
+    accounts::ProgrammableNft {
+        token_record: None,
+        destination_token_record: None,
+        authorization_rules: None,
+        authorization_rules_program: None,
+        sysvar_instructions: None,
+    }
+}
+
+/// Accounts only used by Core assets.
+pub fn no_core() -> accounts::CoreAsset {
+    accounts::CoreAsset {
+        collection: None,
+        core_program: None,
+    }
+}
+
+/// Accounts only used by compressed NFTs.
+pub fn no_compressed() -> accounts::CompressedNft {
+    accounts::CompressedNft {
+        tree_config: None,
+        merkle_tree: None,
+        log_wrapper: None,
+        compression_program: None,
+        bubblegum_program: None,
+    }
+}
+
+/// Mint, token account of `owner`, token account of the vault of `auction` and token program of
+/// the currency, all omitted for auctions in SOL.
+fn currency_accounts(currency: Option<&Currency>, owner: &Pubkey, auction: &Pubkey) -> (Option<Pubkey>, Option<Pubkey>, Option<Pubkey>, Option<Pubkey>) {
+    match currency {
+        Some(currency) => (
+            Some(currency.mint),
+            Some(currency.account(owner)),
+            Some(currency.account(&pda::vault(auction))),
+            Some(currency.token_program),
+        ),
+        None => (None, None, None, None),
+    }
+}
+
+/// Accounts receiving the royalties of `creators`, in the order recorded on the auction.
+fn royalty_accounts(creators: &[Pubkey], currency: Option<&Currency>) -> Vec<AccountMeta> {
+    creators
+        .iter()
+        .map(|creator| match currency {
+            Some(currency) => AccountMeta::new(currency.account(creator), false),
+            None => AccountMeta::new(*creator, false),
+        })
+        .collect()
+}
+
+pub fn initialize(admin: &Pubkey, fee_bps: u16) -> Instruction {
+    instruction(
+        accounts::Initialize {
+            admin: *admin,
+            config: pda::config(),
+            treasury: pda::treasury(),
+            system_program: system_program::ID,
+        },
+        data::Initialize { fee_bps },
+        &[],
+    )
+}
+
+pub fn update_config(
+    admin: &Pubkey,
+    fee_bps: Option<u16>,
+    crank_reward_bps: Option<u16>,
+    crank_delay: Option<i64>,
+    bounds: Option<AuctionBounds>,
+    delivery_grace_period: Option<i64>,
+) -> Instruction {
+    instruction(
+        accounts::UpdateConfig {
+            admin: *admin,
+            config: pda::config(),
+        },
+        data::UpdateConfig {
+            fee_bps,
+            crank_reward_bps,
+            crank_delay,
+            bounds,
+            delivery_grace_period,
+        },
+        &[],
+    )
+}
+
+/// Brings a config created by the first version of the program to the current layout, with the
+/// protocol fee `fee_bps`. Only its admin can sign.
+pub fn migrate_config(admin: &Pubkey, fee_bps: u16) -> Instruction {
+    instruction(
+        accounts::MigrateConfig {
+            admin: *admin,
+            config: pda::config(),
+            treasury: pda::treasury(),
+            system_program: system_program::ID,
+        },
+        data::MigrateConfig { fee_bps },
+        &[],
+    )
+}
+
+pub fn withdraw_treasury(admin: &Pubkey, amount: u64, currency: Option<&Currency>) -> Instruction {
+    let treasury = pda::treasury();
+    instruction(
+        accounts::WithdrawTreasury {
+            admin: *admin,
+            config: pda::config(),
+            treasury,
+            currency_mint: currency.map(|currency| currency.mint),
+            admin_currency_account: currency.map(|currency| currency.account(admin)),
+            treasury_currency_account: currency.map(|currency| currency.account(&treasury)),
+            currency_token_program: currency.map(|currency| currency.token_program),
+            system_program: system_program::ID,
+        },
+        data::WithdrawTreasury { amount },
+        &[],
+    )
+}
+
+pub fn add_collection(admin: &Pubkey, collection_mint: &Pubkey) -> Instruction {
+    instruction(
+        accounts::AddCollection {
+            admin: *admin,
+            config: pda::config(),
+            collection_mint: *collection_mint,
+            approved_collection: pda::approved_collection(collection_mint),
+            system_program: system_program::ID,
+        },
+        data::AddCollection {},
+        &[],
+    )
+}
+
+pub fn remove_collection(admin: &Pubkey, collection_mint: &Pubkey) -> Instruction {
+    instruction(
+        accounts::RemoveCollection {
+            admin: *admin,
+            config: pda::config(),
+            approved_collection: pda::approved_collection(collection_mint),
+        },
+        data::RemoveCollection {},
+        &[],
+    )
+}
+
+pub fn add_creator(admin: &Pubkey, creator: &Pubkey) -> Instruction {
+    instruction(
+        accounts::AddCreator {
+            admin: *admin,
+            config: pda::config(),
+            approved_creator: pda::approved_creator(creator),
+            system_program: system_program::ID,
+        },
+        data::AddCreator { creator: *creator },
+        &[],
+    )
+}
+
+pub fn remove_creator(admin: &Pubkey, creator: &Pubkey) -> Instruction {
+    instruction(
+        accounts::RemoveCreator {
+            admin: *admin,
+            config: pda::config(),
+            approved_creator: pda::approved_creator(creator),
+        },
+        data::RemoveCreator {},
+        &[],
+    )
+}
+
+/// Puts the NFT `mint` of `payer` up for auction. `id` must be the `count` of the auction counter
+/// of the NFT, or 0 for its first auction.
+pub fn create_auction(payer: &Pubkey, mint: &Pubkey, id: u64, approval: Approval, params: AuctionParams, currency: Option<&Currency>) -> Instruction {
+    let auction = pda::auction(mint, id);
+    let (approved_collection, approved_creator) = approval.accounts();
+    instruction(
+        accounts::CreateAuction {
+            payer: *payer,
+            mint: *mint,
+            approved_collection,
+            approved_creator,
+            mint_ata: Some(pda::associated_token_account(payer, mint, &token::ID)),
+            metadata: Some(pda::metadata(mint)),
+            edition: Some(pda::edition(mint)),
+            auction_counter: pda::auction_counter(mint),
+            auction,
+            vault: pda::vault(&auction),
+            vault_state: pda::vault_state(&auction),
+            escrow: None,
+            config: pda::config(),
+            currency_mint: currency.map(|currency| currency.mint),
+            vault_currency_account: currency.map(|currency| currency.account(&pda::vault(&auction))),
+            currency_token_program: currency.map(|currency| currency.token_program),
+            system_program: system_program::ID,
+            token_program: token::ID,
+            metadata_program: metadata::ID,
+            associated_token_program: associated_token::ID,
+            programmable: no_programmable(),
+            core: no_core(),
+            compressed: no_compressed(),
+        },
+        data::CreateAuction {
+            start_time: params.start_time,
+            deadline: params.deadline,
+            min_price: params.min_price,
+            min_increment: params.min_increment,
+            soft_close: params.soft_close,
+            kind: params.kind,
+            buy_now_price: params.buy_now_price,
+            leaf: None,
+        },
+        &[],
+    )
+}
+
+/// Changes the terms of the auction `id` of `mint` before any bid. Terms left to `None` are kept.
+pub fn update_auction(
+    maker: &Pubkey,
+    mint: &Pubkey,
+    id: u64,
+    start_time: Option<i64>,
+    deadline: Option<i64>,
+    min_price: Option<u64>,
+    min_increment: Option<u64>,
+) -> Instruction {
+    instruction(
+        accounts::UpdateAuction {
+            maker: *maker,
+            auction: pda::auction(mint, id),
+            config: pda::config(),
+        },
+        data::UpdateAuction {
+            start_time,
+            deadline,
+            min_price,
+            min_increment,
+        },
+        &[],
+    )
+}
+
+/// Adds the NFT `mint` of `maker` to the lot of the auction `id` of `auction_mint`.
+pub fn add_lot_item(maker: &Pubkey, auction_mint: &Pubkey, id: u64, mint: &Pubkey, approval: Approval) -> Instruction {
+    let auction = pda::auction(auction_mint, id);
+    let (approved_collection, approved_creator) = approval.accounts();
+    instruction(
+        accounts::AddLotItem {
+            maker: *maker,
+            auction_mint: *auction_mint,
+            auction,
+            lot: pda::lot(&auction),
+            mint: *mint,
+            mint_ata: pda::associated_token_account(maker, mint, &token::ID),
+            metadata: pda::metadata(mint),
+            edition: pda::edition(mint),
+            approved_collection,
+            approved_creator,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            metadata_program: metadata::ID,
+        },
+        data::AddLotItem {},
+        &[],
+    )
+}
+
+pub fn bid(bidder: &Pubkey, mint: &Pubkey, id: u64, amount: u64, currency: Option<&Currency>) -> Instruction {
+    let auction = pda::auction(mint, id);
+    let (currency_mint, bidder_currency_account, vault_currency_account, currency_token_program) =
+        currency_accounts(currency, bidder, &auction);
+    instruction(
+        accounts::Bid {
+            bidder: *bidder,
+            mint: *mint,
+            auction,
+            vault: pda::vault(&auction),
+            vault_state: pda::vault_state(&auction),
+            refund_ledger: pda::refund_ledger(&auction, bidder),
+            currency_mint,
+            bidder_currency_account,
+            vault_currency_account,
+            currency_token_program,
+            system_program: system_program::ID,
+        },
+        data::Bid { amount },
+        &[],
+    )
+}
+
+/// Refunds `bidder` the bids it has been outbid on. Anyone can sign.
+pub fn withdraw_refund(signer: &Pubkey, bidder: &Pubkey, mint: &Pubkey, id: u64, currency: Option<&Currency>) -> Instruction {
+    let auction = pda::auction(mint, id);
+    let (currency_mint, bidder_currency_account, vault_currency_account, currency_token_program) =
+        currency_accounts(currency, bidder, &auction);
+    instruction(
+        accounts::WithdrawRefund {
+            signer: *signer,
+            bidder: *bidder,
+            mint: *mint,
+            auction,
+            vault: pda::vault(&auction),
+            vault_state: pda::vault_state(&auction),
+            refund_ledger: pda::refund_ledger(&auction, bidder),
+            currency_mint,
+            bidder_currency_account,
+            vault_currency_account,
+            currency_token_program,
+            system_program: system_program::ID,
+        },
+        data::WithdrawRefund {},
+        &[],
+    )
+}
+
+/// Commits `bidder` to a sealed bid. `commitment` is
+/// [`SealedBid::commitment`](crate::SealedBid::commitment) of the auction address, the bidder,
+/// the amount and a secret salt.
+pub fn commit_bid(bidder: &Pubkey, mint: &Pubkey, id: u64, commitment: [u8; 32], deposit: u64, currency: Option<&Currency>) -> Instruction {
+    let auction = pda::auction(mint, id);
+    let (currency_mint, bidder_currency_account, vault_currency_account, currency_token_program) =
+        currency_accounts(currency, bidder, &auction);
+    instruction(
+        accounts::CommitBid {
+            bidder: *bidder,
+            mint: *mint,
+            auction,
+            vault: pda::vault(&auction),
+            vault_state: pda::vault_state(&auction),
+            sealed_bid: pda::sealed_bid(&auction, bidder),
+            currency_mint,
+            bidder_currency_account,
+            vault_currency_account,
+            currency_token_program,
+            system_program: system_program::ID,
+        },
+        data::CommitBid { commitment, deposit },
+        &[],
+    )
+}
+
+pub fn reveal_bid(bidder: &Pubkey, mint: &Pubkey, id: u64, amount: u64, salt: [u8; 32]) -> Instruction {
+    let auction = pda::auction(mint, id);
+    instruction(
+        accounts::RevealBid {
+            bidder: *bidder,
+            mint: *mint,
+            auction,
+            sealed_bid: pda::sealed_bid(&auction, bidder),
+        },
+        data::RevealBid { amount, salt },
+        &[],
+    )
+}
+
+/// Returns the deposit of `bidder` on a sealed-bid auction. Anyone can sign.
+pub fn withdraw_deposit(signer: &Pubkey, bidder: &Pubkey, maker: &Pubkey, mint: &Pubkey, id: u64, currency: Option<&Currency>) -> Instruction {
+    let auction = pda::auction(mint, id);
+    let (currency_mint, bidder_currency_account, vault_currency_account, currency_token_program) =
+        currency_accounts(currency, bidder, &auction);
+    instruction(
+        accounts::WithdrawDeposit {
+            signer: *signer,
+            bidder: *bidder,
+            maker: *maker,
+            mint: *mint,
+            auction,
+            vault: pda::vault(&auction),
+            vault_state: pda::vault_state(&auction),
+            sealed_bid: pda::sealed_bid(&auction, bidder),
+            currency_mint,
+            bidder_currency_account,
+            maker_currency_account: currency.map(|currency| currency.account(maker)),
+            vault_currency_account,
+            currency_token_program,
+            system_program: system_program::ID,
+        },
+        data::WithdrawDeposit {},
+        &[],
+    )
+}
+
+/// Buys the NFT at the current price of a Dutch auction, or at the buy now price. `creators` are
+/// the creators recorded on the auction, in the same order.
+#[allow(clippy::too_many_arguments)]
+pub fn buy(
+    buyer: &Pubkey,
+    maker: &Pubkey,
+    mint: &Pubkey,
+    id: u64,
+    standard: NftStandard,
+    max_price: u64,
+    creators: &[Pubkey],
+    currency: Option<&Currency>,
+) -> Result<Instruction, UnsupportedStandard> {
+    require_legacy(standard)?;
+    let auction = pda::auction(mint, id);
+    let treasury = pda::treasury();
+    Ok(instruction(
+        accounts::Buy {
+            buyer: *buyer,
+            buyer_ata: Some(pda::associated_token_account(buyer, mint, &token::ID)),
+            maker: *maker,
+            maker_ata: Some(pda::associated_token_account(maker, mint, &token::ID)),
+            mint: *mint,
+            metadata: None,
+            edition: Some(pda::edition(mint)),
+            auction,
+            escrow: None,
+            config: pda::config(),
+            treasury,
+            currency_mint: currency.map(|currency| currency.mint),
+            buyer_currency_account: currency.map(|currency| currency.account(buyer)),
+            maker_currency_account: currency.map(|currency| currency.account(maker)),
+            treasury_currency_account: currency.map(|currency| currency.account(&treasury)),
+            currency_token_program: currency.map(|currency| currency.token_program),
+            system_program: system_program::ID,
+            token_program: token::ID,
+            metadata_program: metadata::ID,
+            associated_token_program: associated_token::ID,
+            programmable: no_programmable(),
+            core: no_core(),
+            compressed: no_compressed(),
+        },
+        data::Buy { max_price, root: None },
+        &royalty_accounts(creators, currency),
+    ))
+}
+
+/// Pays the proceeds of the auction to its maker. `creators` are the creators recorded on the
+/// auction, in the same order.
+pub fn claim_proceeds(maker: &Pubkey, mint: &Pubkey, id: u64, creators: &[Pubkey], currency: Option<&Currency>) -> Instruction {
+    let auction = pda::auction(mint, id);
+    let treasury = pda::treasury();
+    let (currency_mint, signer_currency_account, vault_currency_account, currency_token_program) =
+        currency_accounts(currency, maker, &auction);
+    instruction(
+        accounts::ClaimProceeds {
+            signer: *maker,
+            mint: *mint,
+            auction,
+            vault: pda::vault(&auction),
+            vault_state: pda::vault_state(&auction),
+            config: pda::config(),
+            treasury,
+            currency_mint,
+            signer_currency_account,
+            vault_currency_account,
+            treasury_currency_account: currency.map(|currency| currency.account(&treasury)),
+            currency_token_program,
+            system_program: system_program::ID,
+            associated_token_program: associated_token::ID,
+        },
+        data::ClaimProceeds {},
+        &royalty_accounts(creators, currency),
+    )
+}
+
+/// Delivers the NFT to `signer`, the winner of the auction or its maker if there were no bids.
+/// For auctions with a lot, `lot_items` are the mints of the items to deliver in this claim, which
+/// can be some of them only.
+pub fn claim_nft(
+    signer: &Pubkey,
+    maker: &Pubkey,
+    mint: &Pubkey,
+    id: u64,
+    standard: NftStandard,
+    lot_items: Option<&[Pubkey]>,
+) -> Result<Instruction, UnsupportedStandard> {
+    require_legacy(standard)?;
+    let auction = pda::auction(mint, id);
+    let to_winner = signer != maker;
+    let remaining_accounts: Vec<AccountMeta> = lot_items
+        .unwrap_or_default()
+        .iter()
+        .flat_map(|item| {
+            let mut metas = lot_item_accounts(maker, item);
+            if to_winner {
+                metas.push(AccountMeta::new(pda::associated_token_account(signer, item, &token::ID), false));
+            }
+            metas
+        })
+        .collect();
+    Ok(instruction(
+        accounts::ClaimNFT {
+            signer: *signer,
+            signer_ata: Some(pda::associated_token_account(signer, mint, &token::ID)),
+            auctioneer: *maker,
+            auctioneer_ata: Some(pda::associated_token_account(maker, mint, &token::ID)),
+            mint: *mint,
+            metadata: None,
+            edition: Some(pda::edition(mint)),
+            auction,
+            vault: pda::vault(&auction),
+            vault_state: pda::vault_state(&auction),
+            escrow: None,
+            lot: lot_items.map(|_| pda::lot(&auction)),
+            system_program: system_program::ID,
+            token_program: token::ID,
+            metadata_program: metadata::ID,
+            associated_token_program: associated_token::ID,
+            programmable: no_programmable(),
+            core: no_core(),
+            compressed: no_compressed(),
+        },
+        data::ClaimNft { root: None },
+        &remaining_accounts,
+    ))
+}
+
+/// Delivers the NFT to the winner and pays the maker, whichever is still owed, on behalf of the
+/// parties of an ended auction. `creators` are the creators recorded on the auction, in the same
+/// order. Auctions of Core assets and auctions with a lot cannot be settled this way.
+#[allow(clippy::too_many_arguments)]
+pub fn settle(
+    cranker: &Pubkey,
+    winner: &Pubkey,
+    maker: &Pubkey,
+    mint: &Pubkey,
+    id: u64,
+    standard: NftStandard,
+    creators: &[Pubkey],
+    currency: Option<&Currency>,
+) -> Result<Instruction, UnsupportedStandard> {
+    require_legacy(standard)?;
+    let auction = pda::auction(mint, id);
+    let treasury = pda::treasury();
+    Ok(instruction(
+        accounts::Settle {
+            cranker: *cranker,
+            winner: *winner,
+            winner_ata: Some(pda::associated_token_account(winner, mint, &token::ID)),
+            maker: *maker,
+            maker_ata: Some(pda::associated_token_account(maker, mint, &token::ID)),
+            mint: *mint,
+            metadata: None,
+            edition: Some(pda::edition(mint)),
+            auction,
+            vault: pda::vault(&auction),
+            vault_state: pda::vault_state(&auction),
+            escrow: None,
+            config: pda::config(),
+            treasury,
+            currency_mint: currency.map(|currency| currency.mint),
+            vault_currency_account: currency.map(|currency| currency.account(&pda::vault(&auction))),
+            maker_currency_account: currency.map(|currency| currency.account(maker)),
+            cranker_currency_account: currency.map(|currency| currency.account(cranker)),
+            treasury_currency_account: currency.map(|currency| currency.account(&treasury)),
+            currency_token_program: currency.map(|currency| currency.token_program),
+            system_program: system_program::ID,
+            token_program: token::ID,
+            metadata_program: metadata::ID,
+            associated_token_program: associated_token::ID,
+            programmable: no_programmable(),
+            compressed: no_compressed(),
+        },
+        data::Settle { root: None },
+        &royalty_accounts(creators, currency),
+    ))
+}
+
+/// Cancels an auction without bids and hands the NFT back to its maker. For auctions with a lot,
+/// `lot_items` are the mints of the items to hand back in this call, which can be some of them
+/// only: the auction is cancelled once every item is back.
+pub fn cancel_auction(
+    maker: &Pubkey,
+    mint: &Pubkey,
+    id: u64,
+    standard: NftStandard,
+    lot_items: Option<&[Pubkey]>,
+) -> Result<Instruction, UnsupportedStandard> {
+    require_legacy(standard)?;
+    let auction = pda::auction(mint, id);
+    let remaining_accounts: Vec<AccountMeta> = lot_items
+        .unwrap_or_default()
+        .iter()
+        .flat_map(|item| lot_item_accounts(maker, item))
+        .collect();
+    Ok(instruction(
+        accounts::CancelAuction {
+            maker: *maker,
+            mint: *mint,
+            metadata: None,
+            mint_ata: Some(pda::associated_token_account(maker, mint, &token::ID)),
+            edition: Some(pda::edition(mint)),
+            auction,
+            escrow: None,
+            lot: lot_items.map(|_| pda::lot(&auction)),
+            system_program: system_program::ID,
+            token_program: token::ID,
+            metadata_program: metadata::ID,
+            programmable: no_programmable(),
+            core: no_core(),
+            compressed: no_compressed(),
+        },
+        data::CancelAuction { root: None },
+        &remaining_accounts,
+    ))
+}
+
+/// Aborts the auction `id` of the compressed NFT `mint` whose maker did not deliver it in time.
+/// Only the winner can sign.
+pub fn abort_auction(winner: &Pubkey, mint: &Pubkey, id: u64) -> Instruction {
+    instruction(
+        accounts::AbortAuction {
+            winner: *winner,
+            mint: *mint,
+            auction: pda::auction(mint, id),
+            config: pda::config(),
+        },
+        data::AbortAuction {},
+        &[],
+    )
+}
+
+/// Closes the vault, vault state and lot of a settled auction, returning their rent to the maker.
+/// The auction account stays open as a record of the sale. `lot` tells whether the auction has a
+/// lot.
+pub fn close_auction(maker: &Pubkey, mint: &Pubkey, id: u64, lot: bool, currency: Option<&Currency>) -> Instruction {
+    let auction = pda::auction(mint, id);
+    let (currency_mint, maker_currency_account, vault_currency_account, currency_token_program) =
+        currency_accounts(currency, maker, &auction);
+    instruction(
+        accounts::CloseAuction {
+            maker: *maker,
+            mint: *mint,
+            auction,
+            vault: pda::vault(&auction),
+            vault_state: pda::vault_state(&auction),
+            lot: lot.then(|| pda::lot(&auction)),
+            currency_mint,
+            maker_currency_account,
+            vault_currency_account,
+            currency_token_program,
+            system_program: system_program::ID,
+        },
+        data::CloseAuction {},
+        &[],
+    )
+}
+
+/// Mint, master edition and token account of the maker of an item of a lot.
+fn lot_item_accounts(maker: &Pubkey, item: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(*item, false),
+        AccountMeta::new_readonly(pda::edition(item), false),
+        AccountMeta::new(pda::associated_token_account(maker, item, &token::ID), false),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::Discriminator;
+
+    use super::*;
+
+    fn keys(instruction: &Instruction) -> Vec<Pubkey> {
+        instruction.accounts.iter().map(|meta| meta.pubkey).collect()
+    }
+
+    #[test]
+    fn bid_accounts() {
+        let bidder = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let auction = pda::auction(&mint, 3);
+        let ix = bid(&bidder, &mint, 3, 100, None);
+
+        // omitted optional accounts are replaced by the program id
+        assert_eq!(
+            keys(&ix),
+            [
+                bidder,
+                mint,
+                auction,
+                pda::vault(&auction),
+                pda::vault_state(&auction),
+                pda::refund_ledger(&auction, &bidder),
+                ID,
+                ID,
+                ID,
+                ID,
+                system_program::ID,
+            ],
+        );
+        assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+        assert!(ix.accounts[2].is_writable);
+        assert_eq!(ix.data[..8], data::Bid::DISCRIMINATOR);
+        assert_eq!(ix.data[8..], 100u64.to_le_bytes());
+    }
+
+    #[test]
+    fn bid_in_a_token() {
+        let bidder = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let currency = Currency {
+            mint: Pubkey::new_unique(),
+            token_program: token::ID,
+        };
+        let auction = pda::auction(&mint, 0);
+        let ix = bid(&bidder, &mint, 0, 100, Some(&currency));
+
+        assert_eq!(
+            keys(&ix)[6..10],
+            [
+                currency.mint,
+                pda::associated_token_account(&bidder, &currency.mint, &token::ID),
+                pda::associated_token_account(&pda::vault(&auction), &currency.mint, &token::ID),
+                token::ID,
+            ],
+        );
+    }
+
+    #[test]
+    fn lot_items_follow_the_accounts_of_the_instruction() {
+        let winner = Pubkey::new_unique();
+        let maker = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let item = Pubkey::new_unique();
+        let auction = pda::auction(&mint, 0);
+
+        let ix = claim_nft(&winner, &maker, &mint, 0, NftStandard::Legacy, Some(&[item])).unwrap();
+        let fixed = ix.accounts.len() - 4;
+        assert!(keys(&ix)[..fixed].contains(&pda::lot(&auction)));
+        assert_eq!(
+            keys(&ix)[fixed..],
+            [
+                item,
+                pda::edition(&item),
+                pda::associated_token_account(&maker, &item, &token::ID),
+                pda::associated_token_account(&winner, &item, &token::ID),
+            ],
+        );
+
+        let ix = cancel_auction(&maker, &mint, 0, NftStandard::Legacy, Some(&[item])).unwrap();
+        let fixed = ix.accounts.len() - 3;
+        assert!(keys(&ix)[..fixed].contains(&pda::lot(&auction)));
+        assert_eq!(
+            keys(&ix)[fixed..],
+            [item, pda::edition(&item), pda::associated_token_account(&maker, &item, &token::ID)],
+        );
+
+        let ix = cancel_auction(&maker, &mint, 0, NftStandard::Legacy, None).unwrap();
+        assert!(!keys(&ix).contains(&pda::lot(&auction)));
+    }
+
+    #[test]
+    fn only_legacy_accounts_are_derived() {
+        let signer = Pubkey::new_unique();
+        let maker = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        for standard in [NftStandard::Programmable, NftStandard::Token2022, NftStandard::Core, NftStandard::Compressed] {
+            assert_eq!(claim_nft(&signer, &maker, &mint, 0, standard, None), Err(UnsupportedStandard(standard)));
+            assert_eq!(cancel_auction(&maker, &mint, 0, standard, None), Err(UnsupportedStandard(standard)));
+            assert_eq!(buy(&signer, &maker, &mint, 0, standard, 100, &[], None), Err(UnsupportedStandard(standard)));
+            assert_eq!(settle(&signer, &signer, &maker, &mint, 0, standard, &[], None), Err(UnsupportedStandard(standard)));
+        }
+    }
+
+    #[test]
+    fn royalties_follow_the_accounts_of_the_instruction() {
+        let maker = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let creators = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        let ix = claim_proceeds(&maker, &mint, 0, &creators, None);
+        let accounts = &ix.accounts[ix.accounts.len() - 2..];
+        assert_eq!(accounts.iter().map(|meta| meta.pubkey).collect::<Vec<_>>(), creators);
+        assert!(accounts.iter().all(|meta| meta.is_writable && !meta.is_signer));
+    }
+}
@@ -0,0 +1,10 @@
+//! Client library of the nectart-auctions program: instruction builders, addresses of its
+//! accounts, decoders of its accounts and of its errors.
+
+pub mod accounts;
+pub mod errors;
+pub mod instructions;
+pub mod pda;
+
+pub use nectart_auctions::state::*;
+pub use nectart_auctions::{events, AuctionKind, LeafProof, NftStandard, PriceCurve, SoftClose, ID};
@@ -0,0 +1,95 @@
+//! Addresses of the accounts of the program and of the Token Metadata accounts it reads.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::metadata::mpl_token_metadata::accounts::{MasterEdition, Metadata};
+
+use nectart_auctions::ID;
+
+pub fn config() -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], &ID).0
+}
+
+pub fn treasury() -> Pubkey {
+    Pubkey::find_program_address(&[b"treasury"], &ID).0
+}
+
+pub fn approved_collection(collection_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"collection", collection_mint.as_ref()], &ID).0
+}
+
+pub fn approved_creator(creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"creator", creator.as_ref()], &ID).0
+}
+
+/// Counter of the auctions of an NFT, whose `count` is the id of the next one.
+pub fn auction_counter(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"counter", mint.as_ref()], &ID).0
+}
+
+/// Auction `id` of an NFT. `mint` is the mint of the NFT, the address of a Core asset or the asset
+/// id of a compressed NFT.
+pub fn auction(mint: &Pubkey, id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"auction", mint.as_ref(), &id.to_le_bytes()], &ID).0
+}
+
+pub fn vault(auction: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"vault", auction.as_ref()], &ID).0
+}
+
+pub fn vault_state(auction: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"state", auction.as_ref()], &ID).0
+}
+
+pub fn lot(auction: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"lot", auction.as_ref()], &ID).0
+}
+
+pub fn refund_ledger(auction: &Pubkey, bidder: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"refund", auction.as_ref(), bidder.as_ref()], &ID).0
+}
+
+pub fn sealed_bid(auction: &Pubkey, bidder: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"sealed_bid", auction.as_ref(), bidder.as_ref()], &ID).0
+}
+
+/// Token Metadata account of an NFT.
+pub fn metadata(mint: &Pubkey) -> Pubkey {
+    Metadata::find_pda(mint).0
+}
+
+/// Token Metadata master edition of an NFT.
+pub fn edition(mint: &Pubkey) -> Pubkey {
+    MasterEdition::find_pda(mint).0
+}
+
+pub fn associated_token_account(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auction_is_seeded_by_the_id_in_little_endian() {
+        let mint = Pubkey::new_unique();
+        let expected = Pubkey::find_program_address(&[b"auction", mint.as_ref(), &[1, 0, 0, 0, 0, 0, 0, 0]], &ID).0;
+        assert_eq!(auction(&mint, 1), expected);
+        assert_ne!(auction(&mint, 1), auction(&mint, 1 << 56));
+    }
+
+    #[test]
+    fn auction_accounts_are_seeded_by_the_auction() {
+        let mint = Pubkey::new_unique();
+        let bidder = Pubkey::new_unique();
+        let auction = auction(&mint, 0);
+        let derive = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &ID).0;
+        assert_eq!(vault(&auction), derive(&[b"vault", auction.as_ref()]));
+        assert_eq!(vault_state(&auction), derive(&[b"state", auction.as_ref()]));
+        assert_eq!(lot(&auction), derive(&[b"lot", auction.as_ref()]));
+        assert_eq!(refund_ledger(&auction, &bidder), derive(&[b"refund", auction.as_ref(), bidder.as_ref()]));
+        assert_eq!(sealed_bid(&auction, &bidder), derive(&[b"sealed_bid", auction.as_ref(), bidder.as_ref()]));
+        assert_ne!(vault(&auction), derive(&[b"vault", mint.as_ref()]));
+    }
+}
@@ -0,0 +1,477 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::{
+    metadata::mpl_token_metadata::instructions::{
+        FreezeDelegatedAccountCpi, FreezeDelegatedAccountCpiAccounts, RevokeSaleV1Cpi,
+        RevokeSaleV1CpiAccounts, ThawDelegatedAccountCpi,
+        ThawDelegatedAccountCpiAccounts, TransferV1Cpi, TransferV1CpiAccounts,
+        TransferV1InstructionArgs,
+    },
+    token_interface::{
+        close_account, revoke, transfer_checked, CloseAccount, Revoke, TokenAccount,
+        TransferChecked,
+    },
+};
+use mpl_bubblegum::{
+    hash::{hash_creators, hash_metadata},
+    instructions::{
+        DelegateCpi, DelegateCpiAccounts, DelegateInstructionArgs, TransferCpi,
+        TransferCpiAccounts, TransferInstructionArgs,
+    },
+    programs::{SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID},
+    types::MetadataArgs,
+    utils::get_asset_id,
+};
+use mpl_core::{
+    instructions::{
+        AddPluginV1CpiBuilder, RemovePluginV1CpiBuilder, TransferV1CpiBuilder,
+        UpdatePluginV1CpiBuilder,
+    },
+    types::{FreezeDelegate, Plugin, PluginAuthority, PluginType, TransferDelegate},
+};
+
+use crate::state::{Auction, CompressedLeaf, LeafProof};
+use crate::errors::AuctionError;
+
+/// Returns an account that is only needed by some NFT standards, failing if it is missing.
+pub fn required<T>(account: &Option<T>) -> Result<&T> {
+    account.as_ref().ok_or(error!(AuctionError::BadAccount))
+}
+
+/// Checks `token_account`, passed as a remaining account, holds the NFT of `mint` for `owner`.
+pub fn check_item_account<'info>(
+    token_account: &'info AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    owner: &Pubkey,
+) -> Result<()> {
+    let token_account = InterfaceAccount::<TokenAccount>::try_from(token_account)?;
+    require_keys_eq!(token_account.owner, *owner, AuctionError::BadAccount);
+    require_keys_eq!(token_account.mint, mint.key(), AuctionError::BadAccount);
+    Ok(())
+}
+
+/// Freezes the maker's token account delegated to the auction, so that the NFT cannot leave it
+/// until the auction thaws it. The auction PDA signs as the freeze delegate.
+pub fn freeze_nft<'info>(
+    auction: &Account<'info, Auction>,
+    token_account: &AccountInfo<'info>,
+    edition: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    metadata_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let seeds = auction.signer_seeds();
+    let signer_seeds = &[&seeds.as_seeds()[..]];
+
+    let delegate = &auction.to_account_info();
+    FreezeDelegatedAccountCpi::new(
+        metadata_program,
+        FreezeDelegatedAccountCpiAccounts {
+            delegate,
+            token_account,
+            edition,
+            mint,
+            token_program,
+        },
+    ).invoke_signed(signer_seeds)?;
+    Ok(())
+}
+
+/// Thaws the token account frozen at auction creation. The auction PDA signs as the freeze
+/// delegate.
+pub fn thaw_nft<'info>(
+    auction: &Account<'info, Auction>,
+    token_account: &AccountInfo<'info>,
+    edition: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    metadata_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let seeds = auction.signer_seeds();
+    let signer_seeds = &[&seeds.as_seeds()[..]];
+
+    let delegate = &auction.to_account_info();
+    ThawDelegatedAccountCpi::new(
+        metadata_program,
+        ThawDelegatedAccountCpiAccounts {
+            delegate,
+            token_account,
+            edition,
+            mint,
+            token_program,
+        },
+    ).invoke_signed(signer_seeds)?;
+    Ok(())
+}
+
+/// Thaws the maker's token account and moves the NFT to `to`, using the delegation granted to the
+/// auction PDA at creation.
+pub fn release_nft<'info>(
+    auction: &Account<'info, Auction>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    edition: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    metadata_program: &AccountInfo<'info>,
+) -> Result<()> {
+    thaw_nft(auction, from, edition, mint, token_program, metadata_program)?;
+
+    let seeds = auction.signer_seeds();
+    let signer_seeds = &[&seeds.as_seeds()[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.clone(),
+        TransferChecked {
+            from: from.clone(),
+            mint: mint.clone(),
+            to: to.clone(),
+            authority: auction.to_account_info(),
+        },
+        signer_seeds
+    );
+    transfer_checked(cpi_ctx, 1, 0)
+}
+
+/// Thaws the owner's token account and revokes the delegation of the auction, which hands the NFT
+/// back to its owner. The owner signs.
+pub fn return_nft<'info>(
+    auction: &Account<'info, Auction>,
+    token_account: &AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+    edition: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    metadata_program: &AccountInfo<'info>,
+) -> Result<()> {
+    thaw_nft(auction, token_account, edition, mint, token_program, metadata_program)?;
+    let cpi_ctx = CpiContext::new(
+        token_program.clone(),
+        Revoke {
+            source: token_account.clone(),
+            authority: owner.clone(),
+        },
+    );
+    revoke(cpi_ctx)
+}
+
+/// Moves a Token-2022 NFT out of the escrow of the auction to `to`, then closes the escrow and
+/// returns its rent to the maker.
+pub fn release_escrowed_nft<'info>(
+    auction: &Account<'info, Auction>,
+    escrow: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    maker: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let seeds = auction.signer_seeds();
+    let signer_seeds = &[&seeds.as_seeds()[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.clone(),
+        TransferChecked {
+            from: escrow.clone(),
+            mint: mint.clone(),
+            to: to.clone(),
+            authority: auction.to_account_info(),
+        },
+        signer_seeds
+    );
+    transfer_checked(cpi_ctx, 1, 0)?;
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.clone(),
+        CloseAccount {
+            account: escrow.clone(),
+            destination: maker.clone(),
+            authority: auction.to_account_info(),
+        },
+        signer_seeds
+    );
+    close_account(cpi_ctx)
+}
+
+/// Token Metadata accounts only needed for programmable NFTs.
+#[derive(Accounts)]
+pub struct ProgrammableNft<'info> {
+    /// CHECK: token record of the maker's token account, checked by Token Metadata
+    #[account(mut)]
+    pub token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: token record of the receiving token account, checked by Token Metadata
+    #[account(mut)]
+    pub destination_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: rule set of the NFT, checked by Token Metadata
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked by Token Metadata
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: the instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+}
+
+impl<'info> ProgrammableNft<'info> {
+    pub fn sysvar_instructions(&self) -> Result<&AccountInfo<'info>> {
+        self.sysvar_instructions
+            .as_deref()
+            .ok_or(error!(AuctionError::BadAccount))
+    }
+}
+
+/// Transfers a programmable NFT with Token Metadata. The auction PDA signs as the sale delegate.
+pub fn transfer_programmable_nft<'a, 'info>(
+    auction: &Account<'info, Auction>,
+    metadata_program: &'a AccountInfo<'info>,
+    accounts: TransferV1CpiAccounts<'info, 'a>,
+) -> Result<()> {
+    let seeds = auction.signer_seeds();
+    let signer_seeds = &[&seeds.as_seeds()[..]];
+
+    TransferV1Cpi::new(
+        metadata_program,
+        accounts,
+        TransferV1InstructionArgs {
+            amount: 1,
+            authorization_data: None,
+        },
+    ).invoke_signed(signer_seeds)?;
+    Ok(())
+}
+
+/// Revokes the sale delegation of a programmable NFT, which hands it back to the maker.
+pub fn revoke_programmable_nft<'a, 'info>(
+    metadata_program: &'a AccountInfo<'info>,
+    accounts: RevokeSaleV1CpiAccounts<'info, 'a>,
+) -> Result<()> {
+    RevokeSaleV1Cpi::new(metadata_program, accounts).invoke()?;
+    Ok(())
+}
+
+/// Metaplex Core accounts only needed for Core assets.
+#[derive(Accounts)]
+pub struct CoreAsset<'info> {
+    /// CHECK: collection of the asset, checked by Metaplex Core
+    #[account(mut)]
+    pub collection: Option<UncheckedAccount<'info>>,
+    /// CHECK: the Metaplex Core program
+    #[account(address = mpl_core::ID)]
+    pub core_program: Option<UncheckedAccount<'info>>,
+}
+
+impl<'info> CoreAsset<'info> {
+    /// Adds a frozen freeze delegate and a transfer delegate, both owned by the auction PDA, to
+    /// the asset. The owner signs.
+    pub fn delegate(
+        &self,
+        auction: &Account<'info, Auction>,
+        asset: &AccountInfo<'info>,
+        owner: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+    ) -> Result<()> {
+        let core_program = required(&self.core_program)?;
+        for plugin in [
+            Plugin::TransferDelegate(TransferDelegate {}),
+            Plugin::FreezeDelegate(FreezeDelegate { frozen: true }),
+        ] {
+            AddPluginV1CpiBuilder::new(core_program)
+                .asset(asset)
+                .collection(self.collection.as_deref())
+                .payer(owner)
+                .authority(Some(owner))
+                .system_program(system_program)
+                .plugin(plugin)
+                .init_authority(PluginAuthority::Address { address: auction.key() })
+                .invoke()?;
+        }
+        Ok(())
+    }
+
+    /// Unfreezes the asset. The auction PDA signs as the freeze delegate.
+    pub fn thaw(
+        &self,
+        auction: &Account<'info, Auction>,
+        asset: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+    ) -> Result<()> {
+        let seeds = auction.signer_seeds();
+        let signer_seeds = &[&seeds.as_seeds()[..]];
+
+        UpdatePluginV1CpiBuilder::new(required(&self.core_program)?)
+            .asset(asset)
+            .collection(self.collection.as_deref())
+            .payer(payer)
+            .authority(Some(&auction.to_account_info()))
+            .system_program(system_program)
+            .plugin(Plugin::FreezeDelegate(FreezeDelegate { frozen: false }))
+            .invoke_signed(signer_seeds)?;
+        Ok(())
+    }
+
+    /// Transfers the unfrozen asset to `new_owner`. The auction PDA signs as the transfer delegate.
+    pub fn transfer(
+        &self,
+        auction: &Account<'info, Auction>,
+        asset: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        new_owner: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+    ) -> Result<()> {
+        let seeds = auction.signer_seeds();
+        let signer_seeds = &[&seeds.as_seeds()[..]];
+
+        TransferV1CpiBuilder::new(required(&self.core_program)?)
+            .asset(asset)
+            .collection(self.collection.as_deref())
+            .payer(payer)
+            .authority(Some(&auction.to_account_info()))
+            .new_owner(new_owner)
+            .system_program(Some(system_program))
+            .invoke_signed(signer_seeds)?;
+        Ok(())
+    }
+
+    /// Removes the delegates added at auction creation, so the asset can be auctioned again. The
+    /// owner signs.
+    pub fn remove_delegates(
+        &self,
+        asset: &AccountInfo<'info>,
+        owner: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+    ) -> Result<()> {
+        let core_program = required(&self.core_program)?;
+        for plugin_type in [PluginType::FreezeDelegate, PluginType::TransferDelegate] {
+            RemovePluginV1CpiBuilder::new(core_program)
+                .asset(asset)
+                .collection(self.collection.as_deref())
+                .payer(owner)
+                .authority(Some(owner))
+                .system_program(system_program)
+                .plugin_type(plugin_type)
+                .invoke()?;
+        }
+        Ok(())
+    }
+}
+
+/// Bubblegum accounts only needed for compressed NFTs. The proof nodes of the leaf are passed as
+/// remaining accounts.
+#[derive(Accounts)]
+pub struct CompressedNft<'info> {
+    /// CHECK: tree config of the Merkle tree, checked by Bubblegum
+    pub tree_config: Option<UncheckedAccount<'info>>,
+    /// CHECK: Merkle tree holding the leaf, checked by Bubblegum
+    #[account(mut)]
+    pub merkle_tree: Option<UncheckedAccount<'info>>,
+    /// CHECK: the SPL Noop program
+    #[account(address = SPL_NOOP_ID)]
+    pub log_wrapper: Option<UncheckedAccount<'info>>,
+    /// CHECK: the SPL Account Compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: the Bubblegum program
+    #[account(address = mpl_bubblegum::ID)]
+    pub bubblegum_program: Option<UncheckedAccount<'info>>,
+}
+
+/// Decodes the metadata of a compressed NFT given with its proof.
+pub fn leaf_metadata(proof: &LeafProof) -> Result<MetadataArgs> {
+    MetadataArgs::try_from_slice(&proof.metadata).map_err(|_| error!(AuctionError::NotEligible))
+}
+
+impl<'info> CompressedNft<'info> {
+    /// Builds the leaf of the compressed NFT `asset` from its proof. The asset id must derive from
+    /// the Merkle tree and the nonce of the leaf; the rest is checked by Bubblegum.
+    pub fn leaf(&self, asset: &Pubkey, proof: &LeafProof) -> Result<CompressedLeaf> {
+        let merkle_tree = required(&self.merkle_tree)?.key();
+        require_keys_eq!(get_asset_id(&merkle_tree, proof.nonce), *asset, AuctionError::BadAccount);
+        let metadata = leaf_metadata(proof)?;
+        Ok(CompressedLeaf {
+            merkle_tree,
+            data_hash: hash_metadata(&metadata)?,
+            creator_hash: hash_creators(&metadata.creators),
+            nonce: proof.nonce,
+            index: proof.index,
+        })
+    }
+
+    /// Hands the leaf over from `previous_delegate` to `new_delegate`. The owner signs.
+    #[allow(clippy::too_many_arguments)]
+    pub fn delegate(
+        &self,
+        leaf: &CompressedLeaf,
+        root: [u8; 32],
+        owner: &AccountInfo<'info>,
+        previous_delegate: &AccountInfo<'info>,
+        new_delegate: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        proof: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let merkle_tree = required(&self.merkle_tree)?;
+        require_keys_eq!(merkle_tree.key(), leaf.merkle_tree, AuctionError::BadAccount);
+        let nodes: Vec<_> = proof.iter().map(|node| (node, false, false)).collect();
+        DelegateCpi::new(
+            required(&self.bubblegum_program)?,
+            DelegateCpiAccounts {
+                tree_config: required(&self.tree_config)?,
+                leaf_owner: owner,
+                previous_leaf_delegate: previous_delegate,
+                new_leaf_delegate: new_delegate,
+                merkle_tree,
+                log_wrapper: required(&self.log_wrapper)?,
+                compression_program: required(&self.compression_program)?,
+                system_program,
+            },
+            DelegateInstructionArgs {
+                root,
+                data_hash: leaf.data_hash,
+                creator_hash: leaf.creator_hash,
+                nonce: leaf.nonce,
+                index: leaf.index,
+            },
+        ).invoke_with_remaining_accounts(&nodes)?;
+        Ok(())
+    }
+
+    /// Transfers the leaf from the maker to `new_owner`. The auction PDA signs as the leaf
+    /// delegate.
+    pub fn transfer(
+        &self,
+        auction: &Account<'info, Auction>,
+        root: [u8; 32],
+        owner: &AccountInfo<'info>,
+        new_owner: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        proof: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let leaf = required(&auction.leaf)?;
+        let merkle_tree = required(&self.merkle_tree)?;
+        require_keys_eq!(merkle_tree.key(), leaf.merkle_tree, AuctionError::BadAccount);
+
+        let seeds = auction.signer_seeds();
+        let signer_seeds = &[&seeds.as_seeds()[..]];
+
+        let nodes: Vec<_> = proof.iter().map(|node| (node, false, false)).collect();
+        TransferCpi::new(
+            required(&self.bubblegum_program)?,
+            TransferCpiAccounts {
+                tree_config: required(&self.tree_config)?,
+                leaf_owner: (owner, false),
+                leaf_delegate: (&auction.to_account_info(), true),
+                new_leaf_owner: new_owner,
+                merkle_tree,
+                log_wrapper: required(&self.log_wrapper)?,
+                compression_program: required(&self.compression_program)?,
+                system_program,
+            },
+            TransferInstructionArgs {
+                root,
+                data_hash: leaf.data_hash,
+                creator_hash: leaf.creator_hash,
+                nonce: leaf.nonce,
+                index: leaf.index,
+            },
+        ).invoke_signed_with_remaining_accounts(signer_seeds, &nodes)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::MetadataAccount;
+
+use crate::state::{ApprovedCollection, ApprovedCreator};
+use crate::errors::AuctionError;
+
+/// Checks a Token Metadata NFT can be auctioned: either its verified collection or its first
+/// verified creator must have been approved by the admin.
+pub fn check_metadata_eligibility(
+    metadata: &MetadataAccount,
+    approved_collection: Option<&ApprovedCollection>,
+    approved_creator: Option<&ApprovedCreator>,
+) -> Result<()> {
+    let collection = metadata.collection
+        .as_ref()
+        .filter(|collection| collection.verified)
+        .map(|collection| collection.key);
+    let first_verified_creator = metadata.creators
+        .iter()
+        .flatten()
+        .find(|creator| creator.verified)
+        .map(|creator| creator.address);
+    check_approvals(collection, first_verified_creator, approved_collection, approved_creator)
+}
+
+/// Checks either the verified collection or the first verified creator has been approved by the
+/// admin.
+pub fn check_approvals(
+    collection: Option<Pubkey>,
+    first_verified_creator: Option<Pubkey>,
+    approved_collection: Option<&ApprovedCollection>,
+    approved_creator: Option<&ApprovedCreator>,
+) -> Result<()> {
+    let collection_approved = match (collection, approved_collection) {
+        (Some(collection), Some(approved_collection)) => collection == approved_collection.collection_mint,
+        _ => false,
+    };
+    let creator_approved = match (first_verified_creator, approved_creator) {
+        (Some(creator), Some(approved_creator)) => creator == approved_creator.creator,
+        _ => false,
+    };
+    require!(collection_approved || creator_approved, AuctionError::NotEligible);
+    Ok(())
+}
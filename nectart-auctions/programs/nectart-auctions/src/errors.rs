@@ -10,8 +10,102 @@ pub enum AuctionError {
     AuctionNotEnded,
     #[msg("Bid is too low")]
     BidTooLow,
+    /// No longer raised. Kept so that the codes of the following errors do not change: new errors
+    /// go at the end.
     #[msg("Preceding bidder is not correct")]
     BadPrecedingBidder,
     #[msg("Bad account")]
     BadAccount,
+    #[msg("Auction has been cancelled")]
+    AuctionCancelled,
+    #[msg("Auction has bids")]
+    AuctionHasBids,
+    #[msg("Already claimed")]
+    AlreadyClaimed,
+    #[msg("Auction is not settled")]
+    AuctionNotSettled,
+    #[msg("Operation not allowed in the current auction status")]
+    InvalidAuctionStatus,
+    #[msg("Soft close parameters are invalid")]
+    InvalidSoftClose,
+    #[msg("Operation not supported by this kind of auction")]
+    InvalidAuctionKind,
+    #[msg("Price curve parameters are invalid")]
+    InvalidPriceCurve,
+    #[msg("Price is above the maximum accepted by the buyer")]
+    PriceTooHigh,
+    #[msg("Reveal period has not started")]
+    RevealNotStarted,
+    #[msg("Reveal period has ended")]
+    RevealEnded,
+    #[msg("Revealed bid does not match the commitment")]
+    RevealMismatch,
+    #[msg("Bid has already been revealed")]
+    AlreadyRevealed,
+    #[msg("Revealed bid exceeds the deposit")]
+    BidExceedsDeposit,
+    #[msg("Sealed-bid parameters are invalid")]
+    InvalidSealedBid,
+    #[msg("Currency accounts do not match the auction currency")]
+    BadCurrency,
+    #[msg("Nothing to refund")]
+    NothingToRefund,
+    #[msg("Buy now price is invalid")]
+    InvalidBuyNowPrice,
+    #[msg("Protocol fee cannot exceed 100%")]
+    InvalidFee,
+    #[msg("Treasury balance is insufficient")]
+    InsufficientTreasury,
+    #[msg("Creator accounts do not match the royalties of the NFT")]
+    BadCreator,
+    #[msg("NFT is neither from an approved collection nor by an approved creator")]
+    NotEligible,
+    #[msg("NFT cannot be transferred")]
+    NonTransferable,
+    #[msg("Merkle proof of the compressed NFT is missing")]
+    MissingLeafProof,
+    #[msg("NFT must be delivered before the proceeds are claimed")]
+    NftNotDelivered,
+    #[msg("NFT cannot be added to the lot of this auction")]
+    InvalidLot,
+    #[msg("Lot is full")]
+    LotFull,
+    #[msg("Lot items must have the royalties of the auctioned NFT")]
+    RoyaltiesMismatch,
+    #[msg("Items of the lot are missing")]
+    MissingLotItems,
+    #[msg("Crank reward cannot exceed 5%")]
+    InvalidCrankReward,
+    #[msg("Auction has no winner")]
+    NoWinner,
+    #[msg("Auction must be settled by its parties")]
+    NotSettleable,
+    #[msg("Deadline must come after the start of the auction")]
+    DeadlineBeforeStart,
+    #[msg("Auction is shorter than the minimum duration")]
+    AuctionTooShort,
+    #[msg("Auction is longer than the maximum duration")]
+    AuctionTooLong,
+    #[msg("Auction starts too far in the future")]
+    StartTooFar,
+    #[msg("Minimum increment is below the protocol minimum")]
+    IncrementTooLow,
+    #[msg("Auction bounds are inconsistent")]
+    InvalidBounds,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Protocol fee, royalties and crank reward cannot exceed the price")]
+    DeductionsTooHigh,
+    #[msg("Delivery grace period cannot be negative")]
+    InvalidGracePeriod,
+    #[msg("Only auctions of compressed NFTs can be aborted")]
+    NotAbortable,
+    #[msg("Maker can still deliver the NFT")]
+    DeliveryPending,
+    #[msg("Crank delay cannot be negative")]
+    InvalidCrankDelay,
+    #[msg("Deadline must be in the future")]
+    DeadlineInPast,
+    #[msg("Reveal period is longer than the maximum duration of an auction")]
+    RevealTooLong,
 }
@@ -0,0 +1,197 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{AuctionBounds, AuctionKind, AuctionTerms, NftStandard, SoftClose};
+
+#[event]
+pub struct AuctionCreated {
+    pub auction: Pubkey,
+    pub mint: Pubkey,
+    pub id: u64,
+    pub maker: Pubkey,
+    pub standard: NftStandard,
+    pub kind: AuctionKind,
+    pub start_time: i64,
+    pub deadline: i64,
+    pub min_price: u64,
+    pub min_increment: u64,
+    pub currency: Option<Pubkey>,
+    pub soft_close: Option<SoftClose>,
+    pub buy_now_price: Option<u64>,
+}
+
+#[event]
+pub struct LotItemAdded {
+    pub auction: Pubkey,
+    pub mint: Pubkey,
+    pub lot_items: u8,
+}
+
+/// A new leading bid. The preceding leading bid, `refund`, becomes withdrawable by its bidder.
+#[event]
+pub struct BidPlaced {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub previous_bidder: Option<Pubkey>,
+    pub refund: u64,
+    pub deadline: i64,
+}
+
+/// The deadline was pushed out by `extension` seconds by the soft close rule.
+#[event]
+pub struct AuctionExtended {
+    pub auction: Pubkey,
+    pub deadline: i64,
+    pub extension: i64,
+    pub extended_by: i64,
+}
+
+#[event]
+pub struct BidCommitted {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub deposit: u64,
+}
+
+#[event]
+pub struct BidRevealed {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub leading_bidder: Option<Pubkey>,
+    pub clearing_price: Option<u64>,
+}
+
+#[event]
+pub struct RefundWithdrawn {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+}
+
+/// A sealed-bid deposit was released: `refund` went back to the bidder and `forfeited` to the
+/// maker.
+#[event]
+pub struct DepositWithdrawn {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub refund: u64,
+    pub forfeited: u64,
+}
+
+/// The NFT was bought outright, on a Dutch auction or at the buy now price.
+#[event]
+pub struct NftBought {
+    pub auction: Pubkey,
+    pub buyer: Pubkey,
+    pub price: u64,
+    pub fee: u64,
+    pub royalties: u64,
+}
+
+/// The NFT, or part of its lot, was delivered to `recipient`. `complete` tells whether the
+/// whole NFT side of the auction is settled.
+#[event]
+pub struct NftClaimed {
+    pub auction: Pubkey,
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub lot_items_delivered: u8,
+    pub complete: bool,
+}
+
+#[event]
+pub struct ProceedsClaimed {
+    pub auction: Pubkey,
+    pub maker: Pubkey,
+    pub proceeds: u64,
+    pub fee: u64,
+    pub royalties: u64,
+}
+
+/// A crank settled the auction: `nft_delivered` tells whether it delivered the NFT to the winner
+/// and `proceeds` is what it paid out of the vault, zero if the maker had already claimed them.
+#[event]
+pub struct AuctionSettled {
+    pub auction: Pubkey,
+    pub cranker: Pubkey,
+    pub winner: Pubkey,
+    pub nft_delivered: bool,
+    pub proceeds: u64,
+    pub fee: u64,
+    pub royalties: u64,
+    pub crank_reward: u64,
+}
+
+/// The maker cancelled the auction, or handed part of its lot back. `complete` tells whether
+/// the auction is now cancelled.
+#[event]
+pub struct AuctionCancelled {
+    pub auction: Pubkey,
+    pub maker: Pubkey,
+    pub lot_items_released: u8,
+    pub complete: bool,
+}
+
+/// The winner gave up on a compressed NFT its maker did not deliver in time. Its winning bid,
+/// `bid`, becomes withdrawable.
+#[event]
+pub struct AuctionAborted {
+    pub auction: Pubkey,
+    pub winner: Pubkey,
+    pub bid: u64,
+}
+
+#[event]
+pub struct AuctionClosed {
+    pub auction: Pubkey,
+    pub maker: Pubkey,
+}
+
+/// The maker changed the terms of an auction before any bid.
+#[event]
+pub struct AuctionUpdated {
+    pub auction: Pubkey,
+    pub maker: Pubkey,
+    pub old: AuctionTerms,
+    pub new: AuctionTerms,
+}
+
+/// The admin changed the config, or migrated it from the legacy layout. Holds the new settings.
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub fee_bps: u16,
+    pub crank_reward_bps: u16,
+    pub crank_delay: i64,
+    pub bounds: AuctionBounds,
+    pub delivery_grace_period: i64,
+}
+
+/// The admin withdrew collected fees, in SOL when `currency` is `None`.
+#[event]
+pub struct TreasuryWithdrawn {
+    pub admin: Pubkey,
+    pub currency: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CollectionAdded {
+    pub collection_mint: Pubkey,
+}
+
+#[event]
+pub struct CollectionRemoved {
+    pub collection_mint: Pubkey,
+}
+
+#[event]
+pub struct CreatorAdded {
+    pub creator: Pubkey,
+}
+
+#[event]
+pub struct CreatorRemoved {
+    pub creator: Pubkey,
+}
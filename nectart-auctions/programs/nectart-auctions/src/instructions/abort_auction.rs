@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::events::AuctionAborted;
+use crate::state::{Auction, Config, NftStandard};
+use crate::errors::AuctionError;
+
+/// Lets the winner of a compressed NFT get its bid back when the maker did not deliver the NFT.
+/// Leaves cannot be frozen, so the maker may have moved or burnt the NFT during the auction.
+#[derive(Accounts)]
+pub struct AbortAuction<'info> {
+    pub winner: Signer<'info>,
+    /// CHECK: asset id of the compressed NFT, only used to derive the auction
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"auction", mint.key().as_ref(), &auction.id.to_le_bytes()],
+        bump = auction.bump,
+    )]
+    pub auction: Account<'info, Auction>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> AbortAuction<'info> {
+    /// Aborts the auction once the delivery grace period is over. The winning bid is then
+    /// withdrawn like an outbid one, with `withdraw_refund` or `withdraw_deposit`.
+    pub fn abort(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        self.auction.refresh_status(now);
+        require!(self.auction.standard == NftStandard::Compressed, AuctionError::NotAbortable);
+        require!(self.auction.current_bidder == Some(self.winner.key()), AuctionError::BadAccount);
+        let delivery_deadline = self.auction
+            .end_time()
+            .checked_add(self.config.delivery_grace_period)
+            .ok_or(AuctionError::ArithmeticOverflow)?;
+        require!(now >= delivery_deadline, AuctionError::DeliveryPending);
+        let bid = self.auction.clearing_price().unwrap_or(0);
+        self.auction.abort()?;
+        emit!(AuctionAborted {
+            auction: self.auction.key(),
+            winner: self.winner.key(),
+            bid,
+        });
+        Ok(())
+    }
+}
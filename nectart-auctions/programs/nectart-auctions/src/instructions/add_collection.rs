@@ -0,0 +1,58 @@
+use anchor_lang::{prelude::*, Owners};
+use anchor_spl::token_interface::Mint;
+use mpl_core::{accounts::BaseCollectionV1, types::Key as CoreKey};
+
+use crate::events::CollectionAdded;
+use crate::state::{ApprovedCollection, Config};
+use crate::errors::AuctionError;
+
+#[derive(Accounts)]
+pub struct AddCollection<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ AuctionError::BadAccount,
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: mint of a Token Metadata collection or address of a Core collection, checked in `add`
+    pub collection_mint: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"collection", collection_mint.key().as_ref()],
+        space = 8 + ApprovedCollection::INIT_SPACE,
+        bump,
+    )]
+    pub approved_collection: Account<'info, ApprovedCollection>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddCollection<'info> {
+    pub fn add(&mut self, bumps: &AddCollectionBumps) -> Result<()> {
+        self.check_collection()?;
+        self.approved_collection.set_inner(ApprovedCollection {
+            collection_mint: self.collection_mint.key(),
+            bump: bumps.approved_collection,
+        });
+        emit!(CollectionAdded {
+            collection_mint: self.collection_mint.key(),
+        });
+        Ok(())
+    }
+
+    /// Checks the collection is either a mint or a collection account of Metaplex Core.
+    fn check_collection(&self) -> Result<()> {
+        let collection = self.collection_mint.to_account_info();
+        if *collection.owner == mpl_core::ID {
+            let collection = BaseCollectionV1::try_from(&collection)
+                .map_err(|_| error!(AuctionError::BadAccount))?;
+            require!(collection.key == CoreKey::CollectionV1, AuctionError::BadAccount);
+        } else {
+            require!(Mint::owners().contains(collection.owner), AuctionError::BadAccount);
+            Mint::try_deserialize(&mut &collection.try_borrow_data()?[..])?;
+        }
+        Ok(())
+    }
+}
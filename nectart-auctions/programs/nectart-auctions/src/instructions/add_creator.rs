@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::events::CreatorAdded;
+use crate::state::{ApprovedCreator, Config};
+use crate::errors::AuctionError;
+
+#[derive(Accounts)]
+#[instruction(creator: Pubkey)]
+pub struct AddCreator<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ AuctionError::BadAccount,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"creator", creator.as_ref()],
+        space = 8 + ApprovedCreator::INIT_SPACE,
+        bump,
+    )]
+    pub approved_creator: Account<'info, ApprovedCreator>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddCreator<'info> {
+    pub fn add(&mut self, creator: Pubkey, bumps: &AddCreatorBumps) -> Result<()> {
+        self.approved_creator.set_inner(ApprovedCreator {
+            creator,
+            bump: bumps.approved_creator,
+        });
+        emit!(CreatorAdded { creator });
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::{
+        mpl_token_metadata::types::TokenStandard,
+        MasterEditionAccount,
+        Metadata,
+        MetadataAccount
+    },
+    token_interface::{self, approve, Approve, TokenInterface},
+};
+
+use crate::custody::freeze_nft;
+use crate::eligibility::check_metadata_eligibility;
+use crate::royalties::royalty_shares;
+use crate::events::LotItemAdded;
+use crate::state::{ApprovedCollection, ApprovedCreator, Auction, AuctionKind, AuctionStatus, Lot, LotItem, NftStandard, MAX_LOT_ITEMS};
+use crate::errors::AuctionError;
+
+/// Bundles one more NFT with the NFT of an auction which has not started yet. The NFT is held the
+/// same way and must be eligible on its own.
+#[derive(Accounts)]
+pub struct AddLotItem<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    /// CHECK: mint of the NFT of the auction, only used to derive the auction
+    pub auction_mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"auction", auction_mint.key().as_ref(), &auction.id.to_le_bytes()],
+        bump = auction.bump,
+        has_one = maker @ AuctionError::BadAccount,
+    )]
+    pub auction: Account<'info, Auction>,
+    #[account(
+        init_if_needed,
+        payer = maker,
+        seeds = [b"lot", auction.key().as_ref()],
+        space = 8 + Lot::INIT_SPACE,
+        bump,
+    )]
+    pub lot: Account<'info, Lot>,
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub mint_ata: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(
+        seeds = [
+            b"metadata",
+            metadata_program.key().as_ref(),
+            mint.key().as_ref(),
+        ],
+        seeds::program = metadata_program.key(),
+        bump,
+    )]
+    pub metadata: Box<Account<'info, MetadataAccount>>,
+    #[account(
+        seeds = [
+            b"metadata",
+            metadata_program.key().as_ref(),
+            mint.key().as_ref(),
+            b"edition",
+        ],
+        seeds::program = metadata_program.key(),
+        bump
+    )]
+    pub edition: Box<Account<'info, MasterEditionAccount>>,
+    pub approved_collection: Option<Account<'info, ApprovedCollection>>,
+    pub approved_creator: Option<Account<'info, ApprovedCreator>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub metadata_program: Program<'info, Metadata>,
+}
+
+impl<'info> AddLotItem<'info> {
+    /// Checks the NFT can join the lot. Lots are limited to non-programmable Token Metadata NFTs
+    /// sharing the royalties of the NFT of the auction, and to auctions settled through claims.
+    pub fn check(&mut self) -> Result<()> {
+        self.auction.refresh_status(Clock::get()?.unix_timestamp);
+        require!(self.auction.status == AuctionStatus::Scheduled, AuctionError::InvalidAuctionStatus);
+        require!(
+            self.auction.standard == NftStandard::Legacy
+                && self.metadata.token_standard != Some(TokenStandard::ProgrammableNonFungible)
+                && !matches!(self.auction.kind, AuctionKind::Dutch { .. })
+                && self.auction.buy_now_price.is_none(),
+            AuctionError::InvalidLot,
+        );
+        require!(
+            self.mint.key() != self.auction.mint
+                && self.lot.items.iter().all(|item| item.mint != self.mint.key()),
+            AuctionError::InvalidLot,
+        );
+        require!(self.lot.items.len() < MAX_LOT_ITEMS, AuctionError::LotFull);
+        check_metadata_eligibility(
+            &self.metadata,
+            self.approved_collection.as_deref(),
+            self.approved_creator.as_deref(),
+        )?;
+        require!(
+            self.metadata.seller_fee_basis_points == self.auction.seller_fee_basis_points
+                && royalty_shares(&self.metadata) == self.auction.creators,
+            AuctionError::RoyaltiesMismatch,
+        );
+        Ok(())
+    }
+
+    pub fn add(&mut self, bumps: &AddLotItemBumps) -> Result<()> {
+        self.lot.auction = self.auction.key();
+        self.lot.bump = bumps.lot;
+        self.lot.items.push(LotItem {
+            mint: self.mint.key(),
+            delivered: false,
+        });
+        self.auction.lot_items = self.auction.lot_items.checked_add(1).ok_or(AuctionError::ArithmeticOverflow)?;
+        emit!(LotItemAdded {
+            auction: self.auction.key(),
+            mint: self.mint.key(),
+            lot_items: self.auction.lot_items,
+        });
+        Ok(())
+    }
+
+    /// Delegates the NFT to the auction and freezes it, as for the NFT of the auction.
+    pub fn take_custody(&mut self) -> Result<()> {
+        let cpi_ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            Approve {
+                to: self.mint_ata.to_account_info(),
+                delegate: self.auction.to_account_info(),
+                authority: self.maker.to_account_info(),
+            },
+        );
+        approve(cpi_ctx, 1)?;
+        freeze_nft(
+            &self.auction,
+            &self.mint_ata.to_account_info(),
+            &self.edition.to_account_info(),
+            &self.mint.to_account_info(),
+            &self.token_program.to_account_info(),
+            &self.metadata_program.to_account_info(),
+        )
+    }
+}
@@ -1,78 +1,141 @@
-use anchor_lang::{prelude::*, system_program::{transfer, Transfer}};
-use anchor_spl::token::Mint;
+use anchor_lang::prelude::*;
+use anchor_spl::{token_interface::{self, TokenInterface}};
 
-use crate::state::{Auction, VaultState};
+use crate::payment::Payment;
+use crate::events::{AuctionExtended, BidPlaced};
+use crate::state::{Auction, AuctionKind, RefundLedger, VaultState};
 use crate::errors::AuctionError;
 
 #[derive(Accounts)]
 pub struct Bid<'info> {
     #[account(mut)]
     pub bidder: Signer<'info>,
-    pub mint: Account<'info, Mint>,
+    /// CHECK: mint of the NFT or address of the Core asset, only used to derive the auction
+    pub mint: UncheckedAccount<'info>,
     #[account(
         mut,
-        seeds = [b"auction", mint.key().as_ref()],
+        seeds = [b"auction", mint.key().as_ref(), &auction.id.to_le_bytes()],
         bump = auction.bump,
     )]
     pub auction: Account<'info, Auction>,
     #[account(
         mut,
-        seeds = [b"vault", mint.key().as_ref()],
+        seeds = [b"vault", auction.key().as_ref()],
         bump = vault_state.vault_bump,
     )]
     pub vault: SystemAccount<'info>,
     #[account(
-        seeds = [b"state", mint.key().as_ref()],
+        seeds = [b"state", auction.key().as_ref()],
         bump = vault_state.state_bump,
     )]
     pub vault_state: Account<'info, VaultState>,
-    /// The account of the preceding bidder. A check is made to ensure its public key is the right
-    /// one.
-    #[account(mut)]
-    pub preceding_bidder: Option<AccountInfo<'info>>,
+    #[account(
+        init_if_needed,
+        payer = bidder,
+        space = 8 + RefundLedger::INIT_SPACE,
+        seeds = [b"refund", auction.key().as_ref(), bidder.key().as_ref()],
+        bump,
+    )]
+    pub refund_ledger: Account<'info, RefundLedger>,
+    /// The following accounts are only needed for auctions in a token.
+    pub currency_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(
+        mut,
+        token::mint = currency_mint,
+        token::authority = bidder,
+        token::token_program = currency_token_program,
+    )]
+    pub bidder_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        mut,
+        associated_token::mint = currency_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = currency_token_program,
+    )]
+    pub vault_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub currency_token_program: Option<Interface<'info, TokenInterface>>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Bid<'info> {
-    pub fn bid(&mut self, lamports: u64) -> Result<()> {
+    /// Escrows a new leading bid. The preceding leading bid is not refunded here but becomes
+    /// withdrawable from the refund ledger of its bidder, so bids do not depend on each other.
+    ///
+    /// On a Dutch auction, the first bid at or above the current price wins and ends the auction
+    /// at that price. The NFT and the proceeds are then claimed as for an English auction, and the
+    /// part of the bid above the price is withdrawable from the refund ledger.
+    pub fn bid(&mut self, amount: u64, bumps: &BidBumps) -> Result<()> {
         let current_time = Clock::get()?.unix_timestamp;
-        let time_elapsed = current_time - self.auction.start_time;
-        require!(time_elapsed >= 0, AuctionError::AuctionNotStarted);
-        require!(current_time < self.auction.deadline, AuctionError::AuctionEnded);
-        let minimum = match self.auction.current_bid {
-            Some(current_bid) => current_bid + self.auction.min_increment,
-            None => self.auction.min_price,
+        self.auction.refresh_status(current_time);
+        self.auction.require_live()?;
+        let dutch_price = match self.auction.kind {
+            AuctionKind::English => None,
+            AuctionKind::Dutch { .. } => Some(self.auction.dutch_price(current_time)?),
+            AuctionKind::Sealed { .. } => return err!(AuctionError::InvalidAuctionKind),
+        };
+        let payment = Payment::new(
+            &self.auction,
+            &self.system_program,
+            self.currency_mint.as_deref(),
+            self.currency_token_program.as_ref(),
+        )?;
+        let received = payment.transfer(
+            &self.bidder.to_account_info(),
+            self.bidder_currency_account.as_deref(),
+            &self.vault.to_account_info(),
+            self.vault_currency_account.as_deref_mut(),
+            amount,
+            &[],
+        )?;
+        match dutch_price {
+            Some(price) => require!(received >= price, AuctionError::BidTooLow),
+            None => {
+                let minimum = match self.auction.current_bid {
+                    Some(current_bid) => current_bid.checked_add(self.auction.min_increment).ok_or(AuctionError::ArithmeticOverflow)?,
+                    None => self.auction.min_price,
+                };
+                require!(received > minimum, AuctionError::BidTooLow);
+            }
+        }
+        if self.refund_ledger.auction == Pubkey::default() {
+            self.refund_ledger.set_inner(RefundLedger {
+                auction: self.auction.key(),
+                bidder: self.bidder.key(),
+                deposited: 0,
+                bump: bumps.refund_ledger,
+            });
+            self.auction.open_escrows = self.auction.open_escrows.checked_add(1).ok_or(AuctionError::ArithmeticOverflow)?;
+        }
+        self.refund_ledger.deposited = self.refund_ledger.deposited.checked_add(received).ok_or(AuctionError::ArithmeticOverflow)?;
+        let previous_bidder = self.auction.current_bidder;
+        let refund = self.auction.current_bid.unwrap_or(0);
+        let extension = match dutch_price {
+            Some(price) => {
+                self.auction.win_dutch(self.bidder.key(), price, current_time);
+                0
+            }
+            None => {
+                self.auction.current_bid = Some(received);
+                self.auction.current_bidder = Some(*self.bidder.key);
+                self.auction.extend_deadline(current_time)?
+            }
         };
-        require!(lamports > minimum, AuctionError::BidTooLow);
-        require!(self.auction.current_bidder == self.preceding_bidder.clone().map(|x| x.key()), AuctionError::BadPrecedingBidder);
-        if self.auction.current_bidder.is_some() {
-            let seeds = [
-                b"vault",
-                self.mint.to_account_info().key.as_ref(),
-                &[self.vault_state.vault_bump],
-            ];
-            let signer_seeds = &[&seeds[..]];
-            let cpi_ctx = CpiContext::new_with_signer(
-                self.system_program.to_account_info(),
-                Transfer {
-                    from: self.vault.to_account_info(),
-                    to: self.preceding_bidder.clone().unwrap(),
-                },
-                signer_seeds,
-            );
-            let current_bid = self.auction.current_bid.unwrap();
-            transfer(cpi_ctx, current_bid)?;
+        emit!(BidPlaced {
+            auction: self.auction.key(),
+            bidder: self.bidder.key(),
+            amount: self.auction.current_bid.unwrap_or(received),
+            previous_bidder,
+            refund,
+            deadline: self.auction.deadline,
+        });
+        if extension > 0 {
+            emit!(AuctionExtended {
+                auction: self.auction.key(),
+                deadline: self.auction.deadline,
+                extension,
+                extended_by: self.auction.extended_by,
+            });
         }
-        let cpi_ctx = CpiContext::new(
-            self.system_program.to_account_info(),
-            Transfer {
-                from: self.bidder.to_account_info(),
-                to: self.vault.to_account_info(),
-            },
-        );
-        transfer(cpi_ctx, lamports)?;
-        self.auction.current_bid = Some(lamports);
-        self.auction.current_bidder = Some(*self.bidder.key);
         Ok(())
     }
 }
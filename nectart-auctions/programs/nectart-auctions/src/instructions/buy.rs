@@ -0,0 +1,249 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::{
+        mpl_token_metadata::instructions::TransferV1CpiAccounts,
+        MasterEditionAccount,
+        Metadata
+    },
+    token_interface::{self, TokenInterface},
+    associated_token::AssociatedToken,
+};
+
+use crate::custody::*;
+use crate::events::NftBought;
+use crate::payment::{net_amount, Payment};
+use crate::royalties::pay_royalties;
+use crate::state::{Auction, Config, NftStandard};
+use crate::errors::AuctionError;
+
+#[derive(Accounts)]
+pub struct Buy<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program,
+    )]
+    pub buyer_ata: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    /// Token account of the maker holding a Token Metadata NFT.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_ata: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    /// CHECK: mint of the NFT or address of the Core asset, checked by the seeds of the auction
+    #[account(mut)]
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK: metadata of the NFT, only used by programmable NFTs and checked by Token Metadata
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            metadata_program.key().as_ref(),
+            mint.key().as_ref(),
+        ],
+        seeds::program = metadata_program.key(),
+        bump,
+    )]
+    pub metadata: Option<UncheckedAccount<'info>>,
+    #[account(
+        seeds = [
+            b"metadata",
+            metadata_program.key().as_ref(),
+            mint.key().as_ref(),
+            b"edition",
+        ],
+        seeds::program = metadata_program.key(),
+        bump
+    )]
+    pub edition: Option<Box<Account<'info, MasterEditionAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"auction", mint.key().as_ref(), &auction.id.to_le_bytes()],
+        bump = auction.bump,
+        has_one = maker @ AuctionError::BadAccount,
+    )]
+    pub auction: Account<'info, Auction>,
+    /// Escrow of a Token-2022 NFT.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = config.treasury_bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+    /// The following accounts are only needed for auctions in a token.
+    pub currency_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(
+        mut,
+        token::mint = currency_mint,
+        token::authority = buyer,
+        token::token_program = currency_token_program,
+    )]
+    pub buyer_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        mut,
+        token::mint = currency_mint,
+        token::authority = maker,
+        token::token_program = currency_token_program,
+    )]
+    pub maker_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = currency_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = currency_token_program,
+    )]
+    pub treasury_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub currency_token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub metadata_program: Program<'info, Metadata>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub programmable: ProgrammableNft<'info>,
+    pub core: CoreAsset<'info>,
+    pub compressed: CompressedNft<'info>,
+}
+
+impl<'info> Buy<'info> {
+    /// Buys the NFT at the current price of a Dutch auction or at the buy now price of an English
+    /// auction, provided it does not exceed `max_price`. The leading bid of an English auction is
+    /// released to the refund ledger of its bidder.
+    pub fn buy(&mut self, max_price: u64) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        self.auction.refresh_status(current_time);
+        self.auction.require_live()?;
+        let price = self.auction.buy_price(current_time)?;
+        require!(price <= max_price, AuctionError::PriceTooHigh);
+        self.auction.sell(self.buyer.key(), price);
+        Ok(())
+    }
+
+    /// Pays the price to the maker, minus the protocol fee which goes to the treasury and the
+    /// royalties which go to the creators, passed as remaining accounts.
+    pub fn pay(&mut self, creator_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let payment = Payment::new(
+            &self.auction,
+            &self.system_program,
+            self.currency_mint.as_deref(),
+            self.currency_token_program.as_ref(),
+        )?;
+        let price = self.auction.current_bid.unwrap_or(0);
+        let fee = self.auction.fee(price);
+        payment.transfer(
+            &self.buyer.to_account_info(),
+            self.buyer_currency_account.as_deref(),
+            &self.treasury.to_account_info(),
+            self.treasury_currency_account.as_deref_mut(),
+            fee,
+            &[],
+        )?;
+        let royalties = pay_royalties(
+            &payment,
+            &self.auction,
+            price,
+            &self.buyer.to_account_info(),
+            self.buyer_currency_account.as_deref(),
+            creator_accounts,
+            &[],
+        )?;
+        payment.transfer(
+            &self.buyer.to_account_info(),
+            self.buyer_currency_account.as_deref(),
+            &self.maker.to_account_info(),
+            self.maker_currency_account.as_deref_mut(),
+            net_amount(price, &[fee, royalties])?,
+            &[],
+        )?;
+        emit!(NftBought {
+            auction: self.auction.key(),
+            buyer: self.buyer.key(),
+            price,
+            fee,
+            royalties,
+        });
+        Ok(())
+    }
+
+    /// Delivers the NFT to the buyer. Compressed NFTs need the current root of their tree and the
+    /// proof nodes of their leaf.
+    pub fn deliver(&mut self, root: Option<[u8; 32]>, proof: &[AccountInfo<'info>]) -> Result<()> {
+        match self.auction.standard {
+            NftStandard::Legacy => release_nft(
+                &self.auction,
+                &required(&self.maker_ata)?.to_account_info(),
+                &required(&self.buyer_ata)?.to_account_info(),
+                &required(&self.edition)?.to_account_info(),
+                &self.mint.to_account_info(),
+                &self.token_program.to_account_info(),
+                &self.metadata_program.to_account_info(),
+            ),
+            NftStandard::Programmable => transfer_programmable_nft(
+                &self.auction,
+                &self.metadata_program.to_account_info(),
+                TransferV1CpiAccounts {
+                    token: &required(&self.maker_ata)?.to_account_info(),
+                    token_owner: &self.maker.to_account_info(),
+                    destination_token: &required(&self.buyer_ata)?.to_account_info(),
+                    destination_owner: &self.buyer.to_account_info(),
+                    mint: &self.mint.to_account_info(),
+                    metadata: &required(&self.metadata)?.to_account_info(),
+                    edition: Some(&required(&self.edition)?.to_account_info()),
+                    token_record: self.programmable.token_record.as_deref(),
+                    destination_token_record: self.programmable.destination_token_record.as_deref(),
+                    authority: &self.auction.to_account_info(),
+                    payer: &self.buyer.to_account_info(),
+                    system_program: &self.system_program.to_account_info(),
+                    sysvar_instructions: self.programmable.sysvar_instructions()?,
+                    spl_token_program: &self.token_program.to_account_info(),
+                    spl_ata_program: &self.associated_token_program.to_account_info(),
+                    authorization_rules_program: self.programmable.authorization_rules_program.as_deref(),
+                    authorization_rules: self.programmable.authorization_rules.as_deref(),
+                },
+            ),
+            NftStandard::Token2022 => release_escrowed_nft(
+                &self.auction,
+                &required(&self.escrow)?.to_account_info(),
+                &required(&self.buyer_ata)?.to_account_info(),
+                &self.mint.to_account_info(),
+                &self.maker.to_account_info(),
+                &self.token_program.to_account_info(),
+            ),
+            NftStandard::Core => {
+                let asset = &self.mint.to_account_info();
+                let buyer = &self.buyer.to_account_info();
+                let system_program = &self.system_program.to_account_info();
+                self.core.thaw(&self.auction, asset, buyer, system_program)?;
+                self.core.transfer(&self.auction, asset, buyer, buyer, system_program)?;
+                self.core.remove_delegates(asset, buyer, system_program)
+            }
+            NftStandard::Compressed => self.compressed.transfer(
+                &self.auction,
+                root.ok_or(error!(AuctionError::MissingLeafProof))?,
+                &self.maker.to_account_info(),
+                &self.buyer.to_account_info(),
+                &self.system_program.to_account_info(),
+                proof,
+            ),
+        }
+    }
+}
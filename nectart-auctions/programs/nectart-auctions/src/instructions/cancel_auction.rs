@@ -0,0 +1,200 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::{
+        mpl_token_metadata::instructions::RevokeSaleV1CpiAccounts,
+        MasterEditionAccount,
+        Metadata
+    },
+    token_interface::{self, TokenInterface},
+};
+
+use crate::custody::*;
+use crate::events::AuctionCancelled;
+use crate::state::{Auction, Lot, NftStandard};
+use crate::errors::AuctionError;
+
+#[derive(Accounts)]
+pub struct CancelAuction<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    /// CHECK: mint of the NFT or address of the Core asset, checked by the seeds of the auction
+    #[account(mut)]
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK: metadata of the NFT, only used by programmable NFTs and checked by Token Metadata
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            metadata_program.key().as_ref(),
+            mint.key().as_ref(),
+        ],
+        seeds::program = metadata_program.key(),
+        bump,
+    )]
+    pub metadata: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub mint_ata: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        seeds = [
+            b"metadata",
+            metadata_program.key().as_ref(),
+            mint.key().as_ref(),
+            b"edition",
+        ],
+        seeds::program = metadata_program.key(),
+        bump
+    )]
+    pub edition: Option<Box<Account<'info, MasterEditionAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"auction", mint.key().as_ref(), &auction.id.to_le_bytes()],
+        bump = auction.bump,
+        has_one = maker @ AuctionError::BadAccount,
+    )]
+    pub auction: Account<'info, Auction>,
+    /// Escrow of a Token-2022 NFT.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"lot", auction.key().as_ref()],
+        bump = lot.bump,
+    )]
+    pub lot: Option<Account<'info, Lot>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub metadata_program: Program<'info, Metadata>,
+    pub programmable: ProgrammableNft<'info>,
+    pub core: CoreAsset<'info>,
+    pub compressed: CompressedNft<'info>,
+}
+
+impl<'info> CancelAuction<'info> {
+    /// Cancels the auction and hands the NFT back to the maker. Compressed NFTs need the current
+    /// root of their tree and the proof nodes of their leaf, passed as `remaining_accounts`. The
+    /// items of a lot are passed as `remaining_accounts` instead, and can be handed back over
+    /// several cancellations: the auction stays closed to bids until the last one.
+    pub fn cancel(&mut self, root: Option<[u8; 32]>, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        self.auction.refresh_status(Clock::get()?.unix_timestamp);
+        self.auction.require_cancellable()?;
+        let (lot_items_released, complete) = if self.auction.lot_items == 0 {
+            self.release(root, remaining_accounts)?;
+            (0, true)
+        } else {
+            if !required(&self.lot)?.lead_delivered {
+                self.release(None, &[])?;
+            }
+            self.release_lot(remaining_accounts)?
+        };
+        self.auction.mark_cancelled(complete)?;
+        emit!(AuctionCancelled {
+            auction: self.auction.key(),
+            maker: self.maker.key(),
+            lot_items_released,
+            complete,
+        });
+        Ok(())
+    }
+
+    /// Gives the maker back full control of the NFT of the auction itself. Compressed NFTs need the current root of
+    /// their tree and the proof nodes of their leaf.
+    fn release(&mut self, root: Option<[u8; 32]>, proof: &[AccountInfo<'info>]) -> Result<()> {
+        match self.auction.standard {
+            NftStandard::Legacy => return_nft(
+                &self.auction,
+                &required(&self.mint_ata)?.to_account_info(),
+                &self.maker.to_account_info(),
+                &required(&self.edition)?.to_account_info(),
+                &self.mint.to_account_info(),
+                &self.token_program.to_account_info(),
+                &self.metadata_program.to_account_info(),
+            ),
+            NftStandard::Programmable => self.revoke_sale(),
+            NftStandard::Token2022 => release_escrowed_nft(
+                &self.auction,
+                &required(&self.escrow)?.to_account_info(),
+                &required(&self.mint_ata)?.to_account_info(),
+                &self.mint.to_account_info(),
+                &self.maker.to_account_info(),
+                &self.token_program.to_account_info(),
+            ),
+            NftStandard::Core => {
+                let asset = &self.mint.to_account_info();
+                let maker = &self.maker.to_account_info();
+                let system_program = &self.system_program.to_account_info();
+                self.core.thaw(&self.auction, asset, maker, system_program)?;
+                self.core.remove_delegates(asset, maker, system_program)
+            }
+            NftStandard::Compressed => {
+                let maker = &self.maker.to_account_info();
+                self.compressed.delegate(
+                    required(&self.auction.leaf)?,
+                    root.ok_or(error!(AuctionError::MissingLeafProof))?,
+                    maker,
+                    &self.auction.to_account_info(),
+                    maker,
+                    &self.system_program.to_account_info(),
+                    proof,
+                )
+            }
+        }
+    }
+
+    fn revoke_sale(&mut self) -> Result<()> {
+        revoke_programmable_nft(
+            &self.metadata_program.to_account_info(),
+            RevokeSaleV1CpiAccounts {
+                delegate_record: None,
+                delegate: &self.auction.to_account_info(),
+                metadata: &required(&self.metadata)?.to_account_info(),
+                master_edition: Some(&required(&self.edition)?.to_account_info()),
+                token_record: self.programmable.token_record.as_deref(),
+                mint: &self.mint.to_account_info(),
+                token: &required(&self.mint_ata)?.to_account_info(),
+                authority: &self.maker.to_account_info(),
+                payer: &self.maker.to_account_info(),
+                system_program: &self.system_program.to_account_info(),
+                sysvar_instructions: self.programmable.sysvar_instructions()?,
+                spl_token_program: Some(&self.token_program.to_account_info()),
+                authorization_rules_program: self.programmable.authorization_rules_program.as_deref(),
+                authorization_rules: self.programmable.authorization_rules.as_deref(),
+            },
+        )
+    }
+
+    /// Hands items of the lot back to the maker, and returns how many were handed back and whether
+    /// the whole lot has been. `item_accounts` holds, for each item, its mint, its master edition
+    /// and the maker's token account holding it.
+    fn release_lot(&mut self, item_accounts: &'info [AccountInfo<'info>]) -> Result<(u8, bool)> {
+        let lot = self.lot.as_deref_mut().ok_or(error!(AuctionError::MissingLotItems))?;
+        lot.lead_delivered = true;
+        let items = item_accounts.chunks_exact(3);
+        require!(items.remainder().is_empty(), AuctionError::MissingLotItems);
+        let released = items.len() as u8;
+        for accounts in items {
+            let (mint, edition, maker_ata) = (&accounts[0], &accounts[1], &accounts[2]);
+            check_item_account(maker_ata, mint, self.maker.key)?;
+            lot.deliver(mint.key)?;
+            return_nft(
+                &self.auction,
+                maker_ata,
+                &self.maker.to_account_info(),
+                edition,
+                mint,
+                &self.token_program.to_account_info(),
+                &self.metadata_program.to_account_info(),
+            )?;
+        }
+        Ok((released, lot.is_delivered()))
+    }
+}
@@ -1,19 +1,17 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     metadata::{
-        mpl_token_metadata::instructions::{
-            ThawDelegatedAccountCpi, ThawDelegatedAccountCpiAccounts
-        }, 
-        MasterEditionAccount, 
+        mpl_token_metadata::instructions::{RevokeSaleV1CpiAccounts, TransferV1CpiAccounts},
+        MasterEditionAccount,
         Metadata
-    }, 
-    token::{
-        Mint, revoke, Revoke, Token, TokenAccount, transfer, Transfer
     },
-    associated_token::AssociatedToken,
+    token_interface::{self, TokenInterface},
+    associated_token::{create_idempotent, AssociatedToken, Create},
 };
 
-use crate::state::{Auction, VaultState};
+use crate::custody::*;
+use crate::events::NftClaimed;
+use crate::state::{Auction, Lot, NftStandard, VaultState};
 use crate::errors::AuctionError;
 
 #[derive(Accounts)]
@@ -25,17 +23,38 @@ pub struct ClaimNFT<'info> {
         payer = signer,
         associated_token::mint = mint,
         associated_token::authority = signer,
+        associated_token::token_program = token_program,
+    )]
+    pub signer_ata: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        mut,
+        address = auction.maker @ AuctionError::BadAccount,
     )]
-    pub signer_ata: Account<'info, TokenAccount>,
-    #[account(mut)]
     pub auctioneer: SystemAccount<'info>,
+    /// Token account of the maker holding a Token Metadata NFT.
     #[account(
         mut,
         associated_token::mint = mint,
         associated_token::authority = auctioneer,
+        associated_token::token_program = token_program,
     )]
-    pub auctioneer_ata: Account<'info, TokenAccount>,
-    pub mint: Account<'info, Mint>,
+    pub auctioneer_ata: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    /// CHECK: mint of the NFT, address of the Core asset or asset id of the compressed NFT, checked
+    /// by the seeds of the auction
+    #[account(mut)]
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK: metadata of the NFT, only used by programmable NFTs and checked by Token Metadata
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            metadata_program.key().as_ref(),
+            mint.key().as_ref(),
+        ],
+        seeds::program = metadata_program.key(),
+        bump,
+    )]
+    pub metadata: Option<UncheckedAccount<'info>>,
     #[account(
         mut,
         seeds = [
@@ -47,33 +66,54 @@ pub struct ClaimNFT<'info> {
         seeds::program = metadata_program.key(),
         bump
     )]
-    pub edition: Account<'info, MasterEditionAccount>,
+    pub edition: Option<Box<Account<'info, MasterEditionAccount>>>,
     #[account(
         mut,
-        seeds = [b"auction", mint.key().as_ref()],
+        seeds = [b"auction", mint.key().as_ref(), &auction.id.to_le_bytes()],
         bump = auction.bump,
     )]
     pub auction: Account<'info, Auction>,
     #[account(
-        seeds = [b"vault", mint.key().as_ref()],
+        seeds = [b"vault", auction.key().as_ref()],
         bump = vault_state.vault_bump,
     )]
     pub vault: SystemAccount<'info>,
     #[account(
-        seeds = [b"state", mint.key().as_ref()],
+        seeds = [b"state", auction.key().as_ref()],
         bump = vault_state.state_bump,
     )]
     pub vault_state: Account<'info, VaultState>,
+    /// Escrow of a Token-2022 NFT.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"lot", auction.key().as_ref()],
+        bump = lot.bump,
+    )]
+    pub lot: Option<Account<'info, Lot>>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub metadata_program: Program<'info, Metadata>,
     pub associated_token_program: Program<'info, AssociatedToken>,
+    pub programmable: ProgrammableNft<'info>,
+    pub core: CoreAsset<'info>,
+    pub compressed: CompressedNft<'info>,
 }
 
 impl<'info> ClaimNFT<'info> {
-    pub fn claim_nft(&mut self) -> Result<()> {
-        let current_time = Clock::get()?.unix_timestamp;
-        require!(current_time >= self.auction.deadline, AuctionError::AuctionNotEnded);
+    /// Delivers the NFT to the winner, or hands it back to the maker if there is none. Compressed
+    /// NFTs need the current root of their tree and the proof nodes of their leaf, passed as
+    /// `remaining_accounts`. The items of a lot are passed as `remaining_accounts` instead, and can
+    /// be delivered over several claims.
+    pub fn claim_nft(&mut self, root: Option<[u8; 32]>, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        self.auction.refresh_status(Clock::get()?.unix_timestamp);
+        self.auction.require_nft_claimable()?;
         match self.auction.current_bidder {
             Some(bidder) => {
                 require!(
@@ -89,40 +129,66 @@ impl<'info> ClaimNFT<'info> {
             }
         }
 
-        let seeds = &[
-            b"auction",
-            self.mint.to_account_info().key.as_ref(),
-            &[self.auction.bump],
-        ];
-        let signer_seeds = &[&seeds[..]];
-
-        let delegate = &self.auction.to_account_info();
-        let token_account = &self.auctioneer_ata.to_account_info();
-        let edition = &self.edition.to_account_info();
-        let mint = &self.mint.to_account_info();
-        let token_program = &self.token_program.to_account_info();
-        let metadata_program = &self.metadata_program.to_account_info();
-        ThawDelegatedAccountCpi::new(
-            metadata_program,
-            ThawDelegatedAccountCpiAccounts {
-                delegate,
-                token_account,
-                edition,
-                mint,
-                token_program,
-            },
-        ).invoke_signed(signer_seeds)?;
+        let (lot_items_delivered, complete) = if self.auction.lot_items == 0 {
+            self.deliver(root, remaining_accounts)?;
+            (0, true)
+        } else {
+            if !required(&self.lot)?.lead_delivered {
+                self.deliver(None, &[])?;
+            }
+            self.deliver_lot_items(remaining_accounts)?
+        };
+        if complete {
+            self.auction.mark_nft_claimed()?;
+        }
+        emit!(NftClaimed {
+            auction: self.auction.key(),
+            mint: self.auction.mint,
+            recipient: self.signer.key(),
+            lot_items_delivered,
+            complete,
+        });
+        Ok(())
+    }
 
-        let cpi_ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            Transfer {
-                from: self.auctioneer_ata.to_account_info(),
-                to: self.signer_ata.to_account_info(),
-                authority: self.auction.to_account_info(),
-            },
-            signer_seeds
-        );
-        transfer(cpi_ctx, 1)?;
+    /// Delivers the NFT of the auction itself.
+    fn deliver(&mut self, root: Option<[u8; 32]>, proof: &[AccountInfo<'info>]) -> Result<()> {
+        match self.auction.standard {
+            NftStandard::Legacy if self.auction.current_bidder.is_none() => return_nft(
+                &self.auction,
+                &required(&self.auctioneer_ata)?.to_account_info(),
+                &self.auctioneer.to_account_info(),
+                &required(&self.edition)?.to_account_info(),
+                &self.mint.to_account_info(),
+                &self.token_program.to_account_info(),
+                &self.metadata_program.to_account_info(),
+            )?,
+            NftStandard::Legacy => release_nft(
+                &self.auction,
+                &required(&self.auctioneer_ata)?.to_account_info(),
+                &required(&self.signer_ata)?.to_account_info(),
+                &required(&self.edition)?.to_account_info(),
+                &self.mint.to_account_info(),
+                &self.token_program.to_account_info(),
+                &self.metadata_program.to_account_info(),
+            )?,
+            NftStandard::Programmable if self.auction.current_bidder.is_none() => {
+                self.revoke_sale()?
+            }
+            NftStandard::Programmable => self.transfer_sale()?,
+            NftStandard::Token2022 => release_escrowed_nft(
+                &self.auction,
+                &required(&self.escrow)?.to_account_info(),
+                &required(&self.signer_ata)?.to_account_info(),
+                &self.mint.to_account_info(),
+                &self.auctioneer.to_account_info(),
+                &self.token_program.to_account_info(),
+            )?,
+            NftStandard::Core => self.release_core_asset()?,
+            NftStandard::Compressed => {
+                self.release_leaf(root.ok_or(error!(AuctionError::MissingLeafProof))?, proof)?
+            }
+        }
 
         // exceeds compute budget
         //let cpi_ctx = CpiContext::new(
@@ -136,5 +202,148 @@ impl<'info> ClaimNFT<'info> {
 
         Ok(())
     }
+
+    /// Delivers items of the lot to the winner, or hands them back to the maker if there is none,
+    /// and returns how many were delivered and whether the whole lot has been. `item_accounts` holds, for each item,
+    /// its mint, its master edition, the maker's token account holding it and, if there is a
+    /// winner, the associated token account of the winner, which is created if needed.
+    fn deliver_lot_items(&mut self, item_accounts: &'info [AccountInfo<'info>]) -> Result<(u8, bool)> {
+        let lot = self.lot.as_deref_mut().ok_or(error!(AuctionError::MissingLotItems))?;
+        lot.lead_delivered = true;
+        let winner = self.auction.current_bidder.is_some();
+        let chunk = if winner { 4 } else { 3 };
+        let items = item_accounts.chunks_exact(chunk);
+        require!(items.remainder().is_empty(), AuctionError::MissingLotItems);
+        let delivered = items.len() as u8;
+        for accounts in items {
+            let (mint, edition, auctioneer_ata) = (&accounts[0], &accounts[1], &accounts[2]);
+            check_item_account(auctioneer_ata, mint, self.auctioneer.key)?;
+            lot.deliver(mint.key)?;
+            if winner {
+                let signer_ata = &accounts[3];
+                let cpi_ctx = CpiContext::new(
+                    self.associated_token_program.to_account_info(),
+                    Create {
+                        payer: self.signer.to_account_info(),
+                        associated_token: signer_ata.clone(),
+                        authority: self.signer.to_account_info(),
+                        mint: mint.clone(),
+                        system_program: self.system_program.to_account_info(),
+                        token_program: self.token_program.to_account_info(),
+                    },
+                );
+                create_idempotent(cpi_ctx)?;
+                release_nft(
+                    &self.auction,
+                    auctioneer_ata,
+                    signer_ata,
+                    edition,
+                    mint,
+                    &self.token_program.to_account_info(),
+                    &self.metadata_program.to_account_info(),
+                )?;
+            } else {
+                return_nft(
+                    &self.auction,
+                    auctioneer_ata,
+                    &self.auctioneer.to_account_info(),
+                    edition,
+                    mint,
+                    &self.token_program.to_account_info(),
+                    &self.metadata_program.to_account_info(),
+                )?;
+            }
+        }
+        Ok((delivered, lot.is_delivered()))
+    }
+
+    /// Unfreezes a Core asset and transfers it to the winner, if any, then removes the delegates
+    /// of the auction.
+    fn release_core_asset(&self) -> Result<()> {
+        let asset = &self.mint.to_account_info();
+        let signer = &self.signer.to_account_info();
+        let system_program = &self.system_program.to_account_info();
+        self.core.thaw(&self.auction, asset, signer, system_program)?;
+        if self.auction.current_bidder.is_some() {
+            self.core.transfer(&self.auction, asset, signer, signer, system_program)?;
+        }
+        self.core.remove_delegates(asset, signer, system_program)
+    }
+
+    /// Transfers a compressed NFT to the winner, or delegates it back to the maker if there is
+    /// none.
+    fn release_leaf(&self, root: [u8; 32], proof: &[AccountInfo<'info>]) -> Result<()> {
+        let auctioneer = &self.auctioneer.to_account_info();
+        let system_program = &self.system_program.to_account_info();
+        match self.auction.current_bidder {
+            Some(_) => self.compressed.transfer(
+                &self.auction,
+                root,
+                auctioneer,
+                &self.signer.to_account_info(),
+                system_program,
+                proof,
+            ),
+            None => self.compressed.delegate(
+                required(&self.auction.leaf)?,
+                root,
+                &self.signer.to_account_info(),
+                &self.auction.to_account_info(),
+                auctioneer,
+                system_program,
+                proof,
+            ),
+        }
+    }
+
+    /// Hands a programmable NFT that did not sell back to the maker.
+    fn revoke_sale(&self) -> Result<()> {
+        revoke_programmable_nft(
+            &self.metadata_program.to_account_info(),
+            RevokeSaleV1CpiAccounts {
+                delegate_record: None,
+                delegate: &self.auction.to_account_info(),
+                metadata: &required(&self.metadata)?.to_account_info(),
+                master_edition: Some(&required(&self.edition)?.to_account_info()),
+                token_record: self.programmable.token_record.as_deref(),
+                mint: &self.mint.to_account_info(),
+                token: &required(&self.auctioneer_ata)?.to_account_info(),
+                authority: &self.signer.to_account_info(),
+                payer: &self.signer.to_account_info(),
+                system_program: &self.system_program.to_account_info(),
+                sysvar_instructions: self.programmable.sysvar_instructions()?,
+                spl_token_program: Some(&self.token_program.to_account_info()),
+                authorization_rules_program: self.programmable.authorization_rules_program.as_deref(),
+                authorization_rules: self.programmable.authorization_rules.as_deref(),
+            },
+        )
+    }
+
+    /// Transfers a programmable NFT to the winner.
+    fn transfer_sale(&self) -> Result<()> {
+        transfer_programmable_nft(
+            &self.auction,
+            &self.metadata_program.to_account_info(),
+            TransferV1CpiAccounts {
+                token: &required(&self.auctioneer_ata)?.to_account_info(),
+                token_owner: &self.auctioneer.to_account_info(),
+                destination_token: &required(&self.signer_ata)?.to_account_info(),
+                destination_owner: &self.signer.to_account_info(),
+                mint: &self.mint.to_account_info(),
+                metadata: &required(&self.metadata)?.to_account_info(),
+                edition: Some(&required(&self.edition)?.to_account_info()),
+                token_record: self.programmable.token_record.as_deref(),
+                destination_token_record: self.programmable.destination_token_record.as_deref(),
+                authority: &self.auction.to_account_info(),
+                payer: &self.signer.to_account_info(),
+                system_program: &self.system_program.to_account_info(),
+                sysvar_instructions: self.programmable.sysvar_instructions()?,
+                spl_token_program: &self.token_program.to_account_info(),
+                spl_ata_program: &self.associated_token_program.to_account_info(),
+                authorization_rules_program: self.programmable.authorization_rules_program.as_deref(),
+                authorization_rules: self.programmable.authorization_rules.as_deref(),
+            },
+        )
+    }
 }
 
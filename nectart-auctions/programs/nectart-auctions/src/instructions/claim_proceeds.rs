@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{self, TokenInterface}};
+
+use crate::events::ProceedsClaimed;
+use crate::payment::{net_amount, Payment};
+use crate::royalties::pay_royalties;
+use crate::state::{Auction, Config, VaultState};
+use crate::errors::AuctionError;
+
+#[derive(Accounts)]
+pub struct ClaimProceeds<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    /// CHECK: mint of the NFT or address of the Core asset, only used to derive the auction
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"auction", mint.key().as_ref(), &auction.id.to_le_bytes()],
+        bump = auction.bump,
+    )]
+    pub auction: Account<'info, Auction>,
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"state", auction.key().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = config.treasury_bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+    /// The following accounts are only needed for auctions in a token.
+    pub currency_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(
+        mut,
+        token::mint = currency_mint,
+        token::authority = signer,
+        token::token_program = currency_token_program,
+    )]
+    pub signer_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        mut,
+        associated_token::mint = currency_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = currency_token_program,
+    )]
+    pub vault_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        associated_token::mint = currency_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = currency_token_program,
+    )]
+    pub treasury_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub currency_token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ClaimProceeds<'info> {
+    /// Pays the clearing price to the maker, minus the protocol fee which goes to the treasury and
+    /// the royalties which go to the creators, passed as remaining accounts.
+    pub fn claim_proceeds(&mut self, creator_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let maker_key = self.signer.key();
+        require!(maker_key == self.auction.maker, AuctionError::BadAccount);
+        self.auction.refresh_status(Clock::get()?.unix_timestamp);
+        self.auction.mark_sol_claimed()?;
+        let proceeds = self.auction.clearing_price().unwrap_or(0);
+        let fee = self.auction.fee(proceeds);
+        let payment = Payment::new(
+            &self.auction,
+            &self.system_program,
+            self.currency_mint.as_deref(),
+            self.currency_token_program.as_ref(),
+        )?;
+        let seeds = [
+            b"vault",
+            self.auction.to_account_info().key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        payment.transfer(
+            &self.vault.to_account_info(),
+            self.vault_currency_account.as_deref(),
+            &self.treasury.to_account_info(),
+            self.treasury_currency_account.as_deref_mut(),
+            fee,
+            signer_seeds,
+        )?;
+        let royalties = pay_royalties(
+            &payment,
+            &self.auction,
+            proceeds,
+            &self.vault.to_account_info(),
+            self.vault_currency_account.as_deref(),
+            creator_accounts,
+            signer_seeds,
+        )?;
+        payment.transfer(
+            &self.vault.to_account_info(),
+            self.vault_currency_account.as_deref(),
+            &self.signer.to_account_info(),
+            self.signer_currency_account.as_deref_mut(),
+            net_amount(proceeds, &[fee, royalties])?,
+            signer_seeds,
+        )?;
+        emit!(ProceedsClaimed {
+            auction: self.auction.key(),
+            maker: maker_key,
+            proceeds,
+            fee,
+            royalties,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use anchor_lang::{prelude::*, system_program::{transfer, Transfer}};
+use anchor_spl::{token_interface::{self, TokenInterface}};
+
+use crate::events::AuctionClosed;
+use crate::payment::Payment;
+use crate::state::{Auction, Lot, VaultState};
+use crate::errors::AuctionError;
+
+/// Closes the vault, vault state and lot of a settled auction. The auction account itself is kept
+/// as a record of the sale.
+#[derive(Accounts)]
+pub struct CloseAuction<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    /// CHECK: mint of the NFT or address of the Core asset, only used to derive the auction
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"auction", mint.key().as_ref(), &auction.id.to_le_bytes()],
+        bump = auction.bump,
+        has_one = maker @ AuctionError::BadAccount,
+        constraint = auction.is_settled() @ AuctionError::AuctionNotSettled,
+        constraint = auction.lot_items == 0 || lot.is_some() @ AuctionError::MissingLotItems,
+    )]
+    pub auction: Account<'info, Auction>,
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = maker,
+        seeds = [b"state", auction.key().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        close = maker,
+        seeds = [b"lot", auction.key().as_ref()],
+        bump = lot.bump,
+    )]
+    pub lot: Option<Account<'info, Lot>>,
+    /// The following accounts are only needed for auctions in a token. The mint is writable as
+    /// transfer fees withheld by the vault token account are harvested to it.
+    #[account(mut)]
+    pub currency_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(
+        mut,
+        token::mint = currency_mint,
+        token::authority = maker,
+        token::token_program = currency_token_program,
+    )]
+    pub maker_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        mut,
+        associated_token::mint = currency_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = currency_token_program,
+    )]
+    pub vault_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub currency_token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CloseAuction<'info> {
+    /// Sends the tokens left in the vault token account, if any, to the maker and closes it.
+    pub fn close_vault_account(&mut self) -> Result<()> {
+        let payment = Payment::new(
+            &self.auction,
+            &self.system_program,
+            self.currency_mint.as_deref(),
+            self.currency_token_program.as_ref(),
+        )?;
+        if self.auction.currency.is_none() {
+            return Ok(());
+        }
+        let vault_currency_account = self.vault_currency_account.as_deref().ok_or(AuctionError::BadCurrency)?;
+        let seeds = [
+            b"vault",
+            self.auction.to_account_info().key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        payment.transfer(
+            &self.vault.to_account_info(),
+            Some(vault_currency_account),
+            &self.maker.to_account_info(),
+            self.maker_currency_account.as_deref_mut(),
+            vault_currency_account.amount,
+            signer_seeds,
+        )?;
+        payment.close_vault_account(
+            &self.vault.to_account_info(),
+            vault_currency_account,
+            &self.maker.to_account_info(),
+            signer_seeds,
+        )
+    }
+
+    /// Sends what is left in the vault (its rent exemption) back to the maker. The vault ends up
+    /// with no lamports, which lets the runtime garbage-collect it.
+    pub fn close_vault(&mut self) -> Result<()> {
+        let seeds = [
+            b"vault",
+            self.auction.to_account_info().key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.vault.to_account_info(),
+                to: self.maker.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer(cpi_ctx, self.vault.lamports())?;
+        emit!(AuctionClosed {
+            auction: self.auction.key(),
+            maker: self.maker.key(),
+        });
+        Ok(())
+    }
+}
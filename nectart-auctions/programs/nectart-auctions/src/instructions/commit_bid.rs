@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{token_interface::{self, TokenInterface}};
+
+use crate::payment::Payment;
+use crate::events::BidCommitted;
+use crate::state::{Auction, AuctionKind, SealedBid, VaultState};
+use crate::errors::AuctionError;
+
+#[derive(Accounts)]
+pub struct CommitBid<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+    /// CHECK: mint of the NFT or address of the Core asset, only used to derive the auction
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"auction", mint.key().as_ref(), &auction.id.to_le_bytes()],
+        bump = auction.bump,
+    )]
+    pub auction: Account<'info, Auction>,
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"state", auction.key().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        init,
+        payer = bidder,
+        seeds = [b"sealed_bid", auction.key().as_ref(), bidder.key().as_ref()],
+        space = 8 + SealedBid::INIT_SPACE,
+        bump,
+    )]
+    pub sealed_bid: Account<'info, SealedBid>,
+    /// The following accounts are only needed for auctions in a token.
+    pub currency_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(
+        mut,
+        token::mint = currency_mint,
+        token::authority = bidder,
+        token::token_program = currency_token_program,
+    )]
+    pub bidder_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        mut,
+        associated_token::mint = currency_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = currency_token_program,
+    )]
+    pub vault_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub currency_token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CommitBid<'info> {
+    /// Records `commitment`, which hides the bid amount. The deposit must cover the bid, and can be
+    /// larger so as not to reveal it.
+    pub fn commit(&mut self, commitment: [u8; 32], deposit: u64, bumps: &CommitBidBumps) -> Result<()> {
+        self.auction.refresh_status(Clock::get()?.unix_timestamp);
+        self.auction.require_live()?;
+        require!(
+            matches!(self.auction.kind, AuctionKind::Sealed { .. }),
+            AuctionError::InvalidAuctionKind,
+        );
+        require!(deposit > 0, AuctionError::BidTooLow);
+        self.sealed_bid.set_inner(SealedBid {
+            auction: self.auction.key(),
+            bidder: self.bidder.key(),
+            commitment,
+            deposit,
+            revealed: None,
+            bump: bumps.sealed_bid,
+        });
+        self.auction.open_escrows = self.auction.open_escrows.checked_add(1).ok_or(AuctionError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Moves the deposit to the vault. What the vault actually received is recorded, as it may be
+    /// lower than the deposit for tokens charging a transfer fee.
+    pub fn deposit(&mut self) -> Result<()> {
+        let payment = Payment::new(
+            &self.auction,
+            &self.system_program,
+            self.currency_mint.as_deref(),
+            self.currency_token_program.as_ref(),
+        )?;
+        self.sealed_bid.deposit = payment.transfer(
+            &self.bidder.to_account_info(),
+            self.bidder_currency_account.as_deref(),
+            &self.vault.to_account_info(),
+            self.vault_currency_account.as_deref_mut(),
+            self.sealed_bid.deposit,
+            &[],
+        )?;
+        emit!(BidCommitted {
+            auction: self.auction.key(),
+            bidder: self.bidder.key(),
+            deposit: self.sealed_bid.deposit,
+        });
+        Ok(())
+    }
+}
@@ -1,34 +1,67 @@
 use anchor_lang::{prelude::*, system_program::{transfer, Transfer}};
 use anchor_spl::{
     metadata::{
-        mpl_token_metadata::instructions::{
-            FreezeDelegatedAccountCpi, FreezeDelegatedAccountCpiAccounts
-        }, 
-        MasterEditionAccount, 
+        mpl_token_metadata::{
+            instructions::{
+                DelegateSaleV1Cpi, DelegateSaleV1CpiAccounts, DelegateSaleV1InstructionArgs,
+            },
+            types::TokenStandard,
+        },
+        MasterEditionAccount,
         Metadata,
         MetadataAccount
-    }, 
-    token::{
-        approve, Approve, Mint, Token, TokenAccount
-    }
+    },
+    token_2022::{
+        self,
+        spl_token_2022::{
+            self,
+            extension::{
+                group_member_pointer::GroupMemberPointer, metadata_pointer::MetadataPointer,
+                non_transferable::NonTransferable, transfer_hook::TransferHook, StateWithExtensions,
+            },
+        },
+    },
+    token_interface::{
+        self, approve, get_mint_extension_data, transfer_checked, Approve, TokenInterface,
+        TransferChecked,
+    },
+    associated_token::AssociatedToken,
 };
+use mpl_core::{accounts::BaseAssetV1, types::UpdateAuthority};
 use solana_program::sysvar::rent::Rent;
+use spl_token_group_interface::state::TokenGroupMember;
 
-use crate::state::{Auction, VaultState};
+use crate::custody::*;
+use crate::eligibility::{check_approvals, check_metadata_eligibility};
+use crate::royalties::royalty_shares;
+use crate::events::AuctionCreated;
+use crate::state::{ApprovedCollection, ApprovedCreator, Auction, AuctionCounter, AuctionKind, AuctionStatus, Config, LeafProof, NftStandard, PriceCurve, RoyaltyShare, SoftClose, VaultState};
+use crate::errors::AuctionError;
 
 #[derive(Accounts)]
 pub struct CreateAuction<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
-    pub mint: Account<'info, Mint>,
-    pub collection_mint: Account<'info, Mint>,
+    /// CHECK: mint of the NFT, Core asset or asset id of a compressed NFT, whose kind is checked by
+    /// `check_eligibility`
+    #[account(mut)]
+    pub mint: UncheckedAccount<'info>,
+    /// Approval of the verified collection of the NFT or compressed NFT, of the group of a
+    /// Token-2022 NFT or of the collection of a Core asset.
+    pub approved_collection: Option<Account<'info, ApprovedCollection>>,
+    /// Approval of the first verified creator of the NFT, or of the update authority of a Core
+    /// asset, for NFTs without an approved collection.
+    pub approved_creator: Option<Account<'info, ApprovedCreator>>,
     #[account(
         mut,
         associated_token::mint = mint,
         associated_token::authority = payer,
+        associated_token::token_program = token_program,
     )]
-    pub mint_ata: Account<'info, TokenAccount>,
+    pub mint_ata: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    /// Token Metadata accounts of the NFT, omitted for Token-2022 NFTs and Core assets.
     #[account(
+        mut,
         seeds = [
             b"metadata",
             metadata_program.key().as_ref(),
@@ -36,10 +69,8 @@ pub struct CreateAuction<'info> {
         ],
         seeds::program = metadata_program.key(),
         bump,
-        constraint = metadata.collection.as_ref().unwrap().key.as_ref() == collection_mint.key().as_ref(),
-        constraint = metadata.collection.as_ref().unwrap().verified == true,
     )]
-    pub metadata: Account<'info, MetadataAccount>,
+    pub metadata: Option<Box<Account<'info, MetadataAccount>>>,
     #[account(
         seeds = [
             b"metadata",
@@ -50,40 +81,160 @@ pub struct CreateAuction<'info> {
         seeds::program = metadata_program.key(),
         bump
     )]
-    pub edition: Account<'info, MasterEditionAccount>,
+    pub edition: Option<Box<Account<'info, MasterEditionAccount>>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [b"counter", mint.key().as_ref()],
+        space = 8 + AuctionCounter::INIT_SPACE,
+        bump,
+    )]
+    pub auction_counter: Account<'info, AuctionCounter>,
     #[account(
         init,
         payer = payer,
-        seeds = [b"auction", mint.key().as_ref()],
+        seeds = [b"auction", mint.key().as_ref(), &auction_counter.count.to_le_bytes()],
         space = 8 + Auction::INIT_SPACE,
         bump,
     )]
     pub auction: Account<'info, Auction>,
     #[account(
         mut,
-        seeds = [b"vault", mint.key().as_ref()],
+        seeds = [b"vault", auction.key().as_ref()],
         bump,
     )]
     pub vault: SystemAccount<'info>,
     #[account(
         init,
         payer = payer,
-        seeds = [b"state", mint.key().as_ref()],
+        seeds = [b"state", auction.key().as_ref()],
         space = 8 + VaultState::INIT_SPACE,
         bump,
     )]
     pub vault_state: Account<'info, VaultState>,
+    /// Token account holding a Token-2022 NFT for the duration of the auction.
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    /// Mint of the token the auction is denominated in. Bids are made in SOL if omitted.
+    pub currency_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = currency_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = currency_token_program,
+    )]
+    pub vault_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub currency_token_program: Option<Interface<'info, TokenInterface>>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub metadata_program: Program<'info, Metadata>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub programmable: ProgrammableNft<'info>,
+    pub core: CoreAsset<'info>,
+    pub compressed: CompressedNft<'info>,
 }
 
 impl<'info> CreateAuction<'info> {
+    /// Puts the NFT under the control of the auction. Token Metadata NFTs, Core assets and
+    /// compressed NFTs are left in the maker's wallet while Token-2022 NFTs are moved to the escrow
+    /// of the auction.
+    pub fn take_custody(&mut self, leaf: Option<&LeafProof>, proof: &[AccountInfo<'info>]) -> Result<()> {
+        match self.auction.standard {
+            NftStandard::Legacy => {
+                self.approve()?;
+                self.freeze()
+            }
+            NftStandard::Programmable => self.delegate_sale(),
+            NftStandard::Token2022 => self.escrow_nft(),
+            NftStandard::Core => self.core.delegate(
+                &self.auction,
+                &self.mint.to_account_info(),
+                &self.payer.to_account_info(),
+                &self.system_program.to_account_info(),
+            ),
+            NftStandard::Compressed => {
+                self.delegate_leaf(leaf.ok_or(error!(AuctionError::MissingLeafProof))?, proof)
+            }
+        }
+    }
+
+    /// Makes the auction the delegate of the leaf of a compressed NFT, and records the leaf so
+    /// that the auction can transfer it at settlement.
+    pub fn delegate_leaf(&mut self, leaf: &LeafProof, proof: &[AccountInfo<'info>]) -> Result<()> {
+        let compressed_leaf = self.compressed.leaf(&self.mint.key(), leaf)?;
+        self.auction.leaf = Some(compressed_leaf);
+        let payer = &self.payer.to_account_info();
+        self.compressed.delegate(
+            &compressed_leaf,
+            leaf.root,
+            payer,
+            payer,
+            &self.auction.to_account_info(),
+            &self.system_program.to_account_info(),
+            proof,
+        )
+    }
+
+    pub fn escrow_nft(&mut self) -> Result<()> {
+        let cpi_ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            TransferChecked {
+                from: required(&self.mint_ata)?.to_account_info(),
+                mint: self.mint.to_account_info(),
+                to: required(&self.escrow)?.to_account_info(),
+                authority: self.payer.to_account_info(),
+            },
+        );
+        transfer_checked(cpi_ctx, 1, 0)
+    }
+
+    /// Makes the auction the sale delegate of a programmable NFT, which prevents the maker from
+    /// moving it and lets the auction transfer it.
+    pub fn delegate_sale(&mut self) -> Result<()> {
+        let metadata_program = &self.metadata_program.to_account_info();
+        DelegateSaleV1Cpi::new(
+            metadata_program,
+            DelegateSaleV1CpiAccounts {
+                delegate_record: None,
+                delegate: &self.auction.to_account_info(),
+                metadata: &required(&self.metadata)?.to_account_info(),
+                master_edition: Some(&required(&self.edition)?.to_account_info()),
+                token_record: self.programmable.token_record.as_deref(),
+                mint: &self.mint.to_account_info(),
+                token: &required(&self.mint_ata)?.to_account_info(),
+                authority: &self.payer.to_account_info(),
+                payer: &self.payer.to_account_info(),
+                system_program: &self.system_program.to_account_info(),
+                sysvar_instructions: self.programmable.sysvar_instructions()?,
+                spl_token_program: Some(&self.token_program.to_account_info()),
+                authorization_rules_program: self.programmable.authorization_rules_program.as_deref(),
+                authorization_rules: self.programmable.authorization_rules.as_deref(),
+            },
+            DelegateSaleV1InstructionArgs {
+                amount: 1,
+                authorization_data: None,
+            },
+        ).invoke()?;
+        Ok(())
+    }
+
     pub fn approve(&mut self) -> Result<()> {
         let cpi_ctx  = CpiContext::new(
             self.token_program.to_account_info(),
             Approve {
-                to: self.mint_ata.to_account_info(),
+                to: required(&self.mint_ata)?.to_account_info(),
                 delegate: self.auction.to_account_info(),
                 authority: self.payer.to_account_info(),
             },
@@ -93,45 +244,256 @@ impl<'info> CreateAuction<'info> {
     }
 
     pub fn freeze(&mut self) -> Result<()> {
-        let delegate = &self.auction.to_account_info();
-        let token_account = &self.mint_ata.to_account_info();
-        let edition = &self.edition.to_account_info();
-        let mint = &self.mint.to_account_info();
-        let token_program = &self.token_program.to_account_info();
-        let metadata_program = &self.metadata_program.to_account_info();
+        freeze_nft(
+            &self.auction,
+            &required(&self.mint_ata)?.to_account_info(),
+            &required(&self.edition)?.to_account_info(),
+            &self.mint.to_account_info(),
+            &self.token_program.to_account_info(),
+            &self.metadata_program.to_account_info(),
+        )
+    }
 
-        let seeds = &[
-            b"auction",
-            self.mint.to_account_info().key.as_ref(),
-            &[self.auction.bump],
-        ];
-        let signer_seeds = &[&seeds[..]];
+    /// Returns the standard of the NFT, told apart by the program owning it and by the presence
+    /// of Token Metadata or Bubblegum accounts.
+    pub fn standard(&self) -> NftStandard {
+        if *self.mint.owner == mpl_core::ID {
+            return NftStandard::Core;
+        }
+        if self.compressed.merkle_tree.is_some() {
+            return NftStandard::Compressed;
+        }
+        match self.metadata.as_deref() {
+            Some(metadata) if metadata.token_standard == Some(TokenStandard::ProgrammableNonFungible) => {
+                NftStandard::Programmable
+            }
+            Some(_) => NftStandard::Legacy,
+            None => NftStandard::Token2022,
+        }
+    }
 
-        FreezeDelegatedAccountCpi::new(
-            metadata_program,
-            FreezeDelegatedAccountCpiAccounts {
-                delegate,
-                token_account,
-                edition,
-                mint,
-                token_program,
-            },
-        ).invoke_signed(signer_seeds)?;
+    /// Checks the NFT can be auctioned, according to its standard.
+    pub fn check_eligibility(&self, leaf: Option<&LeafProof>) -> Result<()> {
+        match self.standard() {
+            NftStandard::Legacy | NftStandard::Programmable => self.check_metadata_eligibility(),
+            NftStandard::Token2022 => self.check_group_eligibility(),
+            NftStandard::Core => self.check_core_eligibility(),
+            NftStandard::Compressed => {
+                self.check_leaf_eligibility(leaf.ok_or(error!(AuctionError::MissingLeafProof))?)
+            }
+        }
+    }
+
+    /// Checks a Token Metadata NFT can be auctioned: either its verified collection or its first
+    /// verified creator must have been approved by the admin.
+    pub fn check_metadata_eligibility(&self) -> Result<()> {
+        check_metadata_eligibility(
+            required(&self.metadata)?,
+            self.approved_collection.as_deref(),
+            self.approved_creator.as_deref(),
+        )
+    }
+
+    /// Checks a compressed NFT can be auctioned, by the same rules as Token Metadata NFTs applied
+    /// to the metadata of its leaf.
+    pub fn check_leaf_eligibility(&self, leaf: &LeafProof) -> Result<()> {
+        let metadata = leaf_metadata(leaf)?;
+        let collection = metadata.collection
+            .as_ref()
+            .filter(|collection| collection.verified)
+            .map(|collection| collection.key);
+        let first_verified_creator = metadata.creators
+            .iter()
+            .find(|creator| creator.verified)
+            .map(|creator| creator.address);
+        check_approvals(
+            collection,
+            first_verified_creator,
+            self.approved_collection.as_deref(),
+            self.approved_creator.as_deref(),
+        )
+    }
+
+    /// Checks a Token-2022 NFT can be auctioned: it must be transferable without extra accounts,
+    /// keep its metadata and group membership in its mint, and be a member of an approved group.
+    pub fn check_group_eligibility(&self) -> Result<()> {
+        let mint = self.mint.to_account_info();
+        require_keys_eq!(*mint.owner, token_2022::ID, AuctionError::NotEligible);
+        require_keys_eq!(self.token_program.key(), token_2022::ID, AuctionError::NotEligible);
+        {
+            let data = mint.try_borrow_data()?;
+            let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+            require!(state.base.decimals == 0 && state.base.supply == 1, AuctionError::NotEligible);
+        }
+        require!(
+            get_mint_extension_data::<NonTransferable>(&mint).is_err(),
+            AuctionError::NonTransferable,
+        );
+        if let Ok(transfer_hook) = get_mint_extension_data::<TransferHook>(&mint) {
+            require!(
+                Option::<Pubkey>::from(transfer_hook.program_id).is_none(),
+                AuctionError::NotEligible,
+            );
+        }
+
+        let metadata_pointer = get_mint_extension_data::<MetadataPointer>(&mint)
+            .map_err(|_| error!(AuctionError::NotEligible))?;
+        require!(
+            Option::<Pubkey>::from(metadata_pointer.metadata_address) == Some(mint.key()),
+            AuctionError::NotEligible,
+        );
+        let member_pointer = get_mint_extension_data::<GroupMemberPointer>(&mint)
+            .map_err(|_| error!(AuctionError::NotEligible))?;
+        require!(
+            Option::<Pubkey>::from(member_pointer.member_address) == Some(mint.key()),
+            AuctionError::NotEligible,
+        );
+        let member = get_mint_extension_data::<TokenGroupMember>(&mint)
+            .map_err(|_| error!(AuctionError::NotEligible))?;
+        let approved_collection = self.approved_collection
+            .as_ref()
+            .ok_or(error!(AuctionError::NotEligible))?;
+        require_keys_eq!(member.group, approved_collection.collection_mint, AuctionError::NotEligible);
+        Ok(())
+    }
+
+    /// Checks a Core asset can be auctioned: either its collection or, for assets outside of a
+    /// collection, its update authority must have been approved by the admin.
+    pub fn check_core_eligibility(&self) -> Result<()> {
+        let asset = BaseAssetV1::try_from(&self.mint.to_account_info())
+            .map_err(|_| error!(AuctionError::NotEligible))?;
+        require_keys_eq!(asset.owner, self.payer.key(), AuctionError::BadAccount);
+        let eligible = match (asset.update_authority, &self.approved_collection, &self.approved_creator) {
+            (UpdateAuthority::Collection(collection), Some(approved_collection), _) => {
+                collection == approved_collection.collection_mint
+            }
+            (UpdateAuthority::Address(address), _, Some(approved_creator)) => {
+                address == approved_creator.creator
+            }
+            _ => false,
+        };
+        require!(eligible, AuctionError::NotEligible);
+        Ok(())
+    }
+
+    /// Records the royalties of the NFT. Only verified creators are paid: the share of the others
+    /// goes to the maker.
+    pub fn record_royalties(&mut self, leaf: Option<&LeafProof>) -> Result<()> {
+        self.read_royalties(leaf)?;
+        self.auction.check_deductions()
+    }
+
+    fn read_royalties(&mut self, leaf: Option<&LeafProof>) -> Result<()> {
+        if self.auction.standard == NftStandard::Compressed {
+            let metadata = leaf_metadata(leaf.ok_or(error!(AuctionError::MissingLeafProof))?)?;
+            self.auction.seller_fee_basis_points = metadata.seller_fee_basis_points;
+            self.auction.creators = metadata.creators
+                .iter()
+                .filter(|creator| creator.verified && creator.share > 0)
+                .map(|creator| RoyaltyShare {
+                    address: creator.address,
+                    share: creator.share,
+                })
+                .collect();
+            return Ok(());
+        }
+        let Some(metadata) = self.metadata.as_deref() else {
+            return Ok(());
+        };
+        self.auction.seller_fee_basis_points = metadata.seller_fee_basis_points;
+        self.auction.creators = royalty_shares(metadata);
+        Ok(())
+    }
+
+    /// Sets the kind of the auction, its soft close rule and its buy now price. Must be called
+    /// after `create`.
+    pub fn configure(&mut self, kind: AuctionKind, soft_close: Option<SoftClose>, buy_now_price: Option<u64>) -> Result<()> {
+        if let AuctionKind::Dutch { curve, .. } = kind {
+            if let PriceCurve::Exponential { half_life } = curve {
+                require!(half_life > 0, AuctionError::InvalidPriceCurve);
+            }
+            require!(soft_close.is_none(), AuctionError::InvalidSoftClose);
+        }
+        if let AuctionKind::Sealed { .. } = kind {
+            require!(soft_close.is_none(), AuctionError::InvalidSoftClose);
+        }
+        if let Some(soft_close) = soft_close {
+            require!(
+                soft_close.window > 0
+                    && soft_close.extension > 0
+                    && soft_close.max_extension.unwrap_or(0) >= 0,
+                AuctionError::InvalidSoftClose,
+            );
+        }
+        if buy_now_price.is_some() {
+            require!(kind == AuctionKind::English, AuctionError::InvalidBuyNowPrice);
+        }
+        self.auction.kind = kind;
+        self.auction.soft_close = soft_close;
+        self.auction.buy_now_price = buy_now_price;
+        self.auction.check_terms(&self.config.bounds)?;
+        emit!(AuctionCreated {
+            auction: self.auction.key(),
+            mint: self.auction.mint,
+            id: self.auction.id,
+            maker: self.auction.maker,
+            standard: self.auction.standard,
+            kind,
+            start_time: self.auction.start_time,
+            deadline: self.auction.deadline,
+            min_price: self.auction.min_price,
+            min_increment: self.auction.min_increment,
+            currency: self.auction.currency,
+            soft_close,
+            buy_now_price,
+        });
         Ok(())
     }
 
     pub fn create(&mut self, start_time: i64, deadline: i64, min_price: u64, min_increment: u64, bumps: &CreateAuctionBumps) -> Result<()> {
+        require!(
+            self.currency_mint.is_some() == self.vault_currency_account.is_some(),
+            AuctionError::BadCurrency,
+        );
+        let standard = self.standard();
+        require!(
+            self.escrow.is_some() == (standard == NftStandard::Token2022),
+            AuctionError::BadAccount,
+        );
+        let now = Clock::get()?.unix_timestamp;
+        self.config.bounds.check(start_time, deadline, min_increment, now)?;
         self.auction.set_inner(Auction {
             start_time,
             deadline,
             min_price,
             current_bidder: None,
             current_bid: None,
+            second_bid: None,
             min_increment,
             mint: self.mint.key(),
+            id: self.auction_counter.count,
             maker: self.payer.key(),
+            currency: self.currency_mint.as_ref().map(|currency_mint| currency_mint.key()),
+            standard,
+            leaf: None,
+            status: AuctionStatus::Scheduled,
+            kind: AuctionKind::English,
+            soft_close: None,
+            buy_now_price: None,
+            bought: false,
+            fee_bps: self.config.fee_bps,
+            seller_fee_basis_points: 0,
+            creators: Vec::new(),
+            extended_by: 0,
+            max_duration: self.config.bounds.max_duration,
+            open_escrows: 0,
+            lot_items: 0,
             bump: bumps.auction,
         });
+        self.auction_counter.mint = self.mint.key();
+        self.auction_counter.count = self.auction_counter.count.checked_add(1).ok_or(AuctionError::ArithmeticOverflow)?;
+        self.auction_counter.bump = bumps.auction_counter;
+        self.auction.refresh_status(now);
         self.vault_state.vault_bump = bumps.vault;
         self.vault_state.state_bump = bumps.vault_state;
         let cpi_ctx = CpiContext::new(
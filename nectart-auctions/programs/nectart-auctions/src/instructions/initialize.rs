@@ -1,6 +1,7 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, system_program::{transfer, Transfer}};
 
-use crate::state::Config;
+use crate::state::{Config, MAX_FEE_BPS};
+use crate::errors::AuctionError;
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
@@ -14,16 +15,29 @@ pub struct Initialize<'info> {
         bump,
     )]
     pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump,
+    )]
+    pub treasury: SystemAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Initialize<'info> {
-    pub fn initialize(&mut self, bumps: &InitializeBumps) -> Result<()> {
+    pub fn initialize(&mut self, fee_bps: u16, bumps: &InitializeBumps) -> Result<()> {
         msg!("Initializing config");
-        self.config.set_inner(Config {
-            admin: *self.admin.key,
-            bump: bumps.config,
-        });
+        require!(fee_bps <= MAX_FEE_BPS, AuctionError::InvalidFee);
+        self.config.set_inner(Config::new(*self.admin.key, fee_bps, bumps.treasury, bumps.config));
+        // the treasury must be rent exempt to receive fees lower than its rent exemption
+        let cpi_ctx = CpiContext::new(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.admin.to_account_info(),
+                to: self.treasury.to_account_info(),
+            },
+        );
+        transfer(cpi_ctx, Rent::get()?.minimum_balance(0))?;
         msg!("Config initialized with admin: {}", self.admin.key.to_string());
         Ok(())
     }
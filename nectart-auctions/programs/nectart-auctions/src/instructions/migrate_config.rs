@@ -0,0 +1,76 @@
+use anchor_lang::{prelude::*, system_program::{transfer, Transfer}, Discriminator};
+
+use crate::state::{Config, MAX_FEE_BPS};
+use crate::errors::AuctionError;
+
+/// Size of a config created by the first version of the program, which only held the admin and
+/// the bump.
+const LEGACY_CONFIG_LEN: usize = 8 + 32 + 1;
+
+/// Brings a config created by the first version of the program to the current layout. Until it
+/// is migrated, the instructions reading the config fail to deserialize it.
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// CHECK: config in the legacy layout, checked in `migrate`
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub config: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateConfig<'info> {
+    /// Checks the config is in the legacy layout and that the signer is its admin.
+    pub fn check(&self) -> Result<()> {
+        let data = self.config.try_borrow_data()?;
+        require!(
+            data.len() == LEGACY_CONFIG_LEN && data[..8] == Config::DISCRIMINATOR,
+            AuctionError::BadAccount,
+        );
+        require!(data[8..40] == self.admin.key.to_bytes(), AuctionError::BadAccount);
+        Ok(())
+    }
+
+    /// Grows the config to the current layout, at the expense of the admin, and fills the new
+    /// fields as `initialize` does.
+    pub fn migrate(&mut self, fee_bps: u16, bumps: &MigrateConfigBumps) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, AuctionError::InvalidFee);
+        let rent = Rent::get()?;
+        let space = 8 + Config::INIT_SPACE;
+        let top_up = rent.minimum_balance(space).saturating_sub(self.config.lamports());
+        self.fund(&self.config.to_account_info(), top_up)?;
+        self.config.realloc(space, false)?;
+        let config = Config::new(self.admin.key(), fee_bps, bumps.treasury, bumps.config);
+        let mut data = self.config.try_borrow_mut_data()?;
+        config.try_serialize(&mut &mut data[..])?;
+        emit!(config.updated_event());
+        // the treasury must be rent exempt to receive fees lower than its rent exemption
+        let top_up = rent.minimum_balance(0).saturating_sub(self.treasury.lamports());
+        self.fund(&self.treasury.to_account_info(), top_up)
+    }
+
+    fn fund(&self, to: &AccountInfo<'info>, lamports: u64) -> Result<()> {
+        if lamports == 0 {
+            return Ok(());
+        }
+        let cpi_ctx = CpiContext::new(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.admin.to_account_info(),
+                to: to.clone(),
+            },
+        );
+        transfer(cpi_ctx, lamports)
+    }
+}
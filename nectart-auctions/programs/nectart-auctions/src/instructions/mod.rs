@@ -1,11 +1,47 @@
 pub mod initialize;
+pub mod update_config;
+pub mod migrate_config;
+pub mod withdraw_treasury;
+pub mod add_collection;
+pub mod remove_collection;
+pub mod add_creator;
+pub mod remove_creator;
 pub mod create_auction;
+pub mod update_auction;
+pub mod add_lot_item;
 pub mod bid;
-pub mod claim_sol;
+pub mod withdraw_refund;
+pub mod buy;
+pub mod commit_bid;
+pub mod reveal_bid;
+pub mod withdraw_deposit;
+pub mod claim_proceeds;
 pub mod claim_nft;
+pub mod cancel_auction;
+pub mod abort_auction;
+pub mod close_auction;
+pub mod settle;
 
 pub use initialize::*;
+pub use update_config::*;
+pub use migrate_config::*;
+pub use withdraw_treasury::*;
+pub use add_collection::*;
+pub use remove_collection::*;
+pub use add_creator::*;
+pub use remove_creator::*;
 pub use create_auction::*;
+pub use update_auction::*;
+pub use add_lot_item::*;
 pub use bid::*;
-pub use claim_sol::*;
+pub use withdraw_refund::*;
+pub use buy::*;
+pub use commit_bid::*;
+pub use reveal_bid::*;
+pub use withdraw_deposit::*;
+pub use claim_proceeds::*;
 pub use claim_nft::*;
+pub use cancel_auction::*;
+pub use abort_auction::*;
+pub use close_auction::*;
+pub use settle::*;
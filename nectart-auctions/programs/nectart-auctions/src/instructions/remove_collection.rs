@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::events::CollectionRemoved;
+use crate::state::{ApprovedCollection, Config};
+use crate::errors::AuctionError;
+
+/// Removes a collection from the allowlist. Auctions already created are not affected.
+#[derive(Accounts)]
+pub struct RemoveCollection<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ AuctionError::BadAccount,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"collection", approved_collection.collection_mint.as_ref()],
+        bump = approved_collection.bump,
+    )]
+    pub approved_collection: Account<'info, ApprovedCollection>,
+}
+
+impl<'info> RemoveCollection<'info> {
+    pub fn remove(&mut self) -> Result<()> {
+        emit!(CollectionRemoved {
+            collection_mint: self.approved_collection.collection_mint,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::events::CreatorRemoved;
+use crate::state::{ApprovedCreator, Config};
+use crate::errors::AuctionError;
+
+/// Removes a creator from the allowlist. Auctions already created are not affected.
+#[derive(Accounts)]
+pub struct RemoveCreator<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ AuctionError::BadAccount,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"creator", approved_creator.creator.as_ref()],
+        bump = approved_creator.bump,
+    )]
+    pub approved_creator: Account<'info, ApprovedCreator>,
+}
+
+impl<'info> RemoveCreator<'info> {
+    pub fn remove(&mut self) -> Result<()> {
+        emit!(CreatorRemoved {
+            creator: self.approved_creator.creator,
+        });
+        Ok(())
+    }
+}
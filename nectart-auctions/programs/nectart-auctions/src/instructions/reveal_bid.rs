@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::events::BidRevealed;
+use crate::state::{Auction, SealedBid};
+use crate::errors::AuctionError;
+
+#[derive(Accounts)]
+pub struct RevealBid<'info> {
+    pub bidder: Signer<'info>,
+    /// CHECK: mint of the NFT or address of the Core asset, only used to derive the auction
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"auction", mint.key().as_ref(), &auction.id.to_le_bytes()],
+        bump = auction.bump,
+    )]
+    pub auction: Account<'info, Auction>,
+    #[account(
+        mut,
+        seeds = [b"sealed_bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = sealed_bid.bump,
+    )]
+    pub sealed_bid: Account<'info, SealedBid>,
+}
+
+impl<'info> RevealBid<'info> {
+    pub fn reveal(&mut self, amount: u64, salt: [u8; 32]) -> Result<()> {
+        self.auction.refresh_status(Clock::get()?.unix_timestamp);
+        self.auction.require_revealing()?;
+        require!(self.sealed_bid.revealed.is_none(), AuctionError::AlreadyRevealed);
+        require!(
+            SealedBid::commitment(&self.auction.key(), &self.bidder.key(), amount, &salt)
+                == self.sealed_bid.commitment,
+            AuctionError::RevealMismatch,
+        );
+        require!(amount <= self.sealed_bid.deposit, AuctionError::BidExceedsDeposit);
+        require!(amount >= self.auction.min_price, AuctionError::BidTooLow);
+        self.sealed_bid.revealed = Some(amount);
+        self.auction.reveal(self.bidder.key(), amount);
+        emit!(BidRevealed {
+            auction: self.auction.key(),
+            bidder: self.bidder.key(),
+            amount,
+            leading_bidder: self.auction.current_bidder,
+            clearing_price: self.auction.clearing_price(),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::{
+        mpl_token_metadata::instructions::TransferV1CpiAccounts,
+        MasterEditionAccount,
+        Metadata
+    },
+    token_interface::{self, TokenInterface},
+    associated_token::AssociatedToken,
+};
+
+use crate::custody::*;
+use crate::events::AuctionSettled;
+use crate::payment::{net_amount, Payment};
+use crate::royalties::pay_royalties;
+use crate::state::{Auction, AuctionStatus, Config, NftStandard, VaultState};
+use crate::errors::AuctionError;
+
+#[derive(Accounts)]
+pub struct Settle<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    #[account(mut)]
+    pub winner: SystemAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        associated_token::mint = mint,
+        associated_token::authority = winner,
+        associated_token::token_program = token_program,
+    )]
+    pub winner_ata: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    /// Token account of the maker holding a Token Metadata NFT.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_ata: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    /// CHECK: mint of the NFT or asset id of the compressed NFT, checked by the seeds of the auction
+    #[account(mut)]
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK: metadata of the NFT, only used by programmable NFTs and checked by Token Metadata
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            metadata_program.key().as_ref(),
+            mint.key().as_ref(),
+        ],
+        seeds::program = metadata_program.key(),
+        bump,
+    )]
+    pub metadata: Option<UncheckedAccount<'info>>,
+    #[account(
+        seeds = [
+            b"metadata",
+            metadata_program.key().as_ref(),
+            mint.key().as_ref(),
+            b"edition",
+        ],
+        seeds::program = metadata_program.key(),
+        bump
+    )]
+    pub edition: Option<Box<Account<'info, MasterEditionAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"auction", mint.key().as_ref(), &auction.id.to_le_bytes()],
+        bump = auction.bump,
+        has_one = maker @ AuctionError::BadAccount,
+    )]
+    pub auction: Account<'info, Auction>,
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"state", auction.key().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    /// Escrow of a Token-2022 NFT.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = config.treasury_bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+    /// The following accounts are only needed for auctions in a token.
+    pub currency_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(
+        mut,
+        associated_token::mint = currency_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = currency_token_program,
+    )]
+    pub vault_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        mut,
+        token::mint = currency_mint,
+        token::authority = maker,
+        token::token_program = currency_token_program,
+    )]
+    pub maker_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        mut,
+        token::mint = currency_mint,
+        token::authority = cranker,
+        token::token_program = currency_token_program,
+    )]
+    pub cranker_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        associated_token::mint = currency_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = currency_token_program,
+    )]
+    pub treasury_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub currency_token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub metadata_program: Program<'info, Metadata>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub programmable: ProgrammableNft<'info>,
+    pub compressed: CompressedNft<'info>,
+}
+
+impl<'info> Settle<'info> {
+    /// Settles an ended auction on behalf of its parties: delivers the NFT to the winner and pays
+    /// the maker, whichever is still owed. Anyone can call it once the auction has ended, but the
+    /// crank reward is only paid after the crank delay. Core assets, whose delegates only their
+    /// new owner can remove, and lots, whose items take several transactions to deliver, are left
+    /// to the parties and fail with `NotSettleable`.
+    pub fn settle(&mut self, root: Option<[u8; 32]>, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        self.auction.refresh_status(now);
+        require!(
+            self.auction.standard != NftStandard::Core && self.auction.lot_items == 0,
+            AuctionError::NotSettleable,
+        );
+        let winner = self.auction.current_bidder.ok_or(AuctionError::NoWinner)?;
+        require_keys_eq!(self.winner.key(), winner, AuctionError::BadAccount);
+
+        // creator accounts come first, followed by the proof nodes of a compressed NFT
+        let creators = self.auction.creators.len().min(remaining_accounts.len());
+        let (creator_accounts, proof) = remaining_accounts.split_at(creators);
+
+        let nft_delivered = self.auction.status != AuctionStatus::NftClaimed;
+        if nft_delivered {
+            self.auction.require_nft_claimable()?;
+            self.deliver(root, proof)?;
+            self.auction.mark_nft_claimed()?;
+        }
+        let (proceeds, fee, royalties, crank_reward) = if self.auction.status == AuctionStatus::NftClaimed {
+            self.auction.mark_sol_claimed()?;
+            self.pay(creator_accounts, now)?
+        } else {
+            (0, 0, 0, 0)
+        };
+        emit!(AuctionSettled {
+            auction: self.auction.key(),
+            cranker: self.cranker.key(),
+            winner,
+            nft_delivered,
+            proceeds,
+            fee,
+            royalties,
+            crank_reward,
+        });
+        Ok(())
+    }
+
+    /// Pays the clearing price out of the vault: the protocol fee to the treasury, the royalties
+    /// to the creators, the crank reward to the cranker and the rest to the maker. Returns the
+    /// proceeds and each deduction.
+    fn pay(&mut self, creator_accounts: &'info [AccountInfo<'info>], now: i64) -> Result<(u64, u64, u64, u64)> {
+        let proceeds = self.auction.clearing_price().unwrap_or(0);
+        let fee = self.auction.fee(proceeds);
+        let crank_reward = self.config.crank_reward(proceeds, self.auction.end_time(), now);
+        let payment = Payment::new(
+            &self.auction,
+            &self.system_program,
+            self.currency_mint.as_deref(),
+            self.currency_token_program.as_ref(),
+        )?;
+        let seeds = [
+            b"vault",
+            self.auction.to_account_info().key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        payment.transfer(
+            &self.vault.to_account_info(),
+            self.vault_currency_account.as_deref(),
+            &self.treasury.to_account_info(),
+            self.treasury_currency_account.as_deref_mut(),
+            fee,
+            signer_seeds,
+        )?;
+        let royalties = pay_royalties(
+            &payment,
+            &self.auction,
+            proceeds,
+            &self.vault.to_account_info(),
+            self.vault_currency_account.as_deref(),
+            creator_accounts,
+            signer_seeds,
+        )?;
+        payment.transfer(
+            &self.vault.to_account_info(),
+            self.vault_currency_account.as_deref(),
+            &self.cranker.to_account_info(),
+            self.cranker_currency_account.as_deref_mut(),
+            crank_reward,
+            signer_seeds,
+        )?;
+        payment.transfer(
+            &self.vault.to_account_info(),
+            self.vault_currency_account.as_deref(),
+            &self.maker.to_account_info(),
+            self.maker_currency_account.as_deref_mut(),
+            net_amount(proceeds, &[fee, royalties, crank_reward])?,
+            signer_seeds,
+        )?;
+        Ok((proceeds, fee, royalties, crank_reward))
+    }
+
+    /// Delivers the NFT to the winner. Compressed NFTs need the current root of their tree and the
+    /// proof nodes of their leaf.
+    fn deliver(&mut self, root: Option<[u8; 32]>, proof: &[AccountInfo<'info>]) -> Result<()> {
+        match self.auction.standard {
+            NftStandard::Legacy => release_nft(
+                &self.auction,
+                &required(&self.maker_ata)?.to_account_info(),
+                &required(&self.winner_ata)?.to_account_info(),
+                &required(&self.edition)?.to_account_info(),
+                &self.mint.to_account_info(),
+                &self.token_program.to_account_info(),
+                &self.metadata_program.to_account_info(),
+            ),
+            NftStandard::Programmable => transfer_programmable_nft(
+                &self.auction,
+                &self.metadata_program.to_account_info(),
+                TransferV1CpiAccounts {
+                    token: &required(&self.maker_ata)?.to_account_info(),
+                    token_owner: &self.maker.to_account_info(),
+                    destination_token: &required(&self.winner_ata)?.to_account_info(),
+                    destination_owner: &self.winner.to_account_info(),
+                    mint: &self.mint.to_account_info(),
+                    metadata: &required(&self.metadata)?.to_account_info(),
+                    edition: Some(&required(&self.edition)?.to_account_info()),
+                    token_record: self.programmable.token_record.as_deref(),
+                    destination_token_record: self.programmable.destination_token_record.as_deref(),
+                    authority: &self.auction.to_account_info(),
+                    payer: &self.cranker.to_account_info(),
+                    system_program: &self.system_program.to_account_info(),
+                    sysvar_instructions: self.programmable.sysvar_instructions()?,
+                    spl_token_program: &self.token_program.to_account_info(),
+                    spl_ata_program: &self.associated_token_program.to_account_info(),
+                    authorization_rules_program: self.programmable.authorization_rules_program.as_deref(),
+                    authorization_rules: self.programmable.authorization_rules.as_deref(),
+                },
+            ),
+            NftStandard::Token2022 => release_escrowed_nft(
+                &self.auction,
+                &required(&self.escrow)?.to_account_info(),
+                &required(&self.winner_ata)?.to_account_info(),
+                &self.mint.to_account_info(),
+                &self.maker.to_account_info(),
+                &self.token_program.to_account_info(),
+            ),
+            NftStandard::Core => err!(AuctionError::NotSettleable),
+            NftStandard::Compressed => self.compressed.transfer(
+                &self.auction,
+                root.ok_or(error!(AuctionError::MissingLeafProof))?,
+                &self.maker.to_account_info(),
+                &self.winner.to_account_info(),
+                &self.system_program.to_account_info(),
+                proof,
+            ),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+use crate::events::AuctionUpdated;
+use crate::state::{Auction, AuctionTerms, Config};
+use crate::errors::AuctionError;
+
+#[derive(Accounts)]
+pub struct UpdateAuction<'info> {
+    pub maker: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"auction", auction.mint.as_ref(), &auction.id.to_le_bytes()],
+        bump = auction.bump,
+        has_one = maker @ AuctionError::BadAccount,
+    )]
+    pub auction: Account<'info, Auction>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> UpdateAuction<'info> {
+    /// Changes the reserve, the increment and the schedule of an auction that has not started or
+    /// has no bid yet. The new terms go through the same checks as at creation; fields left to
+    /// `None` are kept.
+    pub fn update(
+        &mut self,
+        start_time: Option<i64>,
+        deadline: Option<i64>,
+        min_price: Option<u64>,
+        min_increment: Option<u64>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        self.auction.refresh_status(now);
+        self.auction.require_editable()?;
+        let old = self.auction.terms();
+        let new = AuctionTerms {
+            start_time: start_time.unwrap_or(old.start_time),
+            deadline: deadline.unwrap_or(old.deadline),
+            min_price: min_price.unwrap_or(old.min_price),
+            min_increment: min_increment.unwrap_or(old.min_increment),
+        };
+        self.config.bounds.check(new.start_time, new.deadline, new.min_increment, now)?;
+        self.auction.set_terms(new, now);
+        self.auction.check_terms(&self.config.bounds)?;
+        emit!(AuctionUpdated {
+            auction: self.auction.key(),
+            maker: self.maker.key(),
+            old,
+            new,
+        });
+        Ok(())
+    }
+}
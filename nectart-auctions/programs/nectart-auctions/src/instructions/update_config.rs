@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{AuctionBounds, Config, MAX_CRANK_REWARD_BPS, MAX_FEE_BPS};
+use crate::errors::AuctionError;
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ AuctionError::BadAccount,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> UpdateConfig<'info> {
+    /// Changes the protocol fee, the crank reward and its delay, the bounds of new auctions and the
+    /// delivery grace period. Fields left to `None` are kept.
+    pub fn update(
+        &mut self,
+        fee_bps: Option<u16>,
+        crank_reward_bps: Option<u16>,
+        crank_delay: Option<i64>,
+        bounds: Option<AuctionBounds>,
+        delivery_grace_period: Option<i64>,
+    ) -> Result<()> {
+        if let Some(fee_bps) = fee_bps {
+            require!(fee_bps <= MAX_FEE_BPS, AuctionError::InvalidFee);
+            self.config.fee_bps = fee_bps;
+        }
+        if let Some(crank_reward_bps) = crank_reward_bps {
+            require!(crank_reward_bps <= MAX_CRANK_REWARD_BPS, AuctionError::InvalidCrankReward);
+            self.config.crank_reward_bps = crank_reward_bps;
+        }
+        if let Some(crank_delay) = crank_delay {
+            require!(crank_delay >= 0, AuctionError::InvalidCrankDelay);
+            self.config.crank_delay = crank_delay;
+        }
+        if let Some(bounds) = bounds {
+            bounds.validate()?;
+            self.config.bounds = bounds;
+        }
+        if let Some(delivery_grace_period) = delivery_grace_period {
+            require!(delivery_grace_period >= 0, AuctionError::InvalidGracePeriod);
+            self.config.delivery_grace_period = delivery_grace_period;
+        }
+        emit!(self.config.updated_event());
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{token_interface::{self, TokenInterface}};
+
+use crate::payment::Payment;
+use crate::events::DepositWithdrawn;
+use crate::state::{Auction, AuctionKind, SealedBid, VaultState};
+use crate::errors::AuctionError;
+
+/// Releases the deposit of a sealed bid once revealing is over. Anyone can call it, so that the
+/// maker can collect forfeited deposits and close the auction.
+#[derive(Accounts)]
+pub struct WithdrawDeposit<'info> {
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub bidder: SystemAccount<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    /// CHECK: mint of the NFT or address of the Core asset, only used to derive the auction
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"auction", mint.key().as_ref(), &auction.id.to_le_bytes()],
+        bump = auction.bump,
+        has_one = maker @ AuctionError::BadAccount,
+    )]
+    pub auction: Account<'info, Auction>,
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"state", auction.key().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        close = bidder,
+        seeds = [b"sealed_bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = sealed_bid.bump,
+    )]
+    pub sealed_bid: Account<'info, SealedBid>,
+    /// The following accounts are only needed for auctions in a token.
+    pub currency_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(
+        mut,
+        token::mint = currency_mint,
+        token::authority = bidder,
+        token::token_program = currency_token_program,
+    )]
+    pub bidder_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        mut,
+        token::mint = currency_mint,
+        token::authority = maker,
+        token::token_program = currency_token_program,
+    )]
+    pub maker_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        mut,
+        associated_token::mint = currency_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = currency_token_program,
+    )]
+    pub vault_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub currency_token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawDeposit<'info> {
+    pub fn withdraw(&mut self) -> Result<()> {
+        self.auction.refresh_status(Clock::get()?.unix_timestamp);
+        require!(self.auction.is_closed_to_bids(), AuctionError::AuctionNotEnded);
+        let deposit = self.sealed_bid.deposit;
+        let payment = Payment::new(
+            &self.auction,
+            &self.system_program,
+            self.currency_mint.as_deref(),
+            self.currency_token_program.as_ref(),
+        )?;
+        let seeds = [
+            b"vault",
+            self.auction.to_account_info().key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let (refund, forfeited) = if self.auction.current_bidder == Some(self.bidder.key()) {
+            // the clearing price stays in the vault for the maker to claim
+            (deposit.checked_sub(self.auction.clearing_price().unwrap_or(0)).ok_or(AuctionError::ArithmeticOverflow)?, 0)
+        } else if self.sealed_bid.revealed.is_none()
+            && matches!(self.auction.kind, AuctionKind::Sealed { forfeit_unrevealed: true, .. })
+        {
+            (0, deposit)
+        } else {
+            (deposit, 0)
+        };
+        payment.transfer(
+            &self.vault.to_account_info(),
+            self.vault_currency_account.as_deref(),
+            &self.bidder.to_account_info(),
+            self.bidder_currency_account.as_deref_mut(),
+            refund,
+            signer_seeds,
+        )?;
+        payment.transfer(
+            &self.vault.to_account_info(),
+            self.vault_currency_account.as_deref(),
+            &self.maker.to_account_info(),
+            self.maker_currency_account.as_deref_mut(),
+            forfeited,
+            signer_seeds,
+        )?;
+        emit!(DepositWithdrawn {
+            auction: self.auction.key(),
+            bidder: self.bidder.key(),
+            refund,
+            forfeited,
+        });
+        self.auction.open_escrows = self.auction.open_escrows.checked_sub(1).ok_or(AuctionError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
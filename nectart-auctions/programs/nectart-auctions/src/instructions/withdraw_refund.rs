@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{token_interface::{self, TokenInterface}};
+
+use crate::payment::Payment;
+use crate::events::RefundWithdrawn;
+use crate::state::{Auction, RefundLedger, VaultState};
+use crate::errors::AuctionError;
+
+/// Pays back to a bidder the bids that were outbid. Anyone can call it, so that the maker can
+/// release the ledgers left open and close the auction.
+#[derive(Accounts)]
+pub struct WithdrawRefund<'info> {
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub bidder: SystemAccount<'info>,
+    /// CHECK: mint of the NFT or address of the Core asset, only used to derive the auction
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"auction", mint.key().as_ref(), &auction.id.to_le_bytes()],
+        bump = auction.bump,
+    )]
+    pub auction: Account<'info, Auction>,
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"state", auction.key().as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [b"refund", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = refund_ledger.bump,
+    )]
+    pub refund_ledger: Account<'info, RefundLedger>,
+    /// The following accounts are only needed for auctions in a token.
+    pub currency_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(
+        mut,
+        token::mint = currency_mint,
+        token::authority = bidder,
+        token::token_program = currency_token_program,
+    )]
+    pub bidder_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account(
+        mut,
+        associated_token::mint = currency_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = currency_token_program,
+    )]
+    pub vault_currency_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub currency_token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawRefund<'info> {
+    /// Sends the refundable part of the ledger to the bidder. The ledger is closed once nothing
+    /// can be credited to it anymore: when the bidder does not lead, or when bidding is over.
+    pub fn withdraw(&mut self) -> Result<()> {
+        self.auction.refresh_status(Clock::get()?.unix_timestamp);
+        let locked = self.refund_ledger.locked(&self.auction);
+        let refundable = self.refund_ledger.refundable(&self.auction)?;
+        require!(refundable > 0 || self.auction.is_closed_to_bids(), AuctionError::NothingToRefund);
+        let payment = Payment::new(
+            &self.auction,
+            &self.system_program,
+            self.currency_mint.as_deref(),
+            self.currency_token_program.as_ref(),
+        )?;
+        let seeds = [
+            b"vault",
+            self.auction.to_account_info().key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        payment.transfer(
+            &self.vault.to_account_info(),
+            self.vault_currency_account.as_deref(),
+            &self.bidder.to_account_info(),
+            self.bidder_currency_account.as_deref_mut(),
+            refundable,
+            signer_seeds,
+        )?;
+        emit!(RefundWithdrawn {
+            auction: self.auction.key(),
+            bidder: self.bidder.key(),
+            amount: refundable,
+        });
+        self.refund_ledger.deposited = locked;
+        if locked == 0 || self.auction.is_closed_to_bids() {
+            self.auction.open_escrows = self.auction.open_escrows.checked_sub(1).ok_or(AuctionError::ArithmeticOverflow)?;
+            self.refund_ledger.close(self.bidder.to_account_info())?;
+        }
+        Ok(())
+    }
+}
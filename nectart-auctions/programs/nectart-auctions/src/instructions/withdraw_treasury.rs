@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::payment::Payment;
+use crate::events::TreasuryWithdrawn;
+use crate::state::Config;
+use crate::errors::AuctionError;
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ AuctionError::BadAccount,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = config.treasury_bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+    /// The following accounts are only needed to withdraw fees collected in a token.
+    pub currency_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        mut,
+        token::mint = currency_mint,
+        token::authority = admin,
+        token::token_program = currency_token_program,
+    )]
+    pub admin_currency_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        associated_token::mint = currency_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = currency_token_program,
+    )]
+    pub treasury_currency_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub currency_token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawTreasury<'info> {
+    /// Sends `amount` of collected fees to the admin, in SOL or in the token of `currency_mint`.
+    /// The treasury keeps its rent exemption.
+    pub fn withdraw(&mut self, amount: u64) -> Result<()> {
+        let payment = Payment::with_currency(
+            &self.system_program,
+            self.currency_mint.as_deref(),
+            self.currency_token_program.as_ref(),
+        )?;
+        if self.currency_mint.is_none() {
+            let available = self.treasury.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+            require!(amount <= available, AuctionError::InsufficientTreasury);
+        }
+        let seeds = [b"treasury".as_ref(), &[self.config.treasury_bump]];
+        let signer_seeds = &[&seeds[..]];
+        payment.transfer(
+            &self.treasury.to_account_info(),
+            self.treasury_currency_account.as_deref(),
+            &self.admin.to_account_info(),
+            self.admin_currency_account.as_deref_mut(),
+            amount,
+            signer_seeds,
+        )?;
+        emit!(TreasuryWithdrawn {
+            admin: self.admin.key(),
+            currency: self.currency_mint.as_ref().map(|currency_mint| currency_mint.key()),
+            amount,
+        });
+        Ok(())
+    }
+}
@@ -1,9 +1,15 @@
 use anchor_lang::prelude::*;
 pub use instructions::*;
+pub use state::{AuctionBounds, AuctionKind, LeafProof, NftStandard, PriceCurve, SoftClose};
 
+mod custody;
+mod eligibility;
+pub mod events;
 mod instructions;
-mod state;
-mod errors;
+mod payment;
+mod royalties;
+pub mod state;
+pub mod errors;
 
 declare_id!("4zoHXad7ksVtaDgP2YqgCzx8DErSiXgDVsLBaodHpHuh");
 
@@ -11,25 +17,123 @@ declare_id!("4zoHXad7ksVtaDgP2YqgCzx8DErSiXgDVsLBaodHpHuh");
 pub mod nectart_auctions {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        ctx.accounts.initialize(&ctx.bumps)
+    pub fn initialize(ctx: Context<Initialize>, fee_bps: u16) -> Result<()> {
+        ctx.accounts.initialize(fee_bps, &ctx.bumps)
     }
 
-    pub fn create_auction(ctx: Context<CreateAuction>, start_time: i64, deadline: i64, min_price: u64, min_increment: u64) -> Result<()> {
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        fee_bps: Option<u16>,
+        crank_reward_bps: Option<u16>,
+        crank_delay: Option<i64>,
+        bounds: Option<AuctionBounds>,
+        delivery_grace_period: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.update(fee_bps, crank_reward_bps, crank_delay, bounds, delivery_grace_period)
+    }
+
+    pub fn migrate_config(ctx: Context<MigrateConfig>, fee_bps: u16) -> Result<()> {
+        ctx.accounts.check()?;
+        ctx.accounts.migrate(fee_bps, &ctx.bumps)
+    }
+
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        ctx.accounts.withdraw(amount)
+    }
+
+    pub fn add_collection(ctx: Context<AddCollection>) -> Result<()> {
+        ctx.accounts.add(&ctx.bumps)
+    }
+
+    pub fn remove_collection(ctx: Context<RemoveCollection>) -> Result<()> {
+        ctx.accounts.remove()
+    }
+
+    pub fn add_creator(ctx: Context<AddCreator>, creator: Pubkey) -> Result<()> {
+        ctx.accounts.add(creator, &ctx.bumps)
+    }
+
+    pub fn remove_creator(ctx: Context<RemoveCreator>) -> Result<()> {
+        ctx.accounts.remove()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_auction<'info>(ctx: Context<'_, '_, 'info, 'info, CreateAuction<'info>>, start_time: i64, deadline: i64, min_price: u64, min_increment: u64, soft_close: Option<SoftClose>, kind: AuctionKind, buy_now_price: Option<u64>, leaf: Option<LeafProof>) -> Result<()> {
+        ctx.accounts.check_eligibility(leaf.as_ref())?;
         ctx.accounts.create(start_time, deadline, min_price, min_increment, &ctx.bumps)?;
-        ctx.accounts.approve()?;
-        ctx.accounts.freeze()
+        ctx.accounts.configure(kind, soft_close, buy_now_price)?;
+        ctx.accounts.record_royalties(leaf.as_ref())?;
+        ctx.accounts.take_custody(leaf.as_ref(), ctx.remaining_accounts)
+    }
+
+    pub fn update_auction(
+        ctx: Context<UpdateAuction>,
+        start_time: Option<i64>,
+        deadline: Option<i64>,
+        min_price: Option<u64>,
+        min_increment: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.update(start_time, deadline, min_price, min_increment)
+    }
+
+    pub fn add_lot_item(ctx: Context<AddLotItem>) -> Result<()> {
+        ctx.accounts.check()?;
+        ctx.accounts.add(&ctx.bumps)?;
+        ctx.accounts.take_custody()
+    }
+
+    pub fn bid(ctx: Context<Bid>, amount: u64) -> Result<()> {
+        ctx.accounts.bid(amount, &ctx.bumps)
+    }
+
+    pub fn withdraw_refund(ctx: Context<WithdrawRefund>) -> Result<()> {
+        ctx.accounts.withdraw()
+    }
+
+    pub fn commit_bid(ctx: Context<CommitBid>, commitment: [u8; 32], deposit: u64) -> Result<()> {
+        ctx.accounts.commit(commitment, deposit, &ctx.bumps)?;
+        ctx.accounts.deposit()
+    }
+
+    pub fn reveal_bid(ctx: Context<RevealBid>, amount: u64, salt: [u8; 32]) -> Result<()> {
+        ctx.accounts.reveal(amount, salt)
+    }
+
+    pub fn withdraw_deposit(ctx: Context<WithdrawDeposit>) -> Result<()> {
+        ctx.accounts.withdraw()
+    }
+
+    pub fn buy<'info>(ctx: Context<'_, '_, 'info, 'info, Buy<'info>>, max_price: u64, root: Option<[u8; 32]>) -> Result<()> {
+        ctx.accounts.buy(max_price)?;
+        // creator accounts come first, followed by the proof nodes of a compressed NFT
+        let creators = ctx.accounts.auction.creators.len().min(ctx.remaining_accounts.len());
+        let (creator_accounts, proof) = ctx.remaining_accounts.split_at(creators);
+        ctx.accounts.pay(creator_accounts)?;
+        ctx.accounts.deliver(root, proof)
+    }
+
+    pub fn claim_proceeds<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimProceeds<'info>>) -> Result<()> {
+        ctx.accounts.claim_proceeds(ctx.remaining_accounts)
+    }
+
+    pub fn claim_nft<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimNFT<'info>>, root: Option<[u8; 32]>) -> Result<()> {
+        ctx.accounts.claim_nft(root, ctx.remaining_accounts)
+    }
+
+    pub fn settle<'info>(ctx: Context<'_, '_, 'info, 'info, Settle<'info>>, root: Option<[u8; 32]>) -> Result<()> {
+        ctx.accounts.settle(root, ctx.remaining_accounts)
     }
 
-    pub fn bid(ctx: Context<Bid>, lamports: u64) -> Result<()> {
-        ctx.accounts.bid(lamports)
+    pub fn cancel_auction<'info>(ctx: Context<'_, '_, 'info, 'info, CancelAuction<'info>>, root: Option<[u8; 32]>) -> Result<()> {
+        ctx.accounts.cancel(root, ctx.remaining_accounts)
     }
 
-    pub fn claim_sol(ctx: Context<ClaimSol>) -> Result<()> {
-        ctx.accounts.claim_sol()
+    pub fn abort_auction(ctx: Context<AbortAuction>) -> Result<()> {
+        ctx.accounts.abort()
     }
 
-    pub fn claim_nft(ctx: Context<ClaimNFT>) -> Result<()> {
-        ctx.accounts.claim_nft()
+    pub fn close_auction(ctx: Context<CloseAuction>) -> Result<()> {
+        ctx.accounts.close_vault_account()?;
+        ctx.accounts.close_vault()
     }
 }
@@ -0,0 +1,155 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token_interface::{
+    close_account, harvest_withheld_tokens_to_mint, spl_token_2022::{
+        extension::{transfer_fee::TransferFeeAmount, BaseStateWithExtensions, StateWithExtensions},
+        state::Account as SplTokenAccount,
+    },
+    transfer_checked, CloseAccount, HarvestWithheldTokensToMint, Mint, TokenAccount,
+    TokenInterface, TransferChecked,
+};
+
+use crate::state::Auction;
+use crate::errors::AuctionError;
+
+/// Moves funds in the currency of an auction: lamports through the system program, or tokens of
+/// `Auction::currency` through its token program.
+pub struct Payment<'a, 'info> {
+    system_program: &'a Program<'info, System>,
+    currency: Option<(&'a InterfaceAccount<'info, Mint>, &'a Interface<'info, TokenInterface>)>,
+}
+
+impl<'a, 'info> Payment<'a, 'info> {
+    /// Checks the currency accounts passed to an instruction against the auction currency. They
+    /// are ignored for auctions in SOL.
+    pub fn new(
+        auction: &Auction,
+        system_program: &'a Program<'info, System>,
+        currency_mint: Option<&'a InterfaceAccount<'info, Mint>>,
+        currency_token_program: Option<&'a Interface<'info, TokenInterface>>,
+    ) -> Result<Self> {
+        match auction.currency {
+            Some(currency) => {
+                let mint = currency_mint.ok_or(AuctionError::BadCurrency)?;
+                require_keys_eq!(mint.key(), currency, AuctionError::BadCurrency);
+                Self::with_currency(system_program, Some(mint), currency_token_program)
+            }
+            None => Self::with_currency(system_program, None, None),
+        }
+    }
+
+    /// Pays in the token of `currency_mint`, or in SOL when it is `None`.
+    pub fn with_currency(
+        system_program: &'a Program<'info, System>,
+        currency_mint: Option<&'a InterfaceAccount<'info, Mint>>,
+        currency_token_program: Option<&'a Interface<'info, TokenInterface>>,
+    ) -> Result<Self> {
+        let currency = match currency_mint {
+            Some(mint) => {
+                let token_program = currency_token_program.ok_or(AuctionError::BadCurrency)?;
+                require_keys_eq!(*mint.to_account_info().owner, token_program.key(), AuctionError::BadCurrency);
+                Some((mint, token_program))
+            }
+            None => None,
+        };
+        Ok(Self { system_program, currency })
+    }
+
+    /// Sends `amount` from `from` to `to`, or from `from_account` to `to_account` for auctions in
+    /// a token, in which case `from` is the authority of `from_account`. Returns the amount
+    /// actually received, which is lower than `amount` for mints charging a transfer fee.
+    pub fn transfer(
+        &self,
+        from: &AccountInfo<'info>,
+        from_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+        to: &AccountInfo<'info>,
+        to_account: Option<&mut InterfaceAccount<'info, TokenAccount>>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<u64> {
+        if amount == 0 {
+            return Ok(0);
+        }
+        match self.currency {
+            Some((mint, token_program)) => {
+                let from_account = from_account.ok_or(AuctionError::BadCurrency)?;
+                let to_account = to_account.ok_or(AuctionError::BadCurrency)?;
+                let balance_before = to_account.amount;
+                let cpi_ctx = CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    TransferChecked {
+                        from: from_account.to_account_info(),
+                        mint: mint.to_account_info(),
+                        to: to_account.to_account_info(),
+                        authority: from.clone(),
+                    },
+                    signer_seeds,
+                );
+                transfer_checked(cpi_ctx, amount, mint.decimals)?;
+                to_account.reload()?;
+                Ok(to_account.amount.checked_sub(balance_before).ok_or(AuctionError::ArithmeticOverflow)?)
+            }
+            None => {
+                let cpi_ctx = CpiContext::new_with_signer(
+                    self.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: from.clone(),
+                        to: to.clone(),
+                    },
+                    signer_seeds,
+                );
+                system_program::transfer(cpi_ctx, amount)?;
+                Ok(amount)
+            }
+        }
+    }
+
+    /// Closes the token account of the vault, sending its rent to `destination`. Transfer fees
+    /// withheld on the account are harvested to the mint first, as Token-2022 refuses to close an
+    /// account holding some.
+    pub fn close_vault_account(
+        &self,
+        vault: &AccountInfo<'info>,
+        vault_account: &InterfaceAccount<'info, TokenAccount>,
+        destination: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let Some((mint, token_program)) = self.currency else {
+            return Ok(());
+        };
+        let withheld_amount = {
+            let data = vault_account.to_account_info();
+            let data = data.try_borrow_data()?;
+            StateWithExtensions::<SplTokenAccount>::unpack(&data)?
+                .get_extension::<TransferFeeAmount>()
+                .map_or(0, |fee| u64::from(fee.withheld_amount))
+        };
+        if withheld_amount > 0 {
+            let cpi_ctx = CpiContext::new(
+                token_program.to_account_info(),
+                HarvestWithheldTokensToMint {
+                    token_program_id: token_program.to_account_info(),
+                    mint: mint.to_account_info(),
+                },
+            );
+            harvest_withheld_tokens_to_mint(cpi_ctx, vec![vault_account.to_account_info()])?;
+        }
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            CloseAccount {
+                account: vault_account.to_account_info(),
+                destination: destination.clone(),
+                authority: vault.clone(),
+            },
+            signer_seeds,
+        );
+        close_account(cpi_ctx)
+    }
+}
+
+/// What is left of `amount` once every deduction has been taken from it.
+pub fn net_amount(amount: u64, deductions: &[u64]) -> Result<u64> {
+    deductions
+        .iter()
+        .try_fold(amount, |net, deduction| net.checked_sub(*deduction))
+        .ok_or(error!(AuctionError::ArithmeticOverflow))
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{metadata::MetadataAccount, token_interface::TokenAccount};
+
+use crate::payment::Payment;
+use crate::state::{Auction, RoyaltyShare};
+use crate::errors::AuctionError;
+
+/// Shares of the royalties of a Token Metadata NFT. Only verified creators are paid: the share of
+/// the others goes to the maker.
+pub fn royalty_shares(metadata: &MetadataAccount) -> Vec<RoyaltyShare> {
+    metadata.creators
+        .iter()
+        .flatten()
+        .filter(|creator| creator.verified && creator.share > 0)
+        .map(|creator| RoyaltyShare {
+            address: creator.address,
+            share: creator.share,
+        })
+        .collect()
+}
+
+/// Pays the creators of the NFT their royalties on a sale at `price`, and returns the total paid.
+///
+/// `creator_accounts` holds one account per creator recorded on the auction, in the same order:
+/// the creator itself for auctions in SOL, or a token account it owns for auctions in a token.
+/// A SOL royalty that would leave the creator below rent exemption cannot be sent: it is skipped
+/// and stays with the maker.
+pub fn pay_royalties<'info>(
+    payment: &Payment<'_, 'info>,
+    auction: &Auction,
+    price: u64,
+    from: &AccountInfo<'info>,
+    from_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    creator_accounts: &'info [AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<u64> {
+    require!(creator_accounts.len() == auction.creators.len(), AuctionError::BadCreator);
+    let rent = Rent::get()?;
+    let mut paid: u64 = 0;
+    for (creator, creator_account) in auction.creators.iter().zip(creator_accounts) {
+        let royalty = auction.royalty(creator, price);
+        match auction.currency {
+            Some(_) => {
+                let mut token_account = InterfaceAccount::<TokenAccount>::try_from(creator_account)?;
+                require_keys_eq!(token_account.owner, creator.address, AuctionError::BadCreator);
+                payment.transfer(from, from_account, creator_account, Some(&mut token_account), royalty, signer_seeds)?;
+            }
+            None => {
+                require_keys_eq!(creator_account.key(), creator.address, AuctionError::BadCreator);
+                let balance = creator_account.lamports().saturating_add(royalty);
+                if !rent.is_exempt(balance, creator_account.data_len()) {
+                    continue;
+                }
+                payment.transfer(from, from_account, creator_account, None, royalty, signer_seeds)?;
+            }
+        }
+        paid = paid.checked_add(royalty).ok_or(AuctionError::ArithmeticOverflow)?;
+    }
+    Ok(paid)
+}
@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+
+/// Marks a collection as approved by the admin. Only NFTs of approved collections can be
+/// auctioned.
+#[account]
+#[derive(InitSpace)]
+pub struct ApprovedCollection {
+    /// Mint of a Token Metadata collection or address of a Metaplex Core collection.
+    pub collection_mint: Pubkey,
+    pub bump: u8,
+}
@@ -0,0 +1,10 @@
+use anchor_lang::prelude::*;
+
+/// Marks a creator as approved by the admin. NFTs whose first verified creator is approved can be
+/// auctioned even without a verified collection.
+#[account]
+#[derive(InitSpace)]
+pub struct ApprovedCreator {
+    pub creator: Pubkey,
+    pub bump: u8,
+}
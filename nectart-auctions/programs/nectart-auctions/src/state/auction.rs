@@ -1,5 +1,130 @@
 use anchor_lang::prelude::*;
 
+use super::{AuctionBounds, MAX_CRANK_REWARD_BPS, MAX_FEE_BPS};
+use crate::errors::AuctionError;
+
+/// Lifecycle of an auction. `Scheduled`, `Live`, `Revealing` and `Ended` follow the clock and are
+/// brought up to date by every instruction touching the auction; the other states are reached
+/// through claims and cancellation.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum AuctionStatus {
+    Scheduled,
+    Live,
+    /// Sealed-bid auctions only: bidding is closed and bidders reveal their bids.
+    Revealing,
+    Ended,
+    NftClaimed,
+    SolClaimed,
+    Settled,
+    Cancelled,
+    /// Lot auctions only: the maker is taking the NFTs back over several cancellations. The
+    /// auction is closed to bids and becomes `Cancelled` once every NFT is handed back.
+    Cancelling,
+}
+
+/// How the price of a Dutch auction decays from its start price to `Auction::min_price`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum PriceCurve {
+    /// Straight line from the start price at `start_time` to the floor at `deadline`.
+    Linear,
+    /// The distance to the floor halves every `half_life` seconds, linearly within each period.
+    Exponential { half_life: i64 },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum AuctionKind {
+    /// Ascending auction: the highest bid at the deadline wins.
+    English,
+    /// Descending auction: the first buyer at the current price wins immediately.
+    Dutch { start_price: u64, curve: PriceCurve },
+    /// Sealed-bid second-price auction: bidders commit to a bid until the deadline and reveal it
+    /// until `reveal_deadline`. The highest revealed bid wins and pays the second highest one.
+    /// Deposits of unrevealed bids go to the maker if `forfeit_unrevealed` is set.
+    Sealed { reveal_deadline: i64, forfeit_unrevealed: bool },
+}
+
+/// Anti-sniping rule: a bid placed less than `window` seconds before the deadline pushes the
+/// deadline out by `extension` seconds, for at most `max_extension` seconds overall if set.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct SoftClose {
+    pub window: i64,
+    pub extension: i64,
+    pub max_extension: Option<i64>,
+}
+
+/// Reserve, increment and schedule of an auction, which its maker can change until bidding
+/// starts.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct AuctionTerms {
+    pub start_time: i64,
+    pub deadline: i64,
+    pub min_price: u64,
+    pub min_increment: u64,
+}
+
+/// How the auctioned NFT is held while the auction runs.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum NftStandard {
+    /// Non-programmable Token Metadata NFT, frozen in the maker's wallet with the auction as
+    /// delegate.
+    Legacy,
+    /// Programmable NFT, left in the maker's wallet under a Token Metadata sale delegation to the
+    /// auction.
+    Programmable,
+    /// Token-2022 NFT without Token Metadata accounts, held in escrow by the auction.
+    Token2022,
+    /// Metaplex Core asset, left in the maker's wallet frozen by a freeze delegate and movable by a
+    /// transfer delegate, both owned by the auction.
+    Core,
+    /// Bubblegum compressed NFT, left in the maker's wallet with the auction as leaf delegate.
+    /// Leaves cannot be frozen, so the proceeds are only released once the leaf is delivered.
+    Compressed,
+}
+
+/// Leaf of a compressed NFT, as needed to prove it to Bubblegum at settlement.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct CompressedLeaf {
+    pub merkle_tree: Pubkey,
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+}
+
+/// Proof of ownership of a compressed NFT given at auction creation. `metadata` holds the
+/// Borsh-serialized Bubblegum `MetadataArgs` of the leaf, and the proof nodes are passed as
+/// remaining accounts.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct LeafProof {
+    pub root: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+    pub metadata: Vec<u8>,
+}
+
+/// Maximum number of creators sharing the royalties of an NFT.
+pub const MAX_CREATORS: usize = 5;
+
+/// Share of the royalties owed to a verified creator, in percent.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct RoyaltyShare {
+    pub address: Pubkey,
+    pub share: u8,
+}
+
+/// Seeds of an auction PDA, owned so that they can be kept while the auction is borrowed.
+pub struct AuctionSeeds {
+    mint: Pubkey,
+    id: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl AuctionSeeds {
+    pub fn as_seeds(&self) -> [&[u8]; 4] {
+        [b"auction", self.mint.as_ref(), &self.id, &self.bump]
+    }
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct Auction {
@@ -8,8 +133,352 @@ pub struct Auction {
     pub min_price: u64,
     pub current_bidder: Option<Pubkey>,
     pub current_bid: Option<u64>,
+    /// Second highest revealed bid of a sealed-bid auction.
+    pub second_bid: Option<u64>,
     pub min_increment: u64,
     pub mint: Pubkey,
+    /// Index of the auction among the auctions of its mint, part of its seeds.
+    pub id: u64,
     pub maker: Pubkey,
+    /// Mint of the token bids are made in, or `None` for SOL.
+    pub currency: Option<Pubkey>,
+    pub standard: NftStandard,
+    /// Leaf of a compressed NFT.
+    pub leaf: Option<CompressedLeaf>,
+    pub status: AuctionStatus,
+    pub kind: AuctionKind,
+    pub soft_close: Option<SoftClose>,
+    /// Price at which an English auction can be bought outright.
+    pub buy_now_price: Option<u64>,
+    /// Whether the NFT was bought with `buy`, whose price is paid by the buyer directly rather
+    /// than out of the vault.
+    pub bought: bool,
+    /// Protocol fee as set in the config when the auction was created, in basis points.
+    pub fee_bps: u16,
+    /// Royalties of the NFT as recorded in its metadata when the auction was created.
+    pub seller_fee_basis_points: u16,
+    #[max_len(MAX_CREATORS)]
+    pub creators: Vec<RoyaltyShare>,
+    /// Total number of seconds the deadline has been pushed out by the soft close.
+    pub extended_by: i64,
+    /// Longest duration of the auction as set in the config when it was created. The soft close
+    /// never pushes the deadline past `start_time + max_duration`.
+    pub max_duration: i64,
+    /// Number of per-bidder accounts whose funds are still held by the vault.
+    pub open_escrows: u32,
+    /// Number of NFTs bundled with the NFT of the auction in its lot.
+    pub lot_items: u8,
     pub bump: u8,
 }
+
+impl Auction {
+    /// Seeds the auction PDA signs with, as the delegate or the owner of the NFT.
+    pub fn signer_seeds(&self) -> AuctionSeeds {
+        AuctionSeeds {
+            mint: self.mint,
+            id: self.id.to_le_bytes(),
+            bump: [self.bump],
+        }
+    }
+
+    pub fn refresh_status(&mut self, now: i64) {
+        if self.status == AuctionStatus::Scheduled && now >= self.start_time {
+            self.status = AuctionStatus::Live;
+        }
+        if self.status == AuctionStatus::Live && now >= self.deadline {
+            self.status = AuctionStatus::Ended;
+            if let AuctionKind::Sealed { .. } = self.kind {
+                self.status = AuctionStatus::Revealing;
+            }
+        }
+        if let AuctionKind::Sealed { reveal_deadline, .. } = self.kind {
+            if self.status == AuctionStatus::Revealing && now >= reveal_deadline {
+                self.status = AuctionStatus::Ended;
+            }
+        }
+    }
+
+    pub fn require_live(&self) -> Result<()> {
+        match self.status {
+            AuctionStatus::Live => Ok(()),
+            AuctionStatus::Scheduled => err!(AuctionError::AuctionNotStarted),
+            AuctionStatus::Cancelled | AuctionStatus::Cancelling => err!(AuctionError::AuctionCancelled),
+            _ => err!(AuctionError::AuctionEnded),
+        }
+    }
+
+    /// Checks the terms can still change: the auction has not started, or has no bid yet.
+    pub fn require_editable(&self) -> Result<()> {
+        match self.status {
+            AuctionStatus::Scheduled | AuctionStatus::Live => {
+                require!(
+                    self.current_bidder.is_none() && self.open_escrows == 0,
+                    AuctionError::AuctionHasBids,
+                );
+                Ok(())
+            }
+            AuctionStatus::Cancelled | AuctionStatus::Cancelling => err!(AuctionError::AuctionCancelled),
+            _ => err!(AuctionError::AuctionEnded),
+        }
+    }
+
+    pub fn terms(&self) -> AuctionTerms {
+        AuctionTerms {
+            start_time: self.start_time,
+            deadline: self.deadline,
+            min_price: self.min_price,
+            min_increment: self.min_increment,
+        }
+    }
+
+    /// Replaces the terms and brings the status in line with the new schedule.
+    pub fn set_terms(&mut self, terms: AuctionTerms, now: i64) {
+        self.start_time = terms.start_time;
+        self.deadline = terms.deadline;
+        self.min_price = terms.min_price;
+        self.min_increment = terms.min_increment;
+        self.status = AuctionStatus::Scheduled;
+        self.refresh_status(now);
+    }
+
+    /// Checks the prices and the schedule against the kind and the buy now price of the auction,
+    /// and the reveal period of a sealed-bid auction against `bounds`.
+    pub fn check_terms(&self, bounds: &AuctionBounds) -> Result<()> {
+        if let AuctionKind::Dutch { start_price, .. } = self.kind {
+            require!(start_price >= self.min_price, AuctionError::InvalidPriceCurve);
+        }
+        if let AuctionKind::Sealed { reveal_deadline, .. } = self.kind {
+            require!(reveal_deadline > self.deadline, AuctionError::InvalidSealedBid);
+            bounds.check_reveal(self.deadline, reveal_deadline)?;
+        }
+        if let Some(buy_now_price) = self.buy_now_price {
+            require!(buy_now_price > self.min_price, AuctionError::InvalidBuyNowPrice);
+        }
+        Ok(())
+    }
+
+    pub fn require_revealing(&self) -> Result<()> {
+        match self.status {
+            AuctionStatus::Revealing => Ok(()),
+            AuctionStatus::Scheduled | AuctionStatus::Live => err!(AuctionError::RevealNotStarted),
+            AuctionStatus::Cancelled | AuctionStatus::Cancelling => err!(AuctionError::AuctionCancelled),
+            _ => err!(AuctionError::RevealEnded),
+        }
+    }
+
+    /// Whether bidding and revealing are over, so that funds held for bidders can be released.
+    pub fn is_closed_to_bids(&self) -> bool {
+        !matches!(
+            self.status,
+            AuctionStatus::Scheduled | AuctionStatus::Live | AuctionStatus::Revealing
+        )
+    }
+
+    /// Ranks a revealed sealed bid. Ties go to the earliest revealer.
+    pub fn reveal(&mut self, bidder: Pubkey, amount: u64) {
+        match self.current_bid {
+            Some(current_bid) if amount <= current_bid => {
+                if Some(amount) > self.second_bid {
+                    self.second_bid = Some(amount);
+                }
+            }
+            _ => {
+                self.second_bid = self.current_bid;
+                self.current_bid = Some(amount);
+                self.current_bidder = Some(bidder);
+            }
+        }
+    }
+
+    /// Amount owed to the maker by the winner, if any. Sealed-bid auctions clear at the second
+    /// highest revealed bid, or at `min_price` when there is a single revealed bid.
+    pub fn clearing_price(&self) -> Option<u64> {
+        match self.kind {
+            AuctionKind::Sealed { .. } => self.current_bid.map(|_| self.second_bid.unwrap_or(self.min_price)),
+            _ => self.current_bid,
+        }
+    }
+
+    /// Protocol fee taken on `amount`.
+    pub fn fee(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee_bps as u128 / MAX_FEE_BPS as u128) as u64
+    }
+
+    /// Checks the protocol fee, the royalties and the highest crank reward the admin can set fit
+    /// in the price, so that paying them out never fails.
+    pub fn check_deductions(&self) -> Result<()> {
+        let deductions = self.fee_bps as u32 + self.seller_fee_basis_points as u32 + MAX_CRANK_REWARD_BPS as u32;
+        require!(deductions <= MAX_FEE_BPS as u32, AuctionError::DeductionsTooHigh);
+        Ok(())
+    }
+
+    /// Royalties owed to `creator` on a sale at `price`.
+    pub fn royalty(&self, creator: &RoyaltyShare, price: u64) -> u64 {
+        (price as u128 * self.seller_fee_basis_points as u128 * creator.share as u128 / 1_000_000) as u64
+    }
+
+    /// Price the NFT can be bought at right away: the current price of a Dutch auction, or the buy
+    /// now price of an English auction as long as no bid reached it.
+    pub fn buy_price(&self, now: i64) -> Result<u64> {
+        match self.kind {
+            AuctionKind::Dutch { .. } => self.dutch_price(now),
+            AuctionKind::English => {
+                let buy_now_price = self.buy_now_price.ok_or(AuctionError::InvalidAuctionKind)?;
+                require!(self.current_bid < Some(buy_now_price), AuctionError::BidTooLow);
+                Ok(buy_now_price)
+            }
+            AuctionKind::Sealed { .. } => err!(AuctionError::InvalidAuctionKind),
+        }
+    }
+
+    /// Current price of a Dutch auction, never below `min_price`.
+    pub fn dutch_price(&self, now: i64) -> Result<u64> {
+        let AuctionKind::Dutch { start_price, curve } = self.kind else {
+            return err!(AuctionError::InvalidAuctionKind);
+        };
+        let duration = self.deadline.checked_sub(self.start_time).ok_or(AuctionError::ArithmeticOverflow)?;
+        let elapsed = now.saturating_sub(self.start_time).clamp(0, duration) as u128;
+        let duration = duration as u128;
+        let range = start_price.saturating_sub(self.min_price) as u128;
+        let decayed = match curve {
+            PriceCurve::Linear => range * elapsed / duration,
+            PriceCurve::Exponential { half_life } => {
+                let half_life = half_life as u128;
+                let periods = elapsed / half_life;
+                let remaining = if periods >= 128 { 0 } else { range >> periods };
+                let within = remaining / 2 * (elapsed % half_life) / half_life;
+                range - remaining + within
+            }
+        };
+        start_price.checked_sub(decayed as u64).ok_or(error!(AuctionError::ArithmeticOverflow))
+    }
+
+    /// Applies the soft close rule to a bid placed at `now`, within `max_extension` and
+    /// `max_duration`. Returns the number of seconds the deadline was pushed out by.
+    pub fn extend_deadline(&mut self, now: i64) -> Result<i64> {
+        let Some(soft_close) = self.soft_close else {
+            return Ok(0);
+        };
+        if self.deadline.saturating_sub(now) >= soft_close.window {
+            return Ok(0);
+        }
+        let latest_deadline = self.start_time.saturating_add(self.max_duration);
+        let extension = match soft_close.max_extension {
+            Some(max_extension) => soft_close.extension.min(max_extension.saturating_sub(self.extended_by)),
+            None => soft_close.extension,
+        }.min(latest_deadline.saturating_sub(self.deadline)).max(0);
+        self.deadline = self.deadline.checked_add(extension).ok_or(AuctionError::ArithmeticOverflow)?;
+        self.extended_by = self.extended_by.checked_add(extension).ok_or(AuctionError::ArithmeticOverflow)?;
+        Ok(extension)
+    }
+
+    /// Records the winning bid of a Dutch auction, which ends the auction at `now`. Unlike `sell`,
+    /// the price is escrowed in the vault and the NFT and the proceeds are claimed afterwards.
+    pub fn win_dutch(&mut self, bidder: Pubkey, price: u64, now: i64) {
+        self.current_bidder = Some(bidder);
+        self.current_bid = Some(price);
+        self.deadline = now;
+        self.status = AuctionStatus::Ended;
+    }
+
+    /// Records an immediate sale: the NFT and the payment change hands in the same instruction, so
+    /// the auction is settled right away.
+    pub fn sell(&mut self, buyer: Pubkey, price: u64) {
+        self.current_bidder = Some(buyer);
+        self.current_bid = Some(price);
+        self.bought = true;
+        self.status = AuctionStatus::Settled;
+    }
+
+    /// Checks the auction can be cancelled, or that its cancellation is under way.
+    pub fn require_cancellable(&self) -> Result<()> {
+        match self.status {
+            AuctionStatus::Scheduled | AuctionStatus::Live | AuctionStatus::Revealing | AuctionStatus::Ended => {
+                require!(
+                    self.current_bidder.is_none() && self.open_escrows == 0,
+                    AuctionError::AuctionHasBids,
+                );
+                Ok(())
+            }
+            AuctionStatus::Cancelling => Ok(()),
+            AuctionStatus::Cancelled => err!(AuctionError::AuctionCancelled),
+            _ => err!(AuctionError::InvalidAuctionStatus),
+        }
+    }
+
+    /// Cancels the auction, or only closes it to bids if `complete` is false because part of its
+    /// lot is still to be handed back.
+    pub fn mark_cancelled(&mut self, complete: bool) -> Result<()> {
+        self.require_cancellable()?;
+        self.status = if complete {
+            AuctionStatus::Cancelled
+        } else {
+            AuctionStatus::Cancelling
+        };
+        Ok(())
+    }
+
+    /// Checks the NFT can be delivered, which a lot may take several claims to do.
+    pub fn require_nft_claimable(&self) -> Result<()> {
+        match self.status {
+            AuctionStatus::Ended | AuctionStatus::SolClaimed => Ok(()),
+            AuctionStatus::NftClaimed | AuctionStatus::Settled => err!(AuctionError::AlreadyClaimed),
+            AuctionStatus::Scheduled | AuctionStatus::Live | AuctionStatus::Revealing => err!(AuctionError::AuctionNotEnded),
+            AuctionStatus::Cancelled | AuctionStatus::Cancelling => err!(AuctionError::AuctionCancelled),
+        }
+    }
+
+    pub fn mark_nft_claimed(&mut self) -> Result<()> {
+        self.require_nft_claimable()?;
+        self.status = match self.status {
+            AuctionStatus::Ended if self.current_bid.is_some() => AuctionStatus::NftClaimed,
+            _ => AuctionStatus::Settled,
+        };
+        Ok(())
+    }
+
+    pub fn mark_sol_claimed(&mut self) -> Result<()> {
+        self.status = match self.status {
+            AuctionStatus::Ended if self.standard == NftStandard::Compressed && self.current_bid.is_some() => {
+                return err!(AuctionError::NftNotDelivered)
+            }
+            AuctionStatus::Ended => AuctionStatus::SolClaimed,
+            AuctionStatus::NftClaimed => AuctionStatus::Settled,
+            AuctionStatus::SolClaimed | AuctionStatus::Settled => return err!(AuctionError::AlreadyClaimed),
+            AuctionStatus::Scheduled | AuctionStatus::Live | AuctionStatus::Revealing => return err!(AuctionError::AuctionNotEnded),
+            AuctionStatus::Cancelled | AuctionStatus::Cancelling => return err!(AuctionError::AuctionCancelled),
+        };
+        Ok(())
+    }
+
+    /// End of the auction: the deadline, or the end of the reveal period of a sealed-bid auction.
+    pub fn end_time(&self) -> i64 {
+        match self.kind {
+            AuctionKind::Sealed { reveal_deadline, .. } => reveal_deadline,
+            _ => self.deadline,
+        }
+    }
+
+    /// Gives up on delivering the NFT: the winning bid is released like the others and the
+    /// auction ends as if cancelled.
+    pub fn abort(&mut self) -> Result<()> {
+        match self.status {
+            AuctionStatus::Ended => {
+                self.current_bidder = None;
+                self.current_bid = None;
+                self.second_bid = None;
+                self.status = AuctionStatus::Cancelled;
+                Ok(())
+            }
+            AuctionStatus::Scheduled | AuctionStatus::Live | AuctionStatus::Revealing => err!(AuctionError::AuctionNotEnded),
+            AuctionStatus::Cancelled | AuctionStatus::Cancelling => err!(AuctionError::AuctionCancelled),
+            _ => err!(AuctionError::AlreadyClaimed),
+        }
+    }
+
+    /// Whether every claim on the auction has been honoured, so that its accounts can be closed.
+    pub fn is_settled(&self) -> bool {
+        matches!(self.status, AuctionStatus::Settled | AuctionStatus::Cancelled)
+            && self.open_escrows == 0
+    }
+}
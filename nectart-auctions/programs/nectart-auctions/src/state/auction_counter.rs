@@ -0,0 +1,10 @@
+use anchor_lang::prelude::*;
+
+/// Number of auctions ever created for a mint. The next auction of the mint uses it as its id.
+#[account]
+#[derive(InitSpace)]
+pub struct AuctionCounter {
+    pub mint: Pubkey,
+    pub count: u64,
+    pub bump: u8,
+}
@@ -1,8 +1,126 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::AuctionError;
+use crate::events::ConfigUpdated;
+
+pub const MAX_FEE_BPS: u16 = 10_000;
+/// Highest crank reward the admin can set, in basis points.
+pub const MAX_CRANK_REWARD_BPS: u16 = 500;
+
+const DAY: i64 = 24 * 60 * 60;
+/// Crank delay of a new config.
+pub const DEFAULT_CRANK_DELAY: i64 = DAY;
+/// Delivery grace period of a new config.
+pub const DEFAULT_DELIVERY_GRACE_PERIOD: i64 = 7 * DAY;
+
+/// Limits on the parameters of new auctions, set by the admin.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AuctionBounds {
+    /// Shortest time between the start and the deadline of an auction, in seconds.
+    pub min_duration: i64,
+    /// Longest time between the start and the deadline of an auction, in seconds.
+    pub max_duration: i64,
+    /// Longest time between the creation and the start of an auction, in seconds.
+    pub max_lead_time: i64,
+    /// Lowest minimum increment between two bids.
+    pub min_increment: u64,
+}
+
+impl Default for AuctionBounds {
+    fn default() -> Self {
+        Self {
+            min_duration: 1,
+            max_duration: 365 * DAY,
+            max_lead_time: 365 * DAY,
+            min_increment: 0,
+        }
+    }
+}
+
+impl AuctionBounds {
+    /// Checks that the bounds are consistent with each other.
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.min_duration > 0 && self.min_duration <= self.max_duration && self.max_lead_time >= 0,
+            AuctionError::InvalidBounds,
+        );
+        Ok(())
+    }
+
+    /// Checks the schedule and the minimum increment of an auction at `now` against the bounds.
+    pub fn check(&self, start_time: i64, deadline: i64, min_increment: u64, now: i64) -> Result<()> {
+        require!(deadline > start_time, AuctionError::DeadlineBeforeStart);
+        require!(deadline > now, AuctionError::DeadlineInPast);
+        let duration = deadline.checked_sub(start_time).ok_or(AuctionError::ArithmeticOverflow)?;
+        require!(duration >= self.min_duration, AuctionError::AuctionTooShort);
+        require!(duration <= self.max_duration, AuctionError::AuctionTooLong);
+        let lead_time = start_time.checked_sub(now).ok_or(AuctionError::ArithmeticOverflow)?;
+        require!(lead_time <= self.max_lead_time, AuctionError::StartTooFar);
+        require!(min_increment >= self.min_increment, AuctionError::IncrementTooLow);
+        Ok(())
+    }
+
+    /// Checks the reveal period of a sealed-bid auction, which can last as long as an auction.
+    pub fn check_reveal(&self, deadline: i64, reveal_deadline: i64) -> Result<()> {
+        let reveal_period = reveal_deadline.checked_sub(deadline).ok_or(AuctionError::ArithmeticOverflow)?;
+        require!(reveal_period <= self.max_duration, AuctionError::RevealTooLong);
+        Ok(())
+    }
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct Config {
     pub admin: Pubkey,
+    /// Share of the proceeds of every sale going to the treasury, in basis points. Auctions keep
+    /// the fee set when they were created.
+    pub fee_bps: u16,
+    /// Share of the proceeds paid to whoever settles an auction with `settle`, in basis points.
+    pub crank_reward_bps: u16,
+    /// Time after the end of an auction before `settle` pays the crank reward, in seconds, which
+    /// leaves the parties time to settle it themselves for free.
+    pub crank_delay: i64,
+    pub bounds: AuctionBounds,
+    /// Time the maker of a compressed NFT has after the end of its auction to deliver it, in
+    /// seconds. The winner can abort the auction once it has elapsed.
+    pub delivery_grace_period: i64,
+    pub treasury_bump: u8,
     pub bump: u8,
 }
+
+impl Config {
+    /// Config of a new deployment, with the default crank reward, bounds and grace period.
+    pub fn new(admin: Pubkey, fee_bps: u16, treasury_bump: u8, bump: u8) -> Self {
+        Self {
+            admin,
+            fee_bps,
+            crank_reward_bps: 0,
+            crank_delay: DEFAULT_CRANK_DELAY,
+            bounds: AuctionBounds::default(),
+            delivery_grace_period: DEFAULT_DELIVERY_GRACE_PERIOD,
+            treasury_bump,
+            bump,
+        }
+    }
+
+    /// Event reporting the current settings of the config.
+    pub fn updated_event(&self) -> ConfigUpdated {
+        ConfigUpdated {
+            admin: self.admin,
+            fee_bps: self.fee_bps,
+            crank_reward_bps: self.crank_reward_bps,
+            crank_delay: self.crank_delay,
+            bounds: self.bounds,
+            delivery_grace_period: self.delivery_grace_period,
+        }
+    }
+
+    /// Crank reward taken on `amount` when settling at `now` an auction that ended at `end_time`,
+    /// nothing until the crank delay has elapsed.
+    pub fn crank_reward(&self, amount: u64, end_time: i64, now: i64) -> u64 {
+        if now < end_time.saturating_add(self.crank_delay) {
+            return 0;
+        }
+        (amount as u128 * self.crank_reward_bps as u128 / MAX_FEE_BPS as u128) as u64
+    }
+}
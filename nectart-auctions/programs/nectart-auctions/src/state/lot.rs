@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AuctionError;
+
+/// Maximum number of NFTs a lot can bundle on top of the NFT of its auction.
+pub const MAX_LOT_ITEMS: usize = 8;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct LotItem {
+    pub mint: Pubkey,
+    pub delivered: bool,
+}
+
+/// NFTs auctioned together with the NFT of an auction, whose mint seeds the auction. They are held
+/// like the NFT of the auction and delivered to the winner over one or more claims, or handed back
+/// to the maker over one or more cancellations.
+#[account]
+#[derive(InitSpace)]
+pub struct Lot {
+    pub auction: Pubkey,
+    /// Whether the NFT of the auction itself has been delivered, or handed back to the maker.
+    pub lead_delivered: bool,
+    #[max_len(MAX_LOT_ITEMS)]
+    pub items: Vec<LotItem>,
+    pub bump: u8,
+}
+
+impl Lot {
+    pub fn is_delivered(&self) -> bool {
+        self.lead_delivered && self.items.iter().all(|item| item.delivered)
+    }
+
+    /// Marks the item of `mint` as delivered, failing if it is not in the lot or already
+    /// delivered.
+    pub fn deliver(&mut self, mint: &Pubkey) -> Result<()> {
+        let item = self.items
+            .iter_mut()
+            .find(|item| item.mint == *mint)
+            .ok_or(error!(AuctionError::BadAccount))?;
+        require!(!item.delivered, AuctionError::AlreadyClaimed);
+        item.delivered = true;
+        Ok(())
+    }
+}
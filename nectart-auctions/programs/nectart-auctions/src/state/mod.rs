@@ -1,7 +1,19 @@
 pub mod config;
+pub mod approved_collection;
+pub mod approved_creator;
 pub mod auction;
+pub mod auction_counter;
 pub mod vault;
+pub mod sealed_bid;
+pub mod refund_ledger;
+pub mod lot;
 
 pub use config::*;
+pub use approved_collection::*;
+pub use approved_creator::*;
 pub use auction::*;
+pub use auction_counter::*;
 pub use vault::*;
+pub use sealed_bid::*;
+pub use refund_ledger::*;
+pub use lot::*;
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use super::Auction;
+use crate::errors::AuctionError;
+
+/// What a bidder has escrowed in an English auction. Bids are not refunded when outbid: the
+/// outbid amounts stay in the vault until the bidder withdraws them.
+#[account]
+#[derive(InitSpace)]
+pub struct RefundLedger {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    /// Sum of the bids escrowed by the bidder and not withdrawn yet.
+    pub deposited: u64,
+    pub bump: u8,
+}
+
+impl RefundLedger {
+    /// Part of the deposits backing the leading bid, which cannot be withdrawn. Nothing is locked
+    /// once the NFT was bought, even by the leading bidder, as the buyer paid the price directly.
+    pub fn locked(&self, auction: &Auction) -> u64 {
+        if auction.current_bidder == Some(self.bidder) && !auction.bought {
+            auction.current_bid.unwrap_or(0)
+        } else {
+            0
+        }
+    }
+
+    pub fn refundable(&self, auction: &Auction) -> Result<u64> {
+        self.deposited.checked_sub(self.locked(auction)).ok_or(error!(AuctionError::ArithmeticOverflow))
+    }
+}
@@ -0,0 +1,20 @@
+use anchor_lang::{prelude::*, solana_program::hash::hashv};
+
+#[account]
+#[derive(InitSpace)]
+pub struct SealedBid {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    /// `sha256(auction || bidder || amount.to_le_bytes() || salt)`. Binding the auction and the
+    /// bidder keeps a revealed commitment from being replayed by anyone else.
+    pub commitment: [u8; 32],
+    pub deposit: u64,
+    pub revealed: Option<u64>,
+    pub bump: u8,
+}
+
+impl SealedBid {
+    pub fn commitment(auction: &Pubkey, bidder: &Pubkey, amount: u64, salt: &[u8; 32]) -> [u8; 32] {
+        hashv(&[auction.as_ref(), bidder.as_ref(), &amount.to_le_bytes(), salt]).to_bytes()
+    }
+}
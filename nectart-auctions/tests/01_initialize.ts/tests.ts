@@ -13,8 +13,8 @@ const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
 describe("Program initialization", () => {
   it("Program sucessfully initializes", async () => {
     await airdrop_if_needed(provider, admin.publicKey, 5);
-    await program.methods.initialize()
-      .accounts({
+    await program.methods.initialize(0)
+      .accountsPartial({
         admin: admin.publicKey
       })
       .signers([admin])
@@ -27,5 +27,20 @@ describe("Program initialization", () => {
     ], program.programId);
     const accountInfo = await program.account.config.fetch(pda);
     expect(accountInfo.admin.equals(admin.publicKey));
+    expect(accountInfo.feeBps).to.equal(0);
+  });
+
+  it("A config in the current layout cannot be migrated", async () => {
+    try {
+      await program.methods.migrateConfig(0)
+        .accountsPartial({
+          admin: admin.publicKey
+        })
+        .signers([admin])
+        .rpc();
+      expect.fail("Migration should fail");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("BadAccount");
+    }
   });
 });
@@ -1,10 +1,11 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
-import { generateSigner, KeypairSigner, signerIdentity } from "@metaplex-foundation/umi";
-import { findMasterEditionPda, findMetadataPda, mplTokenMetadata, TokenStandard, transferV1 } from "@metaplex-foundation/mpl-token-metadata";
+import { generateSigner, KeypairSigner, percentAmount, signerIdentity } from "@metaplex-foundation/umi";
+import { createNft as metaplexCreateNft, findMasterEditionPda, findMetadataPda, mplTokenMetadata, TokenStandard, transferV1 } from "@metaplex-foundation/mpl-token-metadata";
 import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
-import { airdrop_if_needed, createNft, ONE_SECOND, ONE_MINUTE} from '../lib';
+import { addCollection, airdrop_if_needed, createNft, findApprovedCollection, ONE_SECOND, ONE_MINUTE} from '../lib';
+import { admin } from '../nectart-auctions';
 import { NectartAuctions } from "../../target/types/nectart_auctions";
 import { getAssociatedTokenAddressSync} from "@solana/spl-token";
 import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
@@ -40,19 +41,19 @@ describe("Auction creation", () => {
     nftMint = mint.nftMint;
   });
 
-  it("Creates an auction", async () => {
+  const createAuction = async () => {
     const THIRTY_SECONDS = 30 * ONE_SECOND;
     const mintAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey));
     const nftEdition = findMasterEditionPda(umi, { mint: nftMint.publicKey });
-    const auction = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('auction'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer()], program.programId)[0];
-    const auctionVault = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('vault'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer()], program.programId)[0];
+    const auction = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('auction'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer(), new BN(0).toArrayLike(Buffer, 'le', 8)], program.programId)[0];
+    const auctionVault = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('vault'), auction.toBuffer()], program.programId)[0];
     const nftMetadata = findMetadataPda(umi, { mint: nftMint.publicKey });
 
-    await program.methods.createAuction(new BN(THIRTY_SECONDS), new BN(ONE_MINUTE), new BN(0), new BN(0))
-      .accounts({
+    await program.methods.createAuction(new BN(THIRTY_SECONDS), new BN(ONE_MINUTE), new BN(0), new BN(0), null, { english: {} }, null, null)
+      .accountsPartial({
         payer: auctioneer.publicKey,
         mint: nftMint.publicKey,
-        collectionMint: collectionMint.publicKey,
+        approvedCollection: findApprovedCollection(program, collectionMint),
         mintAta,
         metadata: toWeb3JsPublicKey(nftMetadata[0]),
         edition: toWeb3JsPublicKey(nftEdition[0]),
@@ -61,6 +62,39 @@ describe("Auction creation", () => {
       })
       .signers([web3JsSigner])
       .rpc();
+  }
+
+  it("An NFT of a collection not approved by the admin cannot be auctioned", async () => {
+    await assert.rejects(createAuction, () => true, "Auction creation should fail");
+  });
+
+  it("Only the admin can approve a collection", async () => {
+    await assert.rejects(async () => {
+      await program.methods.addCollection()
+        .accountsPartial({
+          admin: auctioneer.publicKey,
+          collectionMint: collectionMint.publicKey,
+        })
+        .signers([web3JsSigner])
+        .rpc();
+    }, () => true, "Approval should fail");
+  });
+
+  it("Creates an auction", async () => {
+    await addCollection(program, collectionMint);
+    await createAuction();
+  });
+
+  it("The admin can remove a collection", async () => {
+    const approvedCollection = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('collection'), toWeb3JsPublicKey(collectionMint.publicKey).toBuffer()], program.programId)[0];
+    await program.methods.removeCollection()
+      .accountsPartial({
+        admin: admin.publicKey,
+        approvedCollection,
+      })
+      .signers([admin])
+      .rpc();
+    assert.strictEqual(await provider.connection.getAccountInfo(approvedCollection), null);
   });
 
   it("After auction creation, the token should be frozen", async () => {
@@ -78,3 +112,53 @@ describe("Auction creation", () => {
     );
   });
 });
+
+describe("Auction creation without a collection", () => {
+  let soloMint: KeypairSigner;
+  const approvedCreator = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('creator'), toWeb3JsPublicKey(auctioneer.publicKey).toBuffer()], program.programId)[0];
+
+  const createAuction = async () => {
+    const mintAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(soloMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey));
+    const nftEdition = findMasterEditionPda(umi, { mint: soloMint.publicKey });
+    const nftMetadata = findMetadataPda(umi, { mint: soloMint.publicKey });
+    const auction = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('auction'), toWeb3JsPublicKey(soloMint.publicKey).toBuffer(), new BN(0).toArrayLike(Buffer, 'le', 8)], program.programId)[0];
+    await program.methods.createAuction(new BN(30 * ONE_SECOND), new BN(ONE_MINUTE), new BN(0), new BN(0), null, { english: {} }, null, null)
+      .accountsPartial({
+        payer: auctioneer.publicKey,
+        mint: soloMint.publicKey,
+        approvedCreator,
+        mintAta,
+        metadata: toWeb3JsPublicKey(nftMetadata[0]),
+        edition: toWeb3JsPublicKey(nftEdition[0]),
+        auction,
+      })
+      .signers([web3JsSigner])
+      .rpc();
+  }
+
+  before(async () => {
+    // a 1/1 without collection, whose only creator is the auctioneer
+    soloMint = generateSigner(umi);
+    await metaplexCreateNft(umi, {
+      mint: soloMint,
+      name: "GM",
+      symbol: "GM",
+      uri: "https://arweave.net/123",
+      sellerFeeBasisPoints: percentAmount(5.5),
+    }).sendAndConfirm(umi);
+  });
+
+  it("An NFT of a creator not approved by the admin cannot be auctioned", async () => {
+    await assert.rejects(createAuction, () => true, "Auction creation should fail");
+  });
+
+  it("An NFT of an approved creator can be auctioned", async () => {
+    await program.methods.addCreator(toWeb3JsPublicKey(auctioneer.publicKey))
+      .accountsPartial({
+        admin: admin.publicKey,
+      })
+      .signers([admin])
+      .rpc();
+    await createAuction();
+  });
+});
@@ -4,7 +4,7 @@ import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
 import { generateSigner, KeypairSigner, signerIdentity } from "@metaplex-foundation/umi";
 import { findMasterEditionPda, findMetadataPda, mplTokenMetadata } from "@metaplex-foundation/mpl-token-metadata";
 import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
-import { airdrop_if_needed, createNft, ONE_SECOND, ONE_MINUTE} from '../lib';
+import { addCollection, airdrop_if_needed, createNft, findApprovedCollection, findRefundLedger } from '../lib';
 import { NectartAuctions } from "../../target/types/nectart_auctions";
 import { getAssociatedTokenAddressSync} from "@solana/spl-token";
 import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
@@ -48,16 +48,30 @@ let vaultState: anchor.web3.PublicKey;
 let auctionStart: number;
 let auctionEnd: number;
 
+const withdrawRefund = async (bidder: anchor.web3.PublicKey) => {
+  await program.methods.withdrawRefund()
+    .accountsPartial({
+      signer: provider.wallet.publicKey,
+      bidder,
+      mint: nftMint.publicKey,
+      auction,
+      vault,
+      vaultState,
+    })
+    .rpc();
+}
+
 describe("Bids", () => {
   before(async () => {
     umi.use(signerIdentity(auctioneer));
     await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
     const mint  = await createNft(umi);
     collectionMint = mint.collectionMint;
+    await addCollection(program, collectionMint);
     nftMint = mint.nftMint;
-    auction = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('auction'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer()], program.programId)[0];
-    vault = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('vault'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer()], program.programId)[0];
-    vaultState = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('state'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer()], program.programId)[0];
+    auction = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('auction'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer(), new BN(0).toArrayLike(Buffer, 'le', 8)], program.programId)[0];
+    vault = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('vault'), auction.toBuffer()], program.programId)[0];
+    vaultState = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('state'), auction.toBuffer()], program.programId)[0];
 
     await airdrop_if_needed(provider, toWeb3JsPublicKey(bidder1.publicKey), 5);
     await airdrop_if_needed(provider, toWeb3JsPublicKey(bidder2.publicKey), 5);
@@ -69,11 +83,11 @@ describe("Bids", () => {
     const time = Math.round(new Date().getTime() / 1000);
     auctionStart = time + 1;
     auctionEnd = time + 10;
-    await program.methods.createAuction(new BN(auctionStart), new BN(auctionEnd), new BN(0), new BN(0))
-      .accounts({
+    await program.methods.createAuction(new BN(auctionStart), new BN(auctionEnd), new BN(0), new BN(0), null, { english: {} }, null, null)
+      .accountsPartial({
         payer: auctioneer.publicKey,
         mint: nftMint.publicKey,
-        collectionMint: collectionMint.publicKey,
+        approvedCollection: findApprovedCollection(program, collectionMint),
         mintAta,
         metadata: toWeb3JsPublicKey(nftMetadata[0]),
         edition: toWeb3JsPublicKey(nftEdition[0]),
@@ -90,13 +104,12 @@ describe("Bids", () => {
       umi.use(signerIdentity(bidder1));
       await assert.rejects(async () => {
         await program.methods.bid(new BN(1))
-          .accounts({
+          .accountsPartial({
             bidder: bidder1.publicKey,
             mint: nftMint.publicKey,
             auction,
             vault,
             vaultState,
-            precedingBidder: null,
           })
           .signers([web3JsBidder1Signer])
           .rpc();
@@ -114,13 +127,12 @@ describe("Bids", () => {
 
     it("A bet can be made", async () => {
       await program.methods.bid(new BN(1))
-        .accounts({
+        .accountsPartial({
           bidder: bidder1.publicKey,
           mint: nftMint.publicKey,
           auction,
           vault,
           vaultState,
-          precedingBidder: null,
         })
         .signers([web3JsBidder1Signer])
         .rpc();
@@ -141,7 +153,6 @@ describe("Bids", () => {
             auction,
             vault,
             vaultState,
-            precedingBidder: bidder1.publicKey,
           })
           .signers([web3JsBidder2Signer])
           .rpc();
@@ -157,11 +168,27 @@ describe("Bids", () => {
             auction,
             vault,
             vaultState,
-            precedingBidder: toWeb3JsPublicKey(bidder1.publicKey),
           })
           .signers([web3JsBidder2Signer])
           .rpc();
     });
+
+    it("The leading bidder cannot withdraw its bid", async () => {
+      await assert.rejects(async () => {
+        await withdrawRefund(toWeb3JsPublicKey(bidder2.publicKey));
+      }, () => true, "Withdrawal should fail");
+    });
+
+    it("An outbid bidder can withdraw its bid", async () => {
+      const refundLedger = findRefundLedger(program, auction, toWeb3JsPublicKey(bidder1.publicKey));
+      const ledger = await program.account.refundLedger.fetch(refundLedger);
+      assert.strictEqual(ledger.deposited.toNumber(), 1);
+      const before = await provider.connection.getBalance(toWeb3JsPublicKey(bidder1.publicKey));
+      await withdrawRefund(toWeb3JsPublicKey(bidder1.publicKey));
+      const after = await provider.connection.getBalance(toWeb3JsPublicKey(bidder1.publicKey));
+      assert(after > before, "The outbid bidder should be refunded");
+      assert.strictEqual(await provider.connection.getAccountInfo(refundLedger), null);
+    });
   });
 
   describe("After the end of the auction", () => {
@@ -174,13 +201,12 @@ describe("Bids", () => {
       umi.use(signerIdentity(bidder1));
       await assert.rejects(async () => {
         await program.methods.bid(new BN(100))
-          .accounts({
+          .accountsPartial({
             bidder: bidder1.publicKey,
             mint: nftMint.publicKey,
             auction,
             vault,
             vaultState,
-            precedingBidder: bidder2.publicKey,
           })
           .signers([web3JsBidder1Signer])
           .rpc();
@@ -4,9 +4,9 @@ import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
 import { generateSigner, KeypairSigner, Pda, PublicKey, signerIdentity, Umi } from "@metaplex-foundation/umi";
 import { findMasterEditionPda, findMetadataPda, mplTokenMetadata, TokenStandard, transferV1 } from "@metaplex-foundation/mpl-token-metadata";
 import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
-import { airdrop_if_needed, createNft } from '../lib';
+import { addCollection, airdrop_if_needed, createNft, findApprovedCollection } from '../lib';
 import { NectartAuctions } from "../../target/types/nectart_auctions";
-import { getAssociatedTokenAddressSync} from "@solana/spl-token";
+import { getAccount, getAssociatedTokenAddressSync} from "@solana/spl-token";
 import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
 import { Keypair } from "@solana/web3.js";
 import { BN } from "bn.js";
@@ -73,11 +73,12 @@ const initializeAuction = async () => {
   umi.use(signerIdentity(auctioneer));
   const mint  = await createNft(umi);
   collectionMint = mint.collectionMint;
+  await addCollection(program, collectionMint);
   nftMint = mint.nftMint;
   auctioneerAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey));
-  auction = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('auction'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer()], program.programId)[0];
-  vault = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('vault'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer()], program.programId)[0];
-  vaultState = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('state'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer()], program.programId)[0];
+  auction = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('auction'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer(), new BN(0).toArrayLike(Buffer, 'le', 8)], program.programId)[0];
+  vault = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('vault'), auction.toBuffer()], program.programId)[0];
+  vaultState = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('state'), auction.toBuffer()], program.programId)[0];
 
   const mintAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey));
   nftEdition = findMasterEditionPda(umi, { mint: nftMint.publicKey });
@@ -86,11 +87,11 @@ const initializeAuction = async () => {
   const time = Math.round(new Date().getTime() / 1000);
   auctionStart = time;
   auctionEnd = time + 5;
-  await program.methods.createAuction(new BN(auctionStart), new BN(auctionEnd), new BN(0), new BN(0))
-    .accounts({
+  await program.methods.createAuction(new BN(auctionStart), new BN(auctionEnd), new BN(0), new BN(0), null, { english: {} }, null, null)
+    .accountsPartial({
       payer: auctioneer.publicKey,
       mint: nftMint.publicKey,
-      collectionMint: collectionMint.publicKey,
+      approvedCollection: findApprovedCollection(program, collectionMint),
       mintAta,
       metadata: toWeb3JsPublicKey(nftMetadata[0]),
       edition: toWeb3JsPublicKey(nftEdition[0]),
@@ -112,13 +113,12 @@ describe("If a bid is made,", () => {
   before(async () => {
     await initializeAuction();
     await program.methods.bid(new BN(1))
-      .accounts({
+      .accountsPartial({
         bidder: bidder1.publicKey,
         mint: nftMint.publicKey,
         auction,
         vault,
         vaultState,
-        precedingBidder: null,
       })
       .signers([web3JsBidder1Signer])
       .rpc();
@@ -132,9 +132,10 @@ describe("If a bid is made,", () => {
         web3JsSomebodySigner,
       ]) {
         await assert.rejects(async () => {
-          await program.methods.claimNft()
-            .accounts({
+          await program.methods.claimNft(null)
+            .accountsPartial({
               signer: signer.publicKey,
+              signerAta: getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), signer.publicKey),
               auctioneer: auctioneer.publicKey,
               auctioneerAta,
               mint: nftMint.publicKey,
@@ -162,9 +163,10 @@ describe("If a bid is made,", () => {
         web3JsSomebodySigner
       ]) {
         await assert.rejects(async () => {
-          await program.methods.claimNft()
-            .accounts({
+          await program.methods.claimNft(null)
+            .accountsPartial({
               signer: signer.publicKey,
+              signerAta: getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), signer.publicKey),
               auctioneer: auctioneer.publicKey,
               auctioneerAta,
               mint: nftMint.publicKey,
@@ -182,9 +184,10 @@ describe("If a bid is made,", () => {
     it("...except the highest bidder", async () => {
       auctioneerTokenNumberBefore = await howManyTokensHasOwner(umi, auctioneer.publicKey, nftMint.publicKey);
       bidderTokenNumberBefore = await howManyTokensHasOwner(umi, bidder1.publicKey, nftMint.publicKey);
-      await program.methods.claimNft()
-        .accounts({
+      await program.methods.claimNft(null)
+        .accountsPartial({
           signer: bidder1.publicKey,
+          signerAta: getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(bidder1.publicKey)),
           auctioneer: auctioneer.publicKey,
           auctioneerAta,
           mint: nftMint.publicKey,
@@ -197,6 +200,30 @@ describe("If a bid is made,", () => {
         .rpc();
     });
 
+    it("The auction is waiting for the SOL to be claimed", async () => {
+      const auctionAccount = await program.account.auction.fetch(auction);
+      assert.deepStrictEqual(auctionAccount.status, { nftClaimed: {} });
+    });
+
+    it("The NFT cannot be claimed twice", async () => {
+      await assert.rejects(async () => {
+        await program.methods.claimNft(null)
+          .accountsPartial({
+            signer: bidder1.publicKey,
+            signerAta: getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(bidder1.publicKey)),
+            auctioneer: auctioneer.publicKey,
+            auctioneerAta,
+            mint: nftMint.publicKey,
+            edition: toWeb3JsPublicKey(nftEdition[0]),
+            auction,
+            vault,
+            vaultState,
+          })
+          .signers([web3JsBidder1Signer])
+          .rpc();
+      }, () => true, "Claim should fail");
+    });
+
     it("The balance of the auctioneer is decremented", async () => {
       const auctioneerTokenNumberAfter = await howManyTokensHasOwner(umi, auctioneer.publicKey, nftMint.publicKey);
       assert.strictEqual(Number(auctioneerTokenNumberBefore), 1);
@@ -236,9 +263,10 @@ describe("If no bid is made", () => {
         web3JsSomebodySigner,
       ]) {
         await assert.rejects(async () => {
-          await program.methods.claimNft()
-            .accounts({
+          await program.methods.claimNft(null)
+            .accountsPartial({
               signer: signer.publicKey,
+              signerAta: getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), signer.publicKey),
               auctioneer: auctioneer.publicKey,
               auctioneerAta,
               mint: nftMint.publicKey,
@@ -263,9 +291,10 @@ describe("If no bid is made", () => {
     it("nobody can claim the NFT...", async () => {
       for (const signer of [somebody]) {
         await assert.rejects(async () => {
-          await program.methods.claimNft()
-            .accounts({
+          await program.methods.claimNft(null)
+            .accountsPartial({
               signer: signer.publicKey,
+              signerAta: getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), signer.publicKey),
               auctioneer: auctioneer.publicKey,
               auctioneerAta,
               mint: nftMint.publicKey,
@@ -282,9 +311,10 @@ describe("If no bid is made", () => {
 
     it("...except the auctioneer", async () => {
       auctioneerTokenNumberBefore = await howManyTokensHasOwner(umi, auctioneer.publicKey, nftMint.publicKey);
-      await program.methods.claimNft()
-        .accounts({
+      await program.methods.claimNft(null)
+        .accountsPartial({
           signer: auctioneer.publicKey,
+          signerAta: getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey)),
           auctioneer: auctioneer.publicKey,
           auctioneerAta,
           mint: nftMint.publicKey,
@@ -297,12 +327,23 @@ describe("If no bid is made", () => {
         .rpc();
     });
 
+    it("The auction is settled", async () => {
+      const auctionAccount = await program.account.auction.fetch(auction);
+      assert.deepStrictEqual(auctionAccount.status, { settled: {} });
+    });
+
     it("The balance of the auctioneer is the same", async () => {
       const auctioneerTokenNumberAfter = await howManyTokensHasOwner(umi, auctioneer.publicKey, nftMint.publicKey);
       assert.strictEqual(Number(auctioneerTokenNumberBefore), 1);
       assert.strictEqual(Number(auctioneerTokenNumberAfter), 1);
     });
 
+    it("The auction is no longer delegate of the token account", async () => {
+      const tokenAccount = await getAccount(provider.connection, auctioneerAta);
+      assert.strictEqual(tokenAccount.delegate, null);
+      assert(!tokenAccount.isFrozen);
+    });
+
     it("After the claim, the token is not frozen", async () => {
       await (transferV1(umi, {
         mint: nftMint.publicKey,
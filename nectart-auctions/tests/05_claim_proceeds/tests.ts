@@ -4,7 +4,7 @@ import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
 import { generateSigner, KeypairSigner, Pda, signerIdentity, SolAmount } from "@metaplex-foundation/umi";
 import { findMasterEditionPda, findMetadataPda, mplTokenMetadata } from "@metaplex-foundation/mpl-token-metadata";
 import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
-import { airdrop_if_needed, createNft } from '../lib';
+import { addCollection, airdrop_if_needed, createNft, findApprovedCollection } from '../lib';
 import { NectartAuctions } from "../../target/types/nectart_auctions";
 import { getAssociatedTokenAddressSync} from "@solana/spl-token";
 import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
@@ -62,11 +62,12 @@ const initializeAuction = async () => {
   umi.use(signerIdentity(auctioneer));
   const mint  = await createNft(umi);
   collectionMint = mint.collectionMint;
+  await addCollection(program, collectionMint);
   nftMint = mint.nftMint;
   auctioneerAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey));
-  auction = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('auction'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer()], program.programId)[0];
-  vault = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('vault'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer()], program.programId)[0];
-  vaultState = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('state'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer()], program.programId)[0];
+  auction = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('auction'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer(), new BN(0).toArrayLike(Buffer, 'le', 8)], program.programId)[0];
+  vault = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('vault'), auction.toBuffer()], program.programId)[0];
+  vaultState = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('state'), auction.toBuffer()], program.programId)[0];
 
   const mintAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey));
   nftEdition = findMasterEditionPda(umi, { mint: nftMint.publicKey });
@@ -75,11 +76,11 @@ const initializeAuction = async () => {
   const time = Math.round(new Date().getTime() / 1000);
   auctionStart = time;
   auctionEnd = time + 5;
-  await program.methods.createAuction(new BN(auctionStart), new BN(auctionEnd), new BN(0), new BN(0))
-    .accounts({
+  await program.methods.createAuction(new BN(auctionStart), new BN(auctionEnd), new BN(0), new BN(0), null, { english: {} }, null, null)
+    .accountsPartial({
       payer: auctioneer.publicKey,
       mint: nftMint.publicKey,
-      collectionMint: collectionMint.publicKey,
+      approvedCollection: findApprovedCollection(program, collectionMint),
       mintAta,
       metadata: toWeb3JsPublicKey(nftMetadata[0]),
       edition: toWeb3JsPublicKey(nftEdition[0]),
@@ -98,13 +99,12 @@ describe("If a bid is made,", () => {
   before(async () => {
     await initializeAuction();
     await program.methods.bid(new BN(1))
-      .accounts({
+      .accountsPartial({
         bidder: bidder1.publicKey,
         mint: nftMint.publicKey,
         auction,
         vault,
         vaultState,
-        precedingBidder: null,
       })
       .signers([web3JsBidder1Signer])
       .rpc();
@@ -118,8 +118,8 @@ describe("If a bid is made,", () => {
         web3JsSomebodySigner,
       ]) {
         await assert.rejects(async () => {
-          await program.methods.claimSol()
-            .accounts({
+          await program.methods.claimProceeds()
+            .accountsPartial({
               signer: signer.publicKey,
               mint: nftMint.publicKey,
               auction,
@@ -145,8 +145,8 @@ describe("If a bid is made,", () => {
         web3JsSomebodySigner,
       ]) {
         await assert.rejects(async () => {
-          await program.methods.claimSol()
-            .accounts({
+          await program.methods.claimProceeds()
+            .accountsPartial({
               signer: signer.publicKey,
               mint: nftMint.publicKey,
               auction,
@@ -162,8 +162,8 @@ describe("If a bid is made,", () => {
     it("...except the auctioneer", async () => {
       umi.use(signerIdentity(auctioneer));
       balanceBefore = await umi.rpc.getBalance(auctioneer.publicKey);
-      await program.methods.claimSol()
-        .accounts({
+      await program.methods.claimProceeds()
+        .accountsPartial({
           signer: web3JsAuctioneerSigner.publicKey,
           mint: nftMint.publicKey,
           auction,
@@ -178,5 +178,20 @@ describe("If a bid is made,", () => {
       const balanceAfter = await umi.rpc.getBalance(auctioneer.publicKey);
       assert(Number(balanceAfter.basisPoints) - Number(balanceBefore.basisPoints) == 1, "Balance should increase");
     });
+
+    it("The SOL cannot be claimed twice", async () => {
+      await assert.rejects(async () => {
+        await program.methods.claimProceeds()
+          .accountsPartial({
+            signer: web3JsAuctioneerSigner.publicKey,
+            mint: nftMint.publicKey,
+            auction,
+            vault,
+            vaultState,
+          })
+          .signers([web3JsAuctioneerSigner])
+          .rpc();
+      }, () => true, "Claim should fail");
+    });
   });
 });
@@ -0,0 +1,184 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, Pda, signerIdentity } from "@metaplex-foundation/umi";
+import { findMasterEditionPda, findMetadataPda, mplTokenMetadata, TokenStandard, transferV1 } from "@metaplex-foundation/mpl-token-metadata";
+import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
+import { addCollection, airdrop_if_needed, createNft, findApprovedCollection } from '../lib';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { getAssociatedTokenAddressSync} from "@solana/spl-token";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+const bidder1 = generateSigner({ eddsa: umi.eddsa });
+const web3JsBidder1Signer = Keypair.fromSecretKey(bidder1.secretKey);
+
+const somebody = generateSigner({ eddsa: umi.eddsa });
+const web3JsSomebodySigner = Keypair.fromSecretKey(somebody.secretKey);
+
+umi.use(mplTokenMetadata());
+umi.use(mockStorage());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+let collectionMint: KeypairSigner;
+let nftMint: KeypairSigner;
+let auction: anchor.web3.PublicKey;
+let vault: anchor.web3.PublicKey;
+let vaultState: anchor.web3.PublicKey;
+let nftEdition: Pda<string, number>;
+let mintAta: anchor.web3.PublicKey;
+
+let auctionStart: number;
+let auctionEnd: number;
+
+before(async () => {
+  umi.use(signerIdentity(auctioneer));
+  await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+  await airdrop_if_needed(provider, toWeb3JsPublicKey(bidder1.publicKey), 5);
+  await airdrop_if_needed(provider, toWeb3JsPublicKey(somebody.publicKey), 5);
+});
+
+const initializeAuction = async (startIn: number, duration: number) => {
+  umi.use(signerIdentity(auctioneer));
+  const mint  = await createNft(umi);
+  collectionMint = mint.collectionMint;
+  await addCollection(program, collectionMint);
+  nftMint = mint.nftMint;
+  mintAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey));
+  auction = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('auction'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer(), new BN(0).toArrayLike(Buffer, 'le', 8)], program.programId)[0];
+  vault = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('vault'), auction.toBuffer()], program.programId)[0];
+  vaultState = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('state'), auction.toBuffer()], program.programId)[0];
+  nftEdition = findMasterEditionPda(umi, { mint: nftMint.publicKey });
+  const nftMetadata = findMetadataPda(umi, { mint: nftMint.publicKey });
+
+  const time = Math.round(new Date().getTime() / 1000);
+  auctionStart = time + startIn;
+  auctionEnd = auctionStart + duration;
+  await program.methods.createAuction(new BN(auctionStart), new BN(auctionEnd), new BN(0), new BN(0), null, { english: {} }, null, null)
+    .accountsPartial({
+      payer: auctioneer.publicKey,
+      mint: nftMint.publicKey,
+      approvedCollection: findApprovedCollection(program, collectionMint),
+      mintAta,
+      metadata: toWeb3JsPublicKey(nftMetadata[0]),
+      edition: toWeb3JsPublicKey(nftEdition[0]),
+      auction,
+      vault,
+      vaultState,
+    })
+    .signers([web3JsAuctioneerSigner])
+    .rpc();
+}
+
+const cancelAuction = async (signer: Keypair) => {
+  await program.methods.cancelAuction(null)
+    .accountsPartial({
+      maker: signer.publicKey,
+      mint: nftMint.publicKey,
+      mintAta,
+      edition: toWeb3JsPublicKey(nftEdition[0]),
+      auction,
+    })
+    .signers([signer])
+    .rpc();
+}
+
+describe("Before the beginning of the auction,", () => {
+  before(async () => {
+    await initializeAuction(60, 60);
+  });
+
+  it("somebody else than the maker cannot cancel the auction", async () => {
+    await assert.rejects(async () => {
+      await cancelAuction(web3JsSomebodySigner);
+    }, () => true, "Cancellation should fail");
+  });
+
+  it("the maker can cancel the auction", async () => {
+    await cancelAuction(web3JsAuctioneerSigner);
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.deepStrictEqual(auctionAccount.status, { cancelled: {} });
+  });
+
+  it("the auction cannot be cancelled twice", async () => {
+    await assert.rejects(async () => {
+      await cancelAuction(web3JsAuctioneerSigner);
+    }, () => true, "Cancellation should fail");
+  });
+
+  it("after the cancellation, the token is not frozen", async () => {
+    await (transferV1(umi, {
+      mint: nftMint.publicKey,
+      authority: auctioneer,
+      tokenOwner: auctioneer.publicKey,
+      destinationOwner: somebody.publicKey,
+      tokenStandard: TokenStandard.NonFungible,
+    }).sendAndConfirm(umi));
+  });
+});
+
+describe("Once a bid has been made,", () => {
+  before(async () => {
+    await initializeAuction(0, 60);
+    const now = new Date().getTime();
+    await new Promise((resolve) => setTimeout(resolve, auctionStart * 1000 - now + 1500));
+    await program.methods.bid(new BN(1))
+      .accountsPartial({
+        bidder: bidder1.publicKey,
+        mint: nftMint.publicKey,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsBidder1Signer])
+      .rpc();
+  });
+
+  it("the maker cannot cancel the auction", async () => {
+    await assert.rejects(async () => {
+      await cancelAuction(web3JsAuctioneerSigner);
+    }, () => true, "Cancellation should fail");
+  });
+});
+
+describe("After a cancellation,", () => {
+  before(async () => {
+    await initializeAuction(0, 60);
+    await cancelAuction(web3JsAuctioneerSigner);
+    const now = new Date().getTime();
+    await new Promise((resolve) => setTimeout(resolve, auctionStart * 1000 - now + 1500));
+  });
+
+  it("no bet can be made", async () => {
+    await assert.rejects(async () => {
+      await program.methods.bid(new BN(1))
+        .accountsPartial({
+          bidder: bidder1.publicKey,
+          mint: nftMint.publicKey,
+          auction,
+          vault,
+          vaultState,
+        })
+        .signers([web3JsBidder1Signer])
+        .rpc();
+    }, () => true, "Bid should fail");
+  });
+});
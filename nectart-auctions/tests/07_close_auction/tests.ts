@@ -0,0 +1,227 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, Pda, signerIdentity } from "@metaplex-foundation/umi";
+import { findMasterEditionPda, findMetadataPda, mplTokenMetadata } from "@metaplex-foundation/mpl-token-metadata";
+import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
+import { addCollection, airdrop_if_needed, createNft, findApprovedCollection } from '../lib';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { getAssociatedTokenAddressSync} from "@solana/spl-token";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+const bidder1 = generateSigner({ eddsa: umi.eddsa });
+const web3JsBidder1Signer = Keypair.fromSecretKey(bidder1.secretKey);
+
+umi.use(mplTokenMetadata());
+umi.use(mockStorage());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+let collectionMint: KeypairSigner;
+let nftMint: KeypairSigner;
+let auction: anchor.web3.PublicKey;
+let vault: anchor.web3.PublicKey;
+let vaultState: anchor.web3.PublicKey;
+let nftEdition: Pda<string, number>;
+let nftMetadata: Pda<string, number>;
+let auctioneerAta: anchor.web3.PublicKey;
+
+let auctionStart: number;
+let auctionEnd: number;
+
+before(async () => {
+  umi.use(signerIdentity(auctioneer));
+  await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+  await airdrop_if_needed(provider, toWeb3JsPublicKey(bidder1.publicKey), 5);
+  const mint  = await createNft(umi);
+  collectionMint = mint.collectionMint;
+  await addCollection(program, collectionMint);
+  nftMint = mint.nftMint;
+  auctioneerAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey));
+  nftEdition = findMasterEditionPda(umi, { mint: nftMint.publicKey });
+  nftMetadata = findMetadataPda(umi, { mint: nftMint.publicKey });
+});
+
+// the same NFT is auctioned again once the previous auction is closed
+const createAuction = async () => {
+  const auctionCounter = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('counter'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer()], program.programId)[0];
+  const counterAccount = await program.account.auctionCounter.fetchNullable(auctionCounter);
+  const id = counterAccount ? counterAccount.count : new BN(0);
+  auction = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('auction'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer(), id.toArrayLike(Buffer, 'le', 8)], program.programId)[0];
+  vault = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('vault'), auction.toBuffer()], program.programId)[0];
+  vaultState = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('state'), auction.toBuffer()], program.programId)[0];
+  const time = Math.round(new Date().getTime() / 1000);
+  auctionStart = time;
+  auctionEnd = time + 5;
+  await program.methods.createAuction(new BN(auctionStart), new BN(auctionEnd), new BN(0), new BN(0), null, { english: {} }, null, null)
+    .accountsPartial({
+      payer: auctioneer.publicKey,
+      mint: nftMint.publicKey,
+      approvedCollection: findApprovedCollection(program, collectionMint),
+      mintAta: auctioneerAta,
+      metadata: toWeb3JsPublicKey(nftMetadata[0]),
+      edition: toWeb3JsPublicKey(nftEdition[0]),
+      auction,
+      vault,
+      vaultState,
+    })
+    .signers([web3JsAuctioneerSigner])
+    .rpc();
+}
+
+const closeAuction = async () => {
+  await program.methods.closeAuction()
+    .accountsPartial({
+      maker: auctioneer.publicKey,
+      mint: nftMint.publicKey,
+      auction,
+      vault,
+      vaultState,
+    })
+    .signers([web3JsAuctioneerSigner])
+    .rpc();
+}
+
+describe("If no bid is made,", () => {
+  before(async () => {
+    await createAuction();
+  });
+
+  it("the auction cannot be closed before the NFT is claimed back", async () => {
+    await assert.rejects(closeAuction, () => true, "Closing should fail");
+  });
+
+  describe("once the NFT is claimed back,", () => {
+    before(async () => {
+      const now = new Date().getTime();
+      await new Promise((resolve) => setTimeout(resolve, auctionEnd * 1000 - now + 1500));
+      await program.methods.claimNft(null)
+        .accountsPartial({
+          signer: auctioneer.publicKey,
+          signerAta: getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey)),
+          auctioneer: auctioneer.publicKey,
+          auctioneerAta,
+          mint: nftMint.publicKey,
+          edition: toWeb3JsPublicKey(nftEdition[0]),
+          auction,
+          vault,
+          vaultState,
+        })
+        .signers([web3JsAuctioneerSigner])
+        .rpc();
+    });
+
+    it("the maker can close the auction", async () => {
+      await closeAuction();
+    });
+
+    it("the vault and vault state accounts are closed", async () => {
+      for (const account of [vault, vaultState]) {
+        const info = await provider.connection.getAccountInfo(account);
+        assert.strictEqual(info, null);
+      }
+    });
+
+    it("the auction account is kept as a record", async () => {
+      const auctionAccount = await program.account.auction.fetch(auction);
+      assert.strictEqual(auctionAccount.id.toNumber(), 0);
+      assert.deepStrictEqual(auctionAccount.status, { settled: {} });
+    });
+  });
+});
+
+describe("If a bid is made,", () => {
+  let firstAuction: anchor.web3.PublicKey;
+
+  before(async () => {
+    firstAuction = auction;
+    await createAuction();
+    const now = new Date().getTime();
+    await new Promise((resolve) => setTimeout(resolve, auctionStart * 1000 - now + 1500));
+    await program.methods.bid(new BN(1))
+      .accountsPartial({
+        bidder: bidder1.publicKey,
+        mint: nftMint.publicKey,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsBidder1Signer])
+      .rpc();
+    const then = new Date().getTime();
+    await new Promise((resolve) => setTimeout(resolve, auctionEnd * 1000 - then + 1500));
+    await program.methods.claimNft(null)
+      .accountsPartial({
+        signer: bidder1.publicKey,
+        signerAta: getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(bidder1.publicKey)),
+        auctioneer: auctioneer.publicKey,
+        auctioneerAta,
+        mint: nftMint.publicKey,
+        edition: toWeb3JsPublicKey(nftEdition[0]),
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsBidder1Signer])
+      .rpc();
+  });
+
+  it("the auction cannot be closed before the SOL is claimed", async () => {
+    await assert.rejects(closeAuction, () => true, "Closing should fail");
+  });
+
+  it("the auction cannot be closed while a refund ledger is open", async () => {
+    await program.methods.claimProceeds()
+      .accountsPartial({
+        signer: auctioneer.publicKey,
+        mint: nftMint.publicKey,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsAuctioneerSigner])
+      .rpc();
+    await assert.rejects(closeAuction, () => true, "Closing should fail");
+  });
+
+  it("the auction can be closed once the SOL is claimed and the ledgers released", async () => {
+    await program.methods.withdrawRefund()
+      .accountsPartial({
+        signer: auctioneer.publicKey,
+        bidder: bidder1.publicKey,
+        mint: nftMint.publicKey,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsAuctioneerSigner])
+      .rpc();
+    await closeAuction();
+    const info = await provider.connection.getAccountInfo(vaultState);
+    assert.strictEqual(info, null);
+  });
+
+  it("the NFT was auctioned again under a new auction account", async () => {
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.strictEqual(auctionAccount.id.toNumber(), 1);
+    assert(!auction.equals(firstAuction));
+  });
+});
@@ -0,0 +1,187 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, signerIdentity } from "@metaplex-foundation/umi";
+import { findMasterEditionPda, findMetadataPda, mplTokenMetadata } from "@metaplex-foundation/mpl-token-metadata";
+import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
+import { addCollection, airdrop_if_needed, createNft, findApprovedCollection } from '../lib';
+import { admin } from '../nectart-auctions';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { getAssociatedTokenAddressSync} from "@solana/spl-token";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+const bidder1 = generateSigner({ eddsa: umi.eddsa });
+const web3JsBidder1Signer = Keypair.fromSecretKey(bidder1.secretKey);
+
+const bidder2 = generateSigner({ eddsa: umi.eddsa });
+const web3JsBidder2Signer = Keypair.fromSecretKey(bidder2.secretKey);
+
+umi.use(mplTokenMetadata());
+umi.use(mockStorage());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+let collectionMint: KeypairSigner;
+let nftMint: KeypairSigner;
+let auction: anchor.web3.PublicKey;
+let vault: anchor.web3.PublicKey;
+let vaultState: anchor.web3.PublicKey;
+let auctionStart: number;
+let auctionEnd: number;
+
+const WINDOW = 30;
+const EXTENSION = 20;
+const MAX_EXTENSION = 30;
+
+const config = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('config')], program.programId)[0];
+
+const updateMaxDuration = async (maxDuration: number) => {
+  const { bounds } = await program.account.config.fetch(config);
+  await program.methods.updateConfig(null, null, null, { ...bounds, maxDuration: new BN(maxDuration) }, null)
+    .accountsPartial({
+      admin: admin.publicKey,
+      config,
+    })
+    .signers([admin])
+    .rpc();
+}
+
+const createAuction = async (maxExtension: number | null) => {
+  const mint  = await createNft(umi);
+  collectionMint = mint.collectionMint;
+  await addCollection(program, collectionMint);
+  nftMint = mint.nftMint;
+  auction = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('auction'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer(), new BN(0).toArrayLike(Buffer, 'le', 8)], program.programId)[0];
+  vault = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('vault'), auction.toBuffer()], program.programId)[0];
+  vaultState = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('state'), auction.toBuffer()], program.programId)[0];
+
+  const mintAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey));
+  const nftEdition = findMasterEditionPda(umi, { mint: nftMint.publicKey });
+  const nftMetadata = findMetadataPda(umi, { mint: nftMint.publicKey });
+
+  const time = Math.round(new Date().getTime() / 1000);
+  auctionStart = time;
+  auctionEnd = time + 10;
+  const softClose = {
+    window: new BN(WINDOW),
+    extension: new BN(EXTENSION),
+    maxExtension: maxExtension === null ? null : new BN(maxExtension),
+  };
+  await program.methods.createAuction(new BN(auctionStart), new BN(auctionEnd), new BN(0), new BN(0), softClose, { english: {} }, null, null)
+    .accountsPartial({
+      payer: auctioneer.publicKey,
+      mint: nftMint.publicKey,
+      approvedCollection: findApprovedCollection(program, collectionMint),
+      mintAta,
+      metadata: toWeb3JsPublicKey(nftMetadata[0]),
+      edition: toWeb3JsPublicKey(nftEdition[0]),
+      auction,
+      vault,
+      vaultState,
+    })
+    .signers([web3JsAuctioneerSigner])
+    .rpc();
+
+  const now = new Date().getTime();
+  await new Promise((resolve) => setTimeout(resolve, auctionStart * 1000 - now + 1500));
+}
+
+describe("Soft close", () => {
+  before(async () => {
+    umi.use(signerIdentity(auctioneer));
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(bidder1.publicKey), 5);
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(bidder2.publicKey), 5);
+    await createAuction(MAX_EXTENSION);
+  });
+
+  it("The soft close parameters are stored on the auction", async () => {
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.strictEqual(auctionAccount.softClose.window.toNumber(), WINDOW);
+    assert.strictEqual(auctionAccount.softClose.extension.toNumber(), EXTENSION);
+    assert.strictEqual(auctionAccount.softClose.maxExtension.toNumber(), MAX_EXTENSION);
+  });
+
+  it("A bid close to the deadline pushes the deadline out", async () => {
+    await program.methods.bid(new BN(1))
+      .accountsPartial({
+        bidder: bidder1.publicKey,
+        mint: nftMint.publicKey,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsBidder1Signer])
+      .rpc();
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.strictEqual(auctionAccount.deadline.toNumber(), auctionEnd + EXTENSION);
+    assert.strictEqual(auctionAccount.extendedBy.toNumber(), EXTENSION);
+  });
+
+  it("The total extension is capped", async () => {
+    await program.methods.bid(new BN(2))
+      .accountsPartial({
+        bidder: bidder2.publicKey,
+        mint: nftMint.publicKey,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsBidder2Signer])
+      .rpc();
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.strictEqual(auctionAccount.deadline.toNumber(), auctionEnd + MAX_EXTENSION);
+    assert.strictEqual(auctionAccount.extendedBy.toNumber(), MAX_EXTENSION);
+  });
+});
+
+describe("Soft close without a maximum extension", () => {
+  const MAX_DURATION = 15;
+  let defaultMaxDuration: number;
+
+  before(async () => {
+    const { bounds } = await program.account.config.fetch(config);
+    defaultMaxDuration = bounds.maxDuration.toNumber();
+    await updateMaxDuration(MAX_DURATION);
+    await createAuction(null);
+  });
+
+  after(async () => {
+    await updateMaxDuration(defaultMaxDuration);
+  });
+
+  it("The deadline is never pushed past the longest duration", async () => {
+    await program.methods.bid(new BN(1))
+      .accountsPartial({
+        bidder: bidder1.publicKey,
+        mint: nftMint.publicKey,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsBidder1Signer])
+      .rpc();
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.strictEqual(auctionAccount.maxDuration.toNumber(), MAX_DURATION);
+    assert.strictEqual(auctionAccount.deadline.toNumber(), auctionStart + MAX_DURATION);
+    assert.strictEqual(auctionAccount.extendedBy.toNumber(), auctionStart + MAX_DURATION - auctionEnd);
+  });
+});
@@ -0,0 +1,223 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, Pda, signerIdentity } from "@metaplex-foundation/umi";
+import { findMasterEditionPda, findMetadataPda, mplTokenMetadata } from "@metaplex-foundation/mpl-token-metadata";
+import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
+import { addCollection, airdrop_if_needed, createNft, findApprovedCollection, findRefundLedger } from '../lib';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { getAssociatedTokenAddressSync, getAccount } from "@solana/spl-token";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+const buyer = generateSigner({ eddsa: umi.eddsa });
+const web3JsBuyerSigner = Keypair.fromSecretKey(buyer.secretKey);
+
+umi.use(mplTokenMetadata());
+umi.use(mockStorage());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+let collectionMint: KeypairSigner;
+let nftMint: KeypairSigner;
+let auction: anchor.web3.PublicKey;
+let vault: anchor.web3.PublicKey;
+let vaultState: anchor.web3.PublicKey;
+let nftEdition: Pda<string, number>;
+let auctioneerAta: anchor.web3.PublicKey;
+let auctionStart: number;
+
+const START_PRICE = LAMPORTS_PER_SOL;
+const FLOOR_PRICE = LAMPORTS_PER_SOL / 10;
+
+const buy = async (maxPrice: number) => {
+  await program.methods.buy(new BN(maxPrice), null)
+    .accountsPartial({
+      buyer: buyer.publicKey,
+      buyerAta: getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(buyer.publicKey)),
+      maker: auctioneer.publicKey,
+      makerAta: auctioneerAta,
+      mint: nftMint.publicKey,
+      edition: toWeb3JsPublicKey(nftEdition[0]),
+      auction,
+    })
+    .signers([web3JsBuyerSigner])
+    .rpc();
+}
+
+const failsWith = (code: string) => (err: anchor.AnchorError) => err.error.errorCode.code === code;
+
+const createDutchAuction = async () => {
+  const mint  = await createNft(umi);
+  collectionMint = mint.collectionMint;
+  await addCollection(program, collectionMint);
+  nftMint = mint.nftMint;
+  auction = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('auction'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer(), new BN(0).toArrayLike(Buffer, 'le', 8)], program.programId)[0];
+  vault = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('vault'), auction.toBuffer()], program.programId)[0];
+  vaultState = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('state'), auction.toBuffer()], program.programId)[0];
+  auctioneerAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey));
+  nftEdition = findMasterEditionPda(umi, { mint: nftMint.publicKey });
+  const nftMetadata = findMetadataPda(umi, { mint: nftMint.publicKey });
+
+  const time = Math.round(new Date().getTime() / 1000);
+  auctionStart = time;
+  const kind = {
+    dutch: {
+      startPrice: new BN(START_PRICE),
+      curve: { linear: {} },
+    },
+  };
+  await program.methods.createAuction(new BN(auctionStart), new BN(auctionStart + 100), new BN(FLOOR_PRICE), new BN(0), null, kind, null, null)
+    .accountsPartial({
+      payer: auctioneer.publicKey,
+      mint: nftMint.publicKey,
+      approvedCollection: findApprovedCollection(program, collectionMint),
+      mintAta: auctioneerAta,
+      metadata: toWeb3JsPublicKey(nftMetadata[0]),
+      edition: toWeb3JsPublicKey(nftEdition[0]),
+      auction,
+      vault,
+      vaultState,
+    })
+    .signers([web3JsAuctioneerSigner])
+    .rpc();
+
+  const now = new Date().getTime();
+  await new Promise((resolve) => setTimeout(resolve, auctionStart * 1000 - now + 1500));
+}
+
+const bid = async (amount: number) => {
+  await program.methods.bid(new BN(amount))
+    .accountsPartial({
+      bidder: buyer.publicKey,
+      mint: nftMint.publicKey,
+      auction,
+      vault,
+      vaultState,
+    })
+    .signers([web3JsBuyerSigner])
+    .rpc();
+}
+
+describe("Dutch auction", () => {
+  before(async () => {
+    umi.use(signerIdentity(auctioneer));
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(buyer.publicKey), 5);
+    await createDutchAuction();
+  });
+
+  it("The NFT cannot be bought below the current price", async () => {
+    await assert.rejects(async () => {
+      await buy(FLOOR_PRICE);
+    }, () => true, "Buy should fail");
+  });
+
+  it("The NFT can be bought at the current price", async () => {
+    const makerBalanceBefore = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+    await buy(START_PRICE);
+    const makerBalanceAfter = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+    const paid = makerBalanceAfter - makerBalanceBefore;
+    assert(paid >= FLOOR_PRICE && paid < START_PRICE, "The maker should be paid the decayed price");
+
+    const buyerAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(buyer.publicKey));
+    const buyerAccount = await getAccount(provider.connection, buyerAta);
+    assert.strictEqual(Number(buyerAccount.amount), 1);
+
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.deepStrictEqual(auctionAccount.status, { settled: {} });
+    assert.strictEqual(auctionAccount.currentBid.toNumber(), paid);
+  });
+
+  it("The NFT cannot be bought twice", async () => {
+    await assert.rejects(async () => {
+      await buy(START_PRICE);
+    }, () => true, "Buy should fail");
+  });
+});
+
+describe("Dutch auction won with a bid", () => {
+  before(async () => {
+    await createDutchAuction();
+  });
+
+  it("A bid below the current price is rejected", async () => {
+    await assert.rejects(async () => {
+      await bid(FLOOR_PRICE);
+    }, failsWith("BidTooLow"), "Bid should fail");
+  });
+
+  it("A bid at or above the current price wins at the current price", async () => {
+    await bid(START_PRICE);
+
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.deepStrictEqual(auctionAccount.status, { ended: {} });
+    assert.strictEqual(auctionAccount.currentBidder.toBase58(), buyer.publicKey.toString());
+    const price = auctionAccount.currentBid.toNumber();
+    assert(price >= FLOOR_PRICE && price < START_PRICE, "The bid should win at the decayed price");
+    assert.strictEqual(auctionAccount.bought, false);
+  });
+
+  it("No other bid can be made", async () => {
+    await assert.rejects(async () => {
+      await bid(START_PRICE);
+    }, failsWith("AuctionEnded"), "Bid should fail");
+  });
+
+  it("The part of the bid above the price is refunded", async () => {
+    const { currentBid } = await program.account.auction.fetch(auction);
+    const refundLedger = findRefundLedger(program, auction, toWeb3JsPublicKey(buyer.publicKey));
+    const balanceBefore = await provider.connection.getBalance(toWeb3JsPublicKey(buyer.publicKey));
+    await program.methods.withdrawRefund()
+      .accountsPartial({
+        signer: auctioneer.publicKey,
+        bidder: buyer.publicKey,
+        mint: nftMint.publicKey,
+        auction,
+        vault,
+        vaultState,
+        refundLedger,
+      })
+      .signers([web3JsAuctioneerSigner])
+      .rpc();
+    const balanceAfter = await provider.connection.getBalance(toWeb3JsPublicKey(buyer.publicKey));
+    assert(balanceAfter - balanceBefore >= START_PRICE - currentBid.toNumber(), "The excess should be refunded");
+  });
+
+  it("The winner claims the NFT", async () => {
+    const buyerAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(buyer.publicKey));
+    await program.methods.claimNft(null)
+      .accountsPartial({
+        signer: buyer.publicKey,
+        signerAta: buyerAta,
+        auctioneer: auctioneer.publicKey,
+        auctioneerAta,
+        mint: nftMint.publicKey,
+        edition: toWeb3JsPublicKey(nftEdition[0]),
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsBuyerSigner])
+      .rpc();
+    const buyerAccount = await getAccount(provider.connection, buyerAta);
+    assert.strictEqual(Number(buyerAccount.amount), 1);
+  });
+});
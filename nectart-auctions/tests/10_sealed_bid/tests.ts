@@ -0,0 +1,260 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, signerIdentity } from "@metaplex-foundation/umi";
+import { findMasterEditionPda, findMetadataPda, mplTokenMetadata } from "@metaplex-foundation/mpl-token-metadata";
+import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
+import { addCollection, airdrop_if_needed, createNft, findApprovedCollection } from '../lib';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { getAssociatedTokenAddressSync} from "@solana/spl-token";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+import { createHash, randomBytes } from "node:crypto";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+const bidder1 = Keypair.generate();
+const bidder2 = Keypair.generate();
+const bidder3 = Keypair.generate();
+const copycat = Keypair.generate();
+
+umi.use(mplTokenMetadata());
+umi.use(mockStorage());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+let collectionMint: KeypairSigner;
+let nftMint: KeypairSigner;
+let auction: anchor.web3.PublicKey;
+let vault: anchor.web3.PublicKey;
+let vaultState: anchor.web3.PublicKey;
+let auctionStart: number;
+let auctionEnd: number;
+let revealEnd: number;
+
+const DEPOSIT = LAMPORTS_PER_SOL;
+const bids = [
+  { bidder: bidder1, amount: LAMPORTS_PER_SOL / 2, salt: randomBytes(32) },
+  { bidder: bidder2, amount: LAMPORTS_PER_SOL / 4, salt: randomBytes(32) },
+  { bidder: bidder3, amount: LAMPORTS_PER_SOL / 8, salt: randomBytes(32) },
+];
+
+const commitment = (bidder: anchor.web3.PublicKey, amount: number, salt: Buffer): number[] => {
+  const amountBytes = new BN(amount).toArrayLike(Buffer, "le", 8);
+  return [...createHash("sha256").update(Buffer.concat([auction.toBuffer(), bidder.toBuffer(), amountBytes, salt])).digest()];
+}
+
+const revealBid = async (bidder: Keypair, amount: number, salt: Buffer) => {
+  await program.methods.revealBid(new BN(amount), [...salt])
+    .accountsPartial({
+      bidder: bidder.publicKey,
+      mint: nftMint.publicKey,
+      auction,
+    })
+    .signers([bidder])
+    .rpc();
+}
+
+const withdrawDeposit = async (bidder: Keypair) => {
+  await program.methods.withdrawDeposit()
+    .accountsPartial({
+      signer: provider.wallet.publicKey,
+      bidder: bidder.publicKey,
+      maker: auctioneer.publicKey,
+      mint: nftMint.publicKey,
+      auction,
+      vault,
+      vaultState,
+    })
+    .rpc();
+}
+
+describe("Sealed-bid auction", () => {
+  before(async () => {
+    umi.use(signerIdentity(auctioneer));
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+    for (const { bidder } of bids) {
+      await airdrop_if_needed(provider, bidder.publicKey, 5);
+    }
+    await airdrop_if_needed(provider, copycat.publicKey, 5);
+    const mint  = await createNft(umi);
+    collectionMint = mint.collectionMint;
+    await addCollection(program, collectionMint);
+    nftMint = mint.nftMint;
+    auction = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('auction'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer(), new BN(0).toArrayLike(Buffer, 'le', 8)], program.programId)[0];
+    vault = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('vault'), auction.toBuffer()], program.programId)[0];
+    vaultState = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('state'), auction.toBuffer()], program.programId)[0];
+
+    const mintAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey));
+    const nftEdition = findMasterEditionPda(umi, { mint: nftMint.publicKey });
+    const nftMetadata = findMetadataPda(umi, { mint: nftMint.publicKey });
+
+    const time = Math.round(new Date().getTime() / 1000);
+    auctionStart = time;
+    auctionEnd = time + 10;
+    revealEnd = time + 20;
+    const kind = {
+      sealed: {
+        revealDeadline: new BN(revealEnd),
+        forfeitUnrevealed: true,
+      },
+    };
+    await program.methods.createAuction(new BN(auctionStart), new BN(auctionEnd), new BN(0), new BN(0), null, kind, null, null)
+      .accountsPartial({
+        payer: auctioneer.publicKey,
+        mint: nftMint.publicKey,
+        approvedCollection: findApprovedCollection(program, collectionMint),
+        mintAta,
+        metadata: toWeb3JsPublicKey(nftMetadata[0]),
+        edition: toWeb3JsPublicKey(nftEdition[0]),
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsAuctioneerSigner])
+      .rpc();
+
+    const now = new Date().getTime();
+    await new Promise((resolve) => setTimeout(resolve, auctionStart * 1000 - now + 1500));
+  });
+
+  describe("During the bidding period,", () => {
+    it("bidders can commit to their bids", async () => {
+      for (const { bidder, amount, salt } of bids) {
+        await program.methods.commitBid(commitment(bidder.publicKey, amount, salt), new BN(DEPOSIT))
+          .accountsPartial({
+            bidder: bidder.publicKey,
+            mint: nftMint.publicKey,
+            auction,
+            vault,
+            vaultState,
+          })
+          .signers([bidder])
+          .rpc();
+      }
+    });
+
+    it("a commitment can be copied by another bidder", async () => {
+      const sealedBid = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from('sealed_bid'), auction.toBuffer(), bidder2.publicKey.toBuffer()], program.programId)[0];
+      const { commitment } = await program.account.sealedBid.fetch(sealedBid);
+      await program.methods.commitBid(commitment, new BN(DEPOSIT))
+        .accountsPartial({
+          bidder: copycat.publicKey,
+          mint: nftMint.publicKey,
+          auction,
+          vault,
+          vaultState,
+        })
+        .signers([copycat])
+        .rpc();
+    });
+
+    it("bids cannot be revealed", async () => {
+      const { bidder, amount, salt } = bids[0];
+      await assert.rejects(async () => {
+        await revealBid(bidder, amount, salt);
+      }, () => true, "Reveal should fail");
+    });
+  });
+
+  describe("During the reveal period,", () => {
+    before(async () => {
+      const now = new Date().getTime();
+      await new Promise((resolve) => setTimeout(resolve, auctionEnd * 1000 - now + 1500));
+    });
+
+    it("a bid not matching its commitment is rejected", async () => {
+      const { bidder, amount, salt } = bids[0];
+      await assert.rejects(async () => {
+        await revealBid(bidder, amount + 1, salt);
+      }, () => true, "Reveal should fail");
+    });
+
+    it("bidders can reveal their bids", async () => {
+      // the third bidder never reveals
+      for (const { bidder, amount, salt } of bids.slice(0, 2)) {
+        await revealBid(bidder, amount, salt);
+      }
+    });
+
+    it("a copied commitment cannot be revealed with the salt of its bidder", async () => {
+      const { amount, salt } = bids[1];
+      await assert.rejects(async () => {
+        await revealBid(copycat, amount, salt);
+      }, (err: anchor.AnchorError) => err.error.errorCode.code === "RevealMismatch", "Reveal should fail");
+    });
+
+    it("the highest revealed bid leads and the second highest sets the price", async () => {
+      const auctionAccount = await program.account.auction.fetch(auction);
+      assert(auctionAccount.currentBidder.equals(bidder1.publicKey));
+      assert.strictEqual(auctionAccount.currentBid.toNumber(), bids[0].amount);
+      assert.strictEqual(auctionAccount.secondBid.toNumber(), bids[1].amount);
+    });
+
+    it("deposits cannot be withdrawn yet", async () => {
+      await assert.rejects(async () => {
+        await withdrawDeposit(bidder2);
+      }, () => true, "Withdrawal should fail");
+    });
+  });
+
+  describe("After the reveal period,", () => {
+    before(async () => {
+      const now = new Date().getTime();
+      await new Promise((resolve) => setTimeout(resolve, revealEnd * 1000 - now + 1500));
+    });
+
+    it("the winner gets back its deposit minus the second price", async () => {
+      const before = await provider.connection.getBalance(bidder1.publicKey);
+      await withdrawDeposit(bidder1);
+      const after = await provider.connection.getBalance(bidder1.publicKey);
+      assert(after - before > DEPOSIT - bids[1].amount, "The winner should be refunded");
+      assert(after - before < DEPOSIT, "The winner should pay the second price");
+    });
+
+    it("a loser gets back its whole deposit", async () => {
+      const before = await provider.connection.getBalance(bidder2.publicKey);
+      await withdrawDeposit(bidder2);
+      const after = await provider.connection.getBalance(bidder2.publicKey);
+      assert(after - before > DEPOSIT, "The loser should be refunded");
+    });
+
+    it("an unrevealed deposit is forfeited to the maker", async () => {
+      const before = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+      await withdrawDeposit(bidder3);
+      const after = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+      assert.strictEqual(after - before, DEPOSIT);
+    });
+
+    it("the maker is paid the second price", async () => {
+      const before = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+      await program.methods.claimProceeds()
+        .accountsPartial({
+          signer: auctioneer.publicKey,
+          mint: nftMint.publicKey,
+          auction,
+          vault,
+          vaultState,
+        })
+        .signers([web3JsAuctioneerSigner])
+        .rpc();
+      const after = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+      assert.strictEqual(after - before, bids[1].amount);
+    });
+  });
+});
@@ -0,0 +1,200 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, signerIdentity } from "@metaplex-foundation/umi";
+import { findMasterEditionPda, findMetadataPda, mplTokenMetadata } from "@metaplex-foundation/mpl-token-metadata";
+import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
+import { addCollection, airdrop_if_needed, createNft, findApprovedCollection } from '../lib';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import {
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+const bidder1 = Keypair.generate();
+const bidder2 = Keypair.generate();
+
+umi.use(mplTokenMetadata());
+umi.use(mockStorage());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+let collectionMint: KeypairSigner;
+let nftMint: KeypairSigner;
+let auction: PublicKey;
+let vault: PublicKey;
+let vaultState: PublicKey;
+let currencyMint: PublicKey;
+let vaultCurrencyAccount: PublicKey;
+let auctioneerCurrencyAccount: PublicKey;
+const bidderCurrencyAccounts = new Map<string, PublicKey>();
+let auctionStart: number;
+let auctionEnd: number;
+
+const BALANCE = 1_000_000;
+
+const bid = async (bidder: Keypair, amount: number) => {
+  await program.methods.bid(new BN(amount))
+    .accountsPartial({
+      bidder: bidder.publicKey,
+      mint: nftMint.publicKey,
+      auction,
+      vault,
+      vaultState,
+      currencyMint,
+      bidderCurrencyAccount: bidderCurrencyAccounts.get(bidder.publicKey.toBase58()),
+      vaultCurrencyAccount,
+      currencyTokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([bidder])
+    .rpc();
+}
+
+const withdrawRefund = async (bidder: Keypair) => {
+  await program.methods.withdrawRefund()
+    .accountsPartial({
+      signer: provider.wallet.publicKey,
+      bidder: bidder.publicKey,
+      mint: nftMint.publicKey,
+      auction,
+      vault,
+      vaultState,
+      currencyMint,
+      bidderCurrencyAccount: bidderCurrencyAccounts.get(bidder.publicKey.toBase58()),
+      vaultCurrencyAccount,
+      currencyTokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+}
+
+const balance = async (account: PublicKey): Promise<number> => {
+  return Number((await getAccount(provider.connection, account)).amount);
+}
+
+describe("Auction in an SPL token", () => {
+  before(async () => {
+    umi.use(signerIdentity(auctioneer));
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+    for (const bidder of [bidder1, bidder2]) {
+      await airdrop_if_needed(provider, bidder.publicKey, 5);
+    }
+    const mint  = await createNft(umi);
+    collectionMint = mint.collectionMint;
+    await addCollection(program, collectionMint);
+    nftMint = mint.nftMint;
+    auction = PublicKey.findProgramAddressSync([Buffer.from('auction'), toWeb3JsPublicKey(nftMint.publicKey).toBuffer(), new BN(0).toArrayLike(Buffer, 'le', 8)], program.programId)[0];
+    vault = PublicKey.findProgramAddressSync([Buffer.from('vault'), auction.toBuffer()], program.programId)[0];
+    vaultState = PublicKey.findProgramAddressSync([Buffer.from('state'), auction.toBuffer()], program.programId)[0];
+
+    currencyMint = await createMint(provider.connection, web3JsAuctioneerSigner, web3JsAuctioneerSigner.publicKey, null, 6);
+    auctioneerCurrencyAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, web3JsAuctioneerSigner, currencyMint, web3JsAuctioneerSigner.publicKey)).address;
+    for (const bidder of [bidder1, bidder2]) {
+      const account = await getOrCreateAssociatedTokenAccount(provider.connection, web3JsAuctioneerSigner, currencyMint, bidder.publicKey);
+      await mintTo(provider.connection, web3JsAuctioneerSigner, currencyMint, account.address, web3JsAuctioneerSigner, BALANCE);
+      bidderCurrencyAccounts.set(bidder.publicKey.toBase58(), account.address);
+    }
+    vaultCurrencyAccount = getAssociatedTokenAddressSync(currencyMint, vault, true);
+
+    const mintAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey));
+    const nftEdition = findMasterEditionPda(umi, { mint: nftMint.publicKey });
+    const nftMetadata = findMetadataPda(umi, { mint: nftMint.publicKey });
+
+    const time = Math.round(new Date().getTime() / 1000);
+    auctionStart = time;
+    auctionEnd = time + 10;
+    await program.methods.createAuction(new BN(auctionStart), new BN(auctionEnd), new BN(0), new BN(0), null, { english: {} }, null, null)
+      .accountsPartial({
+        payer: auctioneer.publicKey,
+        mint: nftMint.publicKey,
+        approvedCollection: findApprovedCollection(program, collectionMint),
+        mintAta,
+        metadata: toWeb3JsPublicKey(nftMetadata[0]),
+        edition: toWeb3JsPublicKey(nftEdition[0]),
+        auction,
+        vault,
+        vaultState,
+        currencyMint,
+        vaultCurrencyAccount,
+        currencyTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([web3JsAuctioneerSigner])
+      .rpc();
+
+    const now = new Date().getTime();
+    await new Promise((resolve) => setTimeout(resolve, auctionStart * 1000 - now + 1500));
+  });
+
+  it("The currency is stored on the auction", async () => {
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert(auctionAccount.currency.equals(currencyMint));
+  });
+
+  it("A bid in SOL is rejected", async () => {
+    await assert.rejects(async () => {
+      await program.methods.bid(new BN(1))
+        .accountsPartial({
+          bidder: bidder1.publicKey,
+          mint: nftMint.publicKey,
+          auction,
+          vault,
+          vaultState,
+        })
+        .signers([bidder1])
+        .rpc();
+    }, () => true, "Bid should fail");
+  });
+
+  it("Bids are escrowed in tokens and the outbid bidder can withdraw its bid", async () => {
+    await bid(bidder1, 100);
+    assert.strictEqual(await balance(vaultCurrencyAccount), 100);
+    await bid(bidder2, 200);
+    assert.strictEqual(await balance(vaultCurrencyAccount), 300);
+    await withdrawRefund(bidder1);
+    assert.strictEqual(await balance(vaultCurrencyAccount), 200);
+    assert.strictEqual(await balance(bidderCurrencyAccounts.get(bidder1.publicKey.toBase58())), BALANCE);
+  });
+
+  it("The maker is paid in tokens", async () => {
+    const now = new Date().getTime();
+    await new Promise((resolve) => setTimeout(resolve, auctionEnd * 1000 - now + 1500));
+    await program.methods.claimProceeds()
+      .accountsPartial({
+        signer: auctioneer.publicKey,
+        mint: nftMint.publicKey,
+        auction,
+        vault,
+        vaultState,
+        currencyMint,
+        signerCurrencyAccount: auctioneerCurrencyAccount,
+        vaultCurrencyAccount,
+        currencyTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([web3JsAuctioneerSigner])
+      .rpc();
+    assert.strictEqual(await balance(auctioneerCurrencyAccount), 200);
+    assert.strictEqual(await balance(vaultCurrencyAccount), 0);
+  });
+});
@@ -0,0 +1,163 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, Pda, signerIdentity } from "@metaplex-foundation/umi";
+import { findMasterEditionPda, mplTokenMetadata } from "@metaplex-foundation/mpl-token-metadata";
+import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
+import { addCollection, airdrop_if_needed, createNft, createAuction, waitUntil } from '../lib';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { getAssociatedTokenAddressSync, getAccount } from "@solana/spl-token";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+const bidder = Keypair.generate();
+const buyer = Keypair.generate();
+
+umi.use(mplTokenMetadata());
+umi.use(mockStorage());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+let collectionMint: KeypairSigner;
+let nftMint: KeypairSigner;
+let auction: anchor.web3.PublicKey;
+let vault: anchor.web3.PublicKey;
+let vaultState: anchor.web3.PublicKey;
+let nftEdition: Pda<string, number>;
+let auctioneerAta: anchor.web3.PublicKey;
+let auctionStart: number;
+
+const BID = LAMPORTS_PER_SOL / 10;
+const BUY_NOW_PRICE = LAMPORTS_PER_SOL;
+
+const withdrawRefund = async (bidder: Keypair) => {
+  await program.methods.withdrawRefund()
+    .accountsPartial({
+      signer: provider.wallet.publicKey,
+      bidder: bidder.publicKey,
+      mint: nftMint.publicKey,
+      auction,
+      vault,
+      vaultState,
+    })
+    .rpc();
+}
+
+const buy = async (maxPrice: number) => {
+  await program.methods.buy(new BN(maxPrice), null)
+    .accountsPartial({
+      buyer: buyer.publicKey,
+      buyerAta: getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), buyer.publicKey),
+      maker: auctioneer.publicKey,
+      makerAta: auctioneerAta,
+      mint: nftMint.publicKey,
+      edition: toWeb3JsPublicKey(nftEdition[0]),
+      auction,
+    })
+    .signers([buyer])
+    .rpc();
+}
+
+describe("Buy now", () => {
+  before(async () => {
+    umi.use(signerIdentity(auctioneer));
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+    await airdrop_if_needed(provider, bidder.publicKey, 5);
+    await airdrop_if_needed(provider, buyer.publicKey, 5);
+    const mint  = await createNft(umi);
+    collectionMint = mint.collectionMint;
+    await addCollection(program, collectionMint);
+    nftMint = mint.nftMint;
+    auctioneerAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey));
+    nftEdition = findMasterEditionPda(umi, { mint: nftMint.publicKey });
+
+    const time = Math.round(new Date().getTime() / 1000);
+    auctionStart = time;
+    ({ auction, vault, vaultState } = await createAuction(program, umi, web3JsAuctioneerSigner, nftMint, collectionMint, {
+      start: auctionStart,
+      deadline: auctionStart + 100,
+      buyNowPrice: BUY_NOW_PRICE,
+    }));
+
+    await waitUntil(auctionStart);
+    // the buyer leads when it buys the NFT
+    for (const [signer, amount] of [[bidder, BID], [buyer, 2 * BID]] as [Keypair, number][]) {
+      await program.methods.bid(new BN(amount))
+        .accountsPartial({
+          bidder: signer.publicKey,
+          mint: nftMint.publicKey,
+          auction,
+          vault,
+          vaultState,
+        })
+        .signers([signer])
+        .rpc();
+    }
+  });
+
+  it("The NFT cannot be bought below the buy now price", async () => {
+    await assert.rejects(async () => {
+      await buy(BUY_NOW_PRICE - 1);
+    }, () => true, "Buy should fail");
+  });
+
+  it("The NFT can be bought at the buy now price", async () => {
+    const makerBalanceBefore = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+    await buy(BUY_NOW_PRICE);
+    const makerBalanceAfter = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+    assert.strictEqual(makerBalanceAfter - makerBalanceBefore, BUY_NOW_PRICE);
+
+    const buyerAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), buyer.publicKey);
+    const buyerAccount = await getAccount(provider.connection, buyerAta);
+    assert.strictEqual(Number(buyerAccount.amount), 1);
+
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.deepStrictEqual(auctionAccount.status, { settled: {} });
+    assert(auctionAccount.currentBidder.equals(buyer.publicKey));
+  });
+
+  it("The outbid bidder can withdraw its bid", async () => {
+    const before = await provider.connection.getBalance(bidder.publicKey);
+    await withdrawRefund(bidder);
+    const after = await provider.connection.getBalance(bidder.publicKey);
+    assert(after - before > BID, "The bidder should be refunded");
+  });
+
+  it("The buyer can withdraw the bid it was leading with", async () => {
+    const before = await provider.connection.getBalance(buyer.publicKey);
+    await withdrawRefund(buyer);
+    const after = await provider.connection.getBalance(buyer.publicKey);
+    assert(after - before > 2 * BID, "The buyer should be refunded");
+  });
+
+  it("The maker can close the auction", async () => {
+    await program.methods.closeAuction()
+      .accountsPartial({
+        maker: auctioneer.publicKey,
+        mint: nftMint.publicKey,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsAuctioneerSigner])
+      .rpc();
+    assert.strictEqual(await provider.connection.getAccountInfo(auction), null);
+  });
+});
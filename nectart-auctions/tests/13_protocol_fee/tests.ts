@@ -0,0 +1,163 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, signerIdentity } from "@metaplex-foundation/umi";
+import { mplTokenMetadata } from "@metaplex-foundation/mpl-token-metadata";
+import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
+import { addCollection, airdrop_if_needed, createNft, findConfig, findTreasury, createAuction, waitUntil } from '../lib';
+import { admin } from '../nectart-auctions';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+const bidder = Keypair.generate();
+
+umi.use(mplTokenMetadata());
+umi.use(mockStorage());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+const config = findConfig(program);
+const treasury = findTreasury(program);
+
+let collectionMint: KeypairSigner;
+let nftMint: KeypairSigner;
+let auction: anchor.web3.PublicKey;
+let vault: anchor.web3.PublicKey;
+let vaultState: anchor.web3.PublicKey;
+let auctionStart: number;
+let auctionEnd: number;
+
+const FEE_BPS = 500;
+const BID = LAMPORTS_PER_SOL;
+const FEE = BID * FEE_BPS / 10_000;
+
+const updateConfig = async (signer: Keypair, feeBps: number) => {
+  await program.methods.updateConfig(feeBps, null, null, null, null)
+    .accountsPartial({
+      admin: signer.publicKey,
+      config,
+    })
+    .signers([signer])
+    .rpc();
+}
+
+describe("Protocol fee", () => {
+  before(async () => {
+    umi.use(signerIdentity(auctioneer));
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+    await airdrop_if_needed(provider, bidder.publicKey, 5);
+    const mint  = await createNft(umi);
+    collectionMint = mint.collectionMint;
+    await addCollection(program, collectionMint);
+    nftMint = mint.nftMint;
+
+    // the fee is set when the auction is created
+    await updateConfig(admin, FEE_BPS);
+
+    const time = Math.round(new Date().getTime() / 1000);
+    auctionStart = time;
+    auctionEnd = time + 5;
+    ({ auction, vault, vaultState } = await createAuction(program, umi, web3JsAuctioneerSigner, nftMint, collectionMint, {
+      start: auctionStart,
+      deadline: auctionEnd,
+    }));
+
+    await waitUntil(auctionStart);
+    await program.methods.bid(new BN(BID))
+      .accountsPartial({
+        bidder: bidder.publicKey,
+        mint: nftMint.publicKey,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([bidder])
+      .rpc();
+  });
+
+  after(async () => {
+    await updateConfig(admin, 0);
+  });
+
+  it("Only the admin can update the config", async () => {
+    await assert.rejects(async () => {
+      await updateConfig(bidder, 0);
+    }, () => true, "Update should fail");
+    const configAccount = await program.account.config.fetch(config);
+    assert.strictEqual(configAccount.feeBps, FEE_BPS);
+  });
+
+  it("The fee cannot exceed 100%", async () => {
+    await assert.rejects(async () => {
+      await updateConfig(admin, 10_001);
+    }, () => true, "Update should fail");
+  });
+
+  it("A fee change does not apply to existing auctions", async () => {
+    await updateConfig(admin, 2 * FEE_BPS);
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.strictEqual(auctionAccount.feeBps, FEE_BPS);
+  });
+
+  it("The proceeds are split between the maker and the treasury", async () => {
+    await waitUntil(auctionEnd);
+    const treasuryBefore = await provider.connection.getBalance(treasury);
+    const makerBefore = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+    await program.methods.claimProceeds()
+      .accountsPartial({
+        signer: auctioneer.publicKey,
+        mint: nftMint.publicKey,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsAuctioneerSigner])
+      .rpc();
+    const treasuryAfter = await provider.connection.getBalance(treasury);
+    const makerAfter = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+    assert.strictEqual(treasuryAfter - treasuryBefore, FEE);
+    assert.strictEqual(makerAfter - makerBefore, BID - FEE);
+  });
+
+  it("The admin can withdraw the treasury", async () => {
+    await assert.rejects(async () => {
+      await program.methods.withdrawTreasury(new BN(FEE))
+        .accountsPartial({
+          admin: bidder.publicKey,
+          config,
+          treasury,
+        })
+        .signers([bidder])
+        .rpc();
+    }, () => true, "Withdrawal should fail");
+    const before = await provider.connection.getBalance(admin.publicKey);
+    await program.methods.withdrawTreasury(new BN(FEE))
+      .accountsPartial({
+        admin: admin.publicKey,
+        config,
+        treasury,
+      })
+      .signers([admin])
+      .rpc();
+    const after = await provider.connection.getBalance(admin.publicKey);
+    assert(after > before, "The admin should have received the fees");
+  });
+});
@@ -0,0 +1,158 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, signerIdentity } from "@metaplex-foundation/umi";
+import { Creator, findMetadataPda, mplTokenMetadata, verifyCreatorV1 } from "@metaplex-foundation/mpl-token-metadata";
+import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
+import { addCollection, airdrop_if_needed, createNft, createAuction, waitUntil } from '../lib';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+// the artist verifies its share, the co-creator does not
+const artist = generateSigner({ eddsa: umi.eddsa });
+const coCreator = generateSigner({ eddsa: umi.eddsa });
+// never funded, so that a small royalty cannot make its account rent exempt
+const unfundedCreator = generateSigner({ eddsa: umi.eddsa });
+
+const bidder = Keypair.generate();
+
+umi.use(mplTokenMetadata());
+umi.use(mockStorage());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+let collectionMint: KeypairSigner;
+let nftMint: KeypairSigner;
+let auction: anchor.web3.PublicKey;
+let vault: anchor.web3.PublicKey;
+let vaultState: anchor.web3.PublicKey;
+let auctionStart: number;
+let auctionEnd: number;
+
+// matches the seller fee set by `createNft`
+const SELLER_FEE_BASIS_POINTS = 550;
+const ARTIST_SHARE = 60;
+const BID = LAMPORTS_PER_SOL;
+const ROYALTY = BID * SELLER_FEE_BASIS_POINTS * ARTIST_SHARE / 1_000_000;
+
+const claimProceeds = async (creators: anchor.web3.PublicKey[]) => {
+  await program.methods.claimProceeds()
+    .accountsPartial({
+      signer: auctioneer.publicKey,
+      mint: nftMint.publicKey,
+      auction,
+      vault,
+      vaultState,
+    })
+    .remainingAccounts(creators.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })))
+    .signers([web3JsAuctioneerSigner])
+    .rpc();
+}
+
+// auctions an NFT of `creators`, of which only `verifiedCreator` is verified, and ends it with a single bid
+const runAuction = async (creators: Creator[], verifiedCreator: KeypairSigner, bid: number) => {
+  const mint  = await createNft(umi, creators);
+  collectionMint = mint.collectionMint;
+  await addCollection(program, collectionMint);
+  nftMint = mint.nftMint;
+  const nftMetadata = findMetadataPda(umi, { mint: nftMint.publicKey });
+  await verifyCreatorV1(umi, { metadata: nftMetadata, authority: verifiedCreator }).sendAndConfirm(umi);
+
+
+  const time = Math.round(new Date().getTime() / 1000);
+  auctionStart = time;
+  auctionEnd = time + 5;
+  ({ auction, vault, vaultState } = await createAuction(program, umi, web3JsAuctioneerSigner, nftMint, collectionMint, {
+    start: auctionStart,
+    deadline: auctionEnd,
+  }));
+
+  await waitUntil(auctionStart);
+  await program.methods.bid(new BN(bid))
+    .accountsPartial({
+      bidder: bidder.publicKey,
+      mint: nftMint.publicKey,
+      auction,
+      vault,
+      vaultState,
+    })
+    .signers([bidder])
+    .rpc();
+  await waitUntil(auctionEnd);
+}
+
+describe("Royalties", () => {
+  before(async () => {
+    umi.use(signerIdentity(auctioneer));
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(artist.publicKey), 1);
+    await airdrop_if_needed(provider, bidder.publicKey, 5);
+    await runAuction([
+      { address: artist.publicKey, verified: false, share: ARTIST_SHARE },
+      { address: coCreator.publicKey, verified: false, share: 100 - ARTIST_SHARE },
+    ], artist, BID);
+  });
+
+  it("Only the verified creators are recorded on the auction", async () => {
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.strictEqual(auctionAccount.sellerFeeBasisPoints, SELLER_FEE_BASIS_POINTS);
+    assert.strictEqual(auctionAccount.creators.length, 1);
+    assert(auctionAccount.creators[0].address.equals(toWeb3JsPublicKey(artist.publicKey)));
+  });
+
+  it("The proceeds cannot be claimed without paying the creators", async () => {
+    await assert.rejects(async () => {
+      await claimProceeds([]);
+    }, () => true, "Claim should fail");
+    await assert.rejects(async () => {
+      await claimProceeds([bidder.publicKey]);
+    }, () => true, "Claim should fail");
+  });
+
+  it("The creators are paid their royalties out of the winning bid", async () => {
+    const artistBefore = await provider.connection.getBalance(toWeb3JsPublicKey(artist.publicKey));
+    const makerBefore = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+    await claimProceeds([toWeb3JsPublicKey(artist.publicKey)]);
+    const artistAfter = await provider.connection.getBalance(toWeb3JsPublicKey(artist.publicKey));
+    const makerAfter = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+    assert.strictEqual(artistAfter - artistBefore, ROYALTY);
+    assert.strictEqual(makerAfter - makerBefore, BID - ROYALTY);
+  });
+});
+
+describe("Royalties to an unfunded creator", () => {
+  // 5.5% of the bid is below the rent exemption of an empty account
+  const SMALL_BID = LAMPORTS_PER_SOL / 100;
+
+  before(async () => {
+    await runAuction([{ address: unfundedCreator.publicKey, verified: false, share: 100 }], unfundedCreator, SMALL_BID);
+  });
+
+  it("A royalty that cannot make the creator rent exempt goes to the maker", async () => {
+    const makerBefore = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+    await claimProceeds([toWeb3JsPublicKey(unfundedCreator.publicKey)]);
+    const makerAfter = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+    assert.strictEqual(await provider.connection.getBalance(toWeb3JsPublicKey(unfundedCreator.publicKey)), 0);
+    assert.strictEqual(makerAfter - makerBefore, SMALL_BID);
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.deepStrictEqual(auctionAccount.status, { solClaimed: {} });
+  });
+});
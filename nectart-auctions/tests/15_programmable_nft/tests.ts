@@ -0,0 +1,170 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, percentAmount, Pda, signerIdentity } from "@metaplex-foundation/umi";
+import {
+  createNft as metaplexCreateNft,
+  createProgrammableNft,
+  findMasterEditionPda,
+  findMetadataPda,
+  findTokenRecordPda,
+  mplTokenMetadata,
+  TokenStandard,
+  transferV1,
+  verifyCollectionV1,
+} from "@metaplex-foundation/mpl-token-metadata";
+import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
+import { addCollection, airdrop_if_needed, createAuction, waitUntil } from '../lib';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { getAccount, getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair, SYSVAR_INSTRUCTIONS_PUBKEY } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+const bidder = generateSigner({ eddsa: umi.eddsa });
+const web3JsBidderSigner = Keypair.fromSecretKey(bidder.secretKey);
+
+const somebody = generateSigner({ eddsa: umi.eddsa });
+
+umi.use(mplTokenMetadata());
+umi.use(mockStorage());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+let collectionMint: KeypairSigner;
+let nftMint: KeypairSigner;
+let auction: anchor.web3.PublicKey;
+let vault: anchor.web3.PublicKey;
+let vaultState: anchor.web3.PublicKey;
+let nftEdition: Pda<string, number>;
+let nftMetadata: Pda<string, number>;
+let auctioneerAta: anchor.web3.PublicKey;
+let auctioneerTokenRecord: anchor.web3.PublicKey;
+let auctionStart: number;
+let auctionEnd: number;
+
+describe("Auction of a programmable NFT", () => {
+  before(async () => {
+    umi.use(signerIdentity(auctioneer));
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(bidder.publicKey), 5);
+
+    collectionMint = generateSigner(umi);
+    await metaplexCreateNft(umi, {
+      mint: collectionMint,
+      name: "GM",
+      symbol: "GM",
+      uri: "https://arweave.net/123",
+      sellerFeeBasisPoints: percentAmount(5.5),
+      isCollection: true,
+    }).sendAndConfirm(umi);
+    await addCollection(program, collectionMint);
+
+    nftMint = generateSigner(umi);
+    await createProgrammableNft(umi, {
+      mint: nftMint,
+      name: "GM",
+      symbol: "GM",
+      uri: "https://arweave.net/123",
+      sellerFeeBasisPoints: percentAmount(5.5),
+      collection: { verified: false, key: collectionMint.publicKey },
+    }).sendAndConfirm(umi);
+    nftMetadata = findMetadataPda(umi, { mint: nftMint.publicKey });
+    nftEdition = findMasterEditionPda(umi, { mint: nftMint.publicKey });
+    await verifyCollectionV1(umi, {
+      metadata: nftMetadata,
+      collectionMint: collectionMint.publicKey,
+      authority: auctioneer,
+    }).sendAndConfirm(umi);
+
+    auctioneerAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey));
+    auctioneerTokenRecord = toWeb3JsPublicKey(findTokenRecordPda(umi, { mint: nftMint.publicKey, token: auctioneerAta.toBase58() as any })[0]);
+
+    const time = Math.round(new Date().getTime() / 1000);
+    auctionStart = time;
+    auctionEnd = time + 10;
+    ({ auction, vault, vaultState } = await createAuction(program, umi, web3JsAuctioneerSigner, nftMint, collectionMint, {
+      start: auctionStart,
+      deadline: auctionEnd,
+    }, {
+      programmable: {
+        tokenRecord: auctioneerTokenRecord,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      },
+    }));
+  });
+
+  it("The auction records the standard of the NFT", async () => {
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.deepStrictEqual(auctionAccount.standard, { programmable: {} });
+  });
+
+  it("The maker cannot transfer the NFT during the auction", async () => {
+    await assert.rejects(async () => {
+      await transferV1(umi, {
+        mint: nftMint.publicKey,
+        authority: auctioneer,
+        tokenOwner: auctioneer.publicKey,
+        destinationOwner: somebody.publicKey,
+        tokenStandard: TokenStandard.ProgrammableNonFungible,
+      }).sendAndConfirm(umi);
+    }, () => true, "Token transfer should fail");
+  });
+
+  it("The winner can claim the NFT", async () => {
+    await waitUntil(auctionStart);
+    await program.methods.bid(new BN(1))
+      .accountsPartial({
+        bidder: bidder.publicKey,
+        mint: nftMint.publicKey,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsBidderSigner])
+      .rpc();
+    await waitUntil(auctionEnd);
+
+    const bidderAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(bidder.publicKey));
+    const bidderTokenRecord = toWeb3JsPublicKey(findTokenRecordPda(umi, { mint: nftMint.publicKey, token: bidderAta.toBase58() as any })[0]);
+    await program.methods.claimNft(null)
+      .accountsPartial({
+        signer: bidder.publicKey,
+        signerAta: bidderAta,
+        auctioneer: auctioneer.publicKey,
+        auctioneerAta,
+        mint: nftMint.publicKey,
+        metadata: toWeb3JsPublicKey(nftMetadata[0]),
+        edition: toWeb3JsPublicKey(nftEdition[0]),
+        auction,
+        vault,
+        vaultState,
+        programmable: {
+          tokenRecord: auctioneerTokenRecord,
+          destinationTokenRecord: bidderTokenRecord,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        },
+      } as any)
+      .signers([web3JsBidderSigner])
+      .rpc();
+
+    const bidderAccount = await getAccount(provider.connection, bidderAta);
+    assert.strictEqual(Number(bidderAccount.amount), 1);
+  });
+});
@@ -0,0 +1,211 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { airdrop_if_needed, findAuctionAccounts, waitUntil } from '../lib';
+import { admin } from '../nectart-auctions';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import {
+  AuthorityType,
+  createAssociatedTokenAccountIdempotent,
+  createInitializeGroupMemberPointerInstruction,
+  createInitializeGroupPointerInstruction,
+  createInitializeMetadataPointerInstruction,
+  createInitializeMint2Instruction,
+  createInitializeNonTransferableMintInstruction,
+  ExtensionType,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMintLen,
+  mintTo,
+  setAuthority,
+  TOKEN_2022_PROGRAM_ID,
+  tokenGroupInitializeGroupWithRentTransfer,
+  tokenGroupMemberInitializeWithRentTransfer,
+  tokenMetadataInitializeWithRentTransfer,
+  transferChecked,
+} from "@solana/spl-token";
+import { Keypair, PublicKey, sendAndConfirmTransaction, SystemProgram, Transaction, TransactionInstruction } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const auctioneer = Keypair.generate();
+const bidder = Keypair.generate();
+const somebody = Keypair.generate();
+
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+let groupMint: PublicKey;
+let nftMint: PublicKey;
+let auction: PublicKey;
+let vault: PublicKey;
+let vaultState: PublicKey;
+let escrow: PublicKey;
+let auctioneerAta: PublicKey;
+let auctionStart: number;
+let auctionEnd: number;
+
+// creates a Token-2022 mint with the given extensions, whose authority is the auctioneer
+const createMint = async (extensions: ExtensionType[], initialize: (mint: PublicKey) => TransactionInstruction[]): Promise<PublicKey> => {
+  const mint = Keypair.generate();
+  const space = getMintLen(extensions);
+  const lamports = await provider.connection.getMinimumBalanceForRentExemption(space);
+  const transaction = new Transaction().add(
+    SystemProgram.createAccount({
+      fromPubkey: auctioneer.publicKey,
+      newAccountPubkey: mint.publicKey,
+      space,
+      lamports,
+      programId: TOKEN_2022_PROGRAM_ID,
+    }),
+    ...initialize(mint.publicKey),
+    createInitializeMint2Instruction(mint.publicKey, 0, auctioneer.publicKey, null, TOKEN_2022_PROGRAM_ID),
+  );
+  await sendAndConfirmTransaction(provider.connection, transaction, [auctioneer, mint]);
+  return mint.publicKey;
+}
+
+// creates a 1/1 Token-2022 NFT of the group of `groupMint`, held by the auctioneer
+const createNft = async (extraExtensions: ExtensionType[] = [], extraInitialize: (mint: PublicKey) => TransactionInstruction[] = () => []): Promise<PublicKey> => {
+  const mint = await createMint(
+    [ExtensionType.GroupMemberPointer, ExtensionType.MetadataPointer, ...extraExtensions],
+    (mint) => [
+      createInitializeGroupMemberPointerInstruction(mint, auctioneer.publicKey, mint, TOKEN_2022_PROGRAM_ID),
+      createInitializeMetadataPointerInstruction(mint, auctioneer.publicKey, mint, TOKEN_2022_PROGRAM_ID),
+      ...extraInitialize(mint),
+    ],
+  );
+  await tokenMetadataInitializeWithRentTransfer(provider.connection, auctioneer, mint, auctioneer.publicKey, auctioneer, "GM", "GM", "https://arweave.net/123", [], undefined, TOKEN_2022_PROGRAM_ID);
+  await tokenGroupMemberInitializeWithRentTransfer(provider.connection, auctioneer, mint, auctioneer, groupMint, auctioneer.publicKey, [], undefined, TOKEN_2022_PROGRAM_ID);
+  const ata = await createAssociatedTokenAccountIdempotent(provider.connection, auctioneer, mint, auctioneer.publicKey, undefined, TOKEN_2022_PROGRAM_ID);
+  await mintTo(provider.connection, auctioneer, mint, ata, auctioneer, 1, [], undefined, TOKEN_2022_PROGRAM_ID);
+  await setAuthority(provider.connection, auctioneer, mint, auctioneer, AuthorityType.MintTokens, null, [], undefined, TOKEN_2022_PROGRAM_ID);
+  return mint;
+}
+
+const createAuction = async (mint: PublicKey) => {
+  const { auction } = findAuctionAccounts(program, mint);
+  await program.methods.createAuction(new BN(auctionStart), new BN(auctionEnd), new BN(0), new BN(0), null, { english: {} }, null, null)
+    .accountsPartial({
+      payer: auctioneer.publicKey,
+      mint,
+      approvedCollection: PublicKey.findProgramAddressSync([Buffer.from('collection'), groupMint.toBuffer()], program.programId)[0],
+      mintAta: getAssociatedTokenAddressSync(mint, auctioneer.publicKey, false, TOKEN_2022_PROGRAM_ID),
+      metadata: null,
+      edition: null,
+      auction,
+      escrow: getAssociatedTokenAddressSync(mint, auction, true, TOKEN_2022_PROGRAM_ID),
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+    })
+    .signers([auctioneer])
+    .rpc();
+}
+
+describe("Auction of a Token-2022 NFT", () => {
+  before(async () => {
+    for (const account of [auctioneer, bidder]) {
+      await airdrop_if_needed(provider, account.publicKey, 5);
+    }
+    groupMint = await createMint(
+      [ExtensionType.GroupPointer],
+      (mint) => [createInitializeGroupPointerInstruction(mint, auctioneer.publicKey, mint, TOKEN_2022_PROGRAM_ID)],
+    );
+    await tokenGroupInitializeGroupWithRentTransfer(provider.connection, auctioneer, groupMint, auctioneer, auctioneer.publicKey, BigInt(10), [], undefined, TOKEN_2022_PROGRAM_ID);
+    nftMint = await createNft();
+
+    ({ auction, vault, vaultState } = findAuctionAccounts(program, nftMint));
+    escrow = getAssociatedTokenAddressSync(nftMint, auction, true, TOKEN_2022_PROGRAM_ID);
+    auctioneerAta = getAssociatedTokenAddressSync(nftMint, auctioneer.publicKey, false, TOKEN_2022_PROGRAM_ID);
+
+    const time = Math.round(new Date().getTime() / 1000);
+    auctionStart = time;
+    auctionEnd = time + 10;
+  });
+
+  it("An NFT of a group not approved by the admin cannot be auctioned", async () => {
+    await assert.rejects(async () => {
+      await createAuction(nftMint);
+    }, () => true, "Auction creation should fail");
+  });
+
+  it("An NFT of an approved group is escrowed by the auction", async () => {
+    await program.methods.addCollection()
+      .accountsPartial({
+        admin: admin.publicKey,
+        collectionMint: groupMint,
+      })
+      .signers([admin])
+      .rpc();
+    await createAuction(nftMint);
+
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.deepStrictEqual(auctionAccount.standard, { token2022: {} });
+    const escrowAccount = await getAccount(provider.connection, escrow, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.strictEqual(Number(escrowAccount.amount), 1);
+  });
+
+  it("The maker cannot transfer the NFT during the auction", async () => {
+    await assert.rejects(async () => {
+      const destination = await createAssociatedTokenAccountIdempotent(provider.connection, auctioneer, nftMint, somebody.publicKey, undefined, TOKEN_2022_PROGRAM_ID);
+      await transferChecked(provider.connection, auctioneer, auctioneerAta, nftMint, destination, auctioneer, 1, 0, [], undefined, TOKEN_2022_PROGRAM_ID);
+    }, () => true, "Token transfer should fail");
+  });
+
+  it("A non-transferable NFT cannot be auctioned", async () => {
+    const soulbound = await createNft(
+      [ExtensionType.NonTransferable],
+      (mint) => [createInitializeNonTransferableMintInstruction(mint, TOKEN_2022_PROGRAM_ID)],
+    );
+    await assert.rejects(async () => {
+      await createAuction(soulbound);
+    }, () => true, "Auction creation should fail");
+  });
+
+  it("The winner can claim the NFT from the escrow", async () => {
+    await waitUntil(auctionStart);
+    await program.methods.bid(new BN(1))
+      .accountsPartial({
+        bidder: bidder.publicKey,
+        mint: nftMint,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([bidder])
+      .rpc();
+    await waitUntil(auctionEnd);
+
+    const bidderAta = getAssociatedTokenAddressSync(nftMint, bidder.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    await program.methods.claimNft(null)
+      .accountsPartial({
+        signer: bidder.publicKey,
+        signerAta: bidderAta,
+        auctioneer: auctioneer.publicKey,
+        auctioneerAta: null,
+        mint: nftMint,
+        metadata: null,
+        edition: null,
+        auction,
+        vault,
+        vaultState,
+        escrow,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([bidder])
+      .rpc();
+
+    const bidderAccount = await getAccount(provider.connection, bidderAta, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.strictEqual(Number(bidderAccount.amount), 1);
+    assert.strictEqual(await provider.connection.getAccountInfo(escrow), null);
+  });
+});
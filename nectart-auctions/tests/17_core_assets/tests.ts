@@ -0,0 +1,160 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, signerIdentity } from "@metaplex-foundation/umi";
+import { create, createCollection, fetchAsset, fetchCollection, MPL_CORE_PROGRAM_ID, mplCore, transfer } from "@metaplex-foundation/mpl-core";
+import { airdrop_if_needed, createAuction, waitUntil } from '../lib';
+import { admin } from '../nectart-auctions';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+const bidder = generateSigner({ eddsa: umi.eddsa });
+const web3JsBidderSigner = Keypair.fromSecretKey(bidder.secretKey);
+
+const somebody = generateSigner({ eddsa: umi.eddsa });
+
+umi.use(mplCore());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+let collection: KeypairSigner;
+let asset: KeypairSigner;
+let auction: PublicKey;
+let vault: PublicKey;
+let vaultState: PublicKey;
+let auctionStart: number;
+let auctionEnd: number;
+
+const core = () => ({
+  collection: toWeb3JsPublicKey(collection.publicKey),
+  coreProgram: toWeb3JsPublicKey(MPL_CORE_PROGRAM_ID),
+});
+
+describe("Auction of a Core asset", () => {
+  before(async () => {
+    umi.use(signerIdentity(auctioneer));
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(bidder.publicKey), 5);
+
+    collection = generateSigner(umi);
+    await createCollection(umi, {
+      collection,
+      name: "GM",
+      uri: "https://arweave.net/123",
+    }).sendAndConfirm(umi);
+    asset = generateSigner(umi);
+    await create(umi, {
+      asset,
+      collection: await fetchCollection(umi, collection.publicKey),
+      name: "GM",
+      uri: "https://arweave.net/123",
+    }).sendAndConfirm(umi);
+
+    await program.methods.addCollection()
+      .accountsPartial({
+        admin: admin.publicKey,
+        collectionMint: toWeb3JsPublicKey(collection.publicKey),
+      })
+      .signers([admin])
+      .rpc();
+
+    const time = Math.round(new Date().getTime() / 1000);
+    auctionStart = time;
+    auctionEnd = time + 10;
+    ({ auction, vault, vaultState } = await createAuction(program, umi, web3JsAuctioneerSigner, asset, collection, {start: auctionStart, deadline: auctionEnd}, {
+      mintAta: null,
+      metadata: null,
+      edition: null,
+      core: core(),
+    }));
+  });
+
+  it("A Core asset cannot be approved as a collection", async () => {
+    await assert.rejects(async () => {
+      await program.methods.addCollection()
+        .accountsPartial({
+          admin: admin.publicKey,
+          collectionMint: toWeb3JsPublicKey(asset.publicKey),
+        })
+        .signers([admin])
+        .rpc();
+    }, () => true, "Approval should fail");
+  });
+
+  it("The auction records the standard of the asset", async () => {
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.deepStrictEqual(auctionAccount.standard, { core: {} });
+  });
+
+  it("The asset is frozen with the auction as delegate", async () => {
+    const coreAsset = await fetchAsset(umi, asset.publicKey);
+    assert.strictEqual(coreAsset.freezeDelegate?.frozen, true);
+    assert.strictEqual(coreAsset.freezeDelegate?.authority.address, auction.toBase58());
+    assert.strictEqual(coreAsset.transferDelegate?.authority.address, auction.toBase58());
+  });
+
+  it("The maker cannot transfer the asset during the auction", async () => {
+    await assert.rejects(async () => {
+      await transfer(umi, {
+        asset: await fetchAsset(umi, asset.publicKey),
+        collection: await fetchCollection(umi, collection.publicKey),
+        newOwner: somebody.publicKey,
+      }).sendAndConfirm(umi);
+    }, () => true, "Asset transfer should fail");
+  });
+
+  it("The winner can claim the asset", async () => {
+    await waitUntil(auctionStart);
+    await program.methods.bid(new BN(1))
+      .accountsPartial({
+        bidder: bidder.publicKey,
+        mint: asset.publicKey,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsBidderSigner])
+      .rpc();
+    await waitUntil(auctionEnd);
+
+    await program.methods.claimNft(null)
+      .accountsPartial({
+        signer: bidder.publicKey,
+        signerAta: null,
+        auctioneer: auctioneer.publicKey,
+        auctioneerAta: null,
+        mint: asset.publicKey,
+        metadata: null,
+        edition: null,
+        auction,
+        vault,
+        vaultState,
+        core: core(),
+      } as any)
+      .signers([web3JsBidderSigner])
+      .rpc();
+
+    const coreAsset = await fetchAsset(umi, asset.publicKey);
+    assert.strictEqual(coreAsset.owner, bidder.publicKey);
+    assert.strictEqual(coreAsset.freezeDelegate, undefined);
+    assert.strictEqual(coreAsset.transferDelegate, undefined);
+  });
+});
@@ -0,0 +1,342 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, none, PublicKey as UmiPublicKey, signerIdentity } from "@metaplex-foundation/umi";
+import {
+  createTree,
+  fetchMerkleTree,
+  findLeafAssetIdPda,
+  findTreeConfigPda,
+  getCurrentRoot,
+  getMerkleProof,
+  getMerkleRoot,
+  getMetadataArgsSerializer,
+  hashLeaf,
+  hashMetadataCreators,
+  hashMetadataData,
+  MetadataArgsArgs,
+  mintV1,
+  MPL_BUBBLEGUM_PROGRAM_ID,
+  mplBubblegum,
+  SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+  SPL_NOOP_PROGRAM_ID,
+  TokenProgramVersion,
+  transfer,
+} from "@metaplex-foundation/mpl-bubblegum";
+import { airdrop_if_needed, findConfig, findApprovedCreator, findAuctionAccounts, waitUntil } from '../lib';
+import { admin } from '../nectart-auctions';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { fromWeb3JsPublicKey, toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+const bidder = generateSigner({ eddsa: umi.eddsa });
+const web3JsBidderSigner = Keypair.fromSecretKey(bidder.secretKey);
+
+const accomplice = generateSigner({ eddsa: umi.eddsa });
+
+umi.use(mplBubblegum());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+const config = findConfig(program);
+
+const MAX_DEPTH = 3;
+const DELIVERY_GRACE_PERIOD = 7 * 24 * 60 * 60;
+
+let merkleTree: KeypairSigner;
+let assetId: PublicKey;
+let metadata: MetadataArgsArgs;
+let auction: PublicKey;
+let vault: PublicKey;
+let vaultState: PublicKey;
+let auctionStart: number;
+let auctionEnd: number;
+
+const compressed = () => ({
+  treeConfig: toWeb3JsPublicKey(findTreeConfigPda(umi, { merkleTree: merkleTree.publicKey })[0]),
+  merkleTree: toWeb3JsPublicKey(merkleTree.publicKey),
+  logWrapper: toWeb3JsPublicKey(SPL_NOOP_PROGRAM_ID),
+  compressionProgram: toWeb3JsPublicKey(SPL_ACCOUNT_COMPRESSION_PROGRAM_ID),
+  bubblegumProgram: toWeb3JsPublicKey(MPL_BUBBLEGUM_PROGRAM_ID),
+});
+
+const leafHash = (owner: UmiPublicKey, delegate: UmiPublicKey) => hashLeaf(umi, {
+  merkleTree: merkleTree.publicKey,
+  owner,
+  delegate,
+  leafIndex: 0,
+  metadata,
+});
+
+// the tree only holds the auctioned leaf, so its proof is made of empty nodes whatever the leaf
+const proof = () => {
+  const leaf = leafHash(auctioneer.publicKey, auctioneer.publicKey);
+  return getMerkleProof([leaf], MAX_DEPTH, leaf)
+    .map((node) => ({ pubkey: toWeb3JsPublicKey(node), isSigner: false, isWritable: false }));
+}
+
+const updateGracePeriod = async (gracePeriod: number) => {
+  await program.methods.updateConfig(null, null, null, null, new BN(gracePeriod))
+    .accountsPartial({
+      admin: admin.publicKey,
+      config,
+    })
+    .signers([admin])
+    .rpc();
+}
+
+const abortAuction = async () => {
+  await program.methods.abortAuction()
+    .accountsPartial({
+      winner: bidder.publicKey,
+      mint: assetId,
+      auction,
+      config,
+    })
+    .signers([web3JsBidderSigner])
+    .rpc();
+}
+
+const failsWith = (code: string) => (err: anchor.AnchorError) => err.error.errorCode.code === code;
+
+const currentRoot = async () => Array.from(getCurrentRoot((await fetchMerkleTree(umi, merkleTree.publicKey)).tree));
+
+const createCompressedAuction = async (start: number, end: number) => {
+  merkleTree = generateSigner(umi);
+  await (await createTree(umi, {
+    merkleTree,
+    maxDepth: MAX_DEPTH,
+    maxBufferSize: 8,
+  })).sendAndConfirm(umi);
+  metadata = {
+    name: "GM",
+    symbol: "GM",
+    uri: "https://arweave.net/123",
+    sellerFeeBasisPoints: 0,
+    primarySaleHappened: false,
+    isMutable: true,
+    editionNonce: none(),
+    tokenStandard: none(),
+    collection: none(),
+    uses: none(),
+    tokenProgramVersion: TokenProgramVersion.Original,
+    creators: [{ address: auctioneer.publicKey, verified: true, share: 100 }],
+  };
+  await mintV1(umi, {
+    leafOwner: auctioneer.publicKey,
+    merkleTree: merkleTree.publicKey,
+    metadata,
+  }).sendAndConfirm(umi);
+  assetId = toWeb3JsPublicKey(findLeafAssetIdPda(umi, { merkleTree: merkleTree.publicKey, leafIndex: 0 })[0]);
+
+  ({ auction, vault, vaultState } = findAuctionAccounts(program, assetId));
+
+  auctionStart = start;
+  auctionEnd = end;
+  const leaf = {
+    root: await currentRoot(),
+    nonce: new BN(0),
+    index: 0,
+    metadata: Buffer.from(getMetadataArgsSerializer().serialize(metadata)),
+  };
+  await program.methods.createAuction(new BN(auctionStart), new BN(auctionEnd), new BN(0), new BN(0), null, { english: {} }, null, leaf)
+    .accountsPartial({
+      payer: auctioneer.publicKey,
+      mint: assetId,
+      approvedCreator: findApprovedCreator(program, toWeb3JsPublicKey(auctioneer.publicKey)),
+      mintAta: null,
+      metadata: null,
+      edition: null,
+      auction,
+      vault,
+      vaultState,
+      compressed: compressed(),
+    } as any)
+    .remainingAccounts(proof())
+    .signers([web3JsAuctioneerSigner])
+    .rpc();
+}
+
+describe("Auction of a compressed NFT", () => {
+  before(async () => {
+    umi.use(signerIdentity(auctioneer));
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(bidder.publicKey), 5);
+
+    await program.methods.addCreator(toWeb3JsPublicKey(auctioneer.publicKey))
+      .accountsPartial({
+        admin: admin.publicKey,
+      })
+      .signers([admin])
+      .rpc();
+
+    const time = Math.round(new Date().getTime() / 1000);
+    await createCompressedAuction(time, time + 10);
+  });
+
+  it("The auction records the standard and the leaf of the NFT", async () => {
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.deepStrictEqual(auctionAccount.standard, { compressed: {} });
+    assert(auctionAccount.leaf.merkleTree.equals(toWeb3JsPublicKey(merkleTree.publicKey)));
+  });
+
+  it("The leaf is delegated to the auction", async () => {
+    const root = getMerkleRoot([leafHash(auctioneer.publicKey, fromWeb3JsPublicKey(auction))], MAX_DEPTH);
+    assert.deepStrictEqual(Array.from(root), await currentRoot());
+  });
+
+  it("The proceeds cannot be claimed before the NFT is delivered", async () => {
+    await waitUntil(auctionStart);
+    await program.methods.bid(new BN(1))
+      .accountsPartial({
+        bidder: bidder.publicKey,
+        mint: assetId,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsBidderSigner])
+      .rpc();
+    await waitUntil(auctionEnd);
+
+    await assert.rejects(async () => {
+      await program.methods.claimProceeds()
+        .accountsPartial({
+          signer: auctioneer.publicKey,
+          mint: assetId,
+          auction,
+          vault,
+          vaultState,
+        })
+        .signers([web3JsAuctioneerSigner])
+        .rpc();
+    }, () => true, "Claiming the proceeds should fail");
+  });
+
+  it("The winner can claim the NFT", async () => {
+    await program.methods.claimNft(await currentRoot())
+      .accountsPartial({
+        signer: bidder.publicKey,
+        signerAta: null,
+        auctioneer: auctioneer.publicKey,
+        auctioneerAta: null,
+        mint: assetId,
+        metadata: null,
+        edition: null,
+        auction,
+        vault,
+        vaultState,
+        compressed: compressed(),
+      } as any)
+      .remainingAccounts(proof())
+      .signers([web3JsBidderSigner])
+      .rpc();
+
+    const root = getMerkleRoot([leafHash(bidder.publicKey, bidder.publicKey)], MAX_DEPTH);
+    assert.deepStrictEqual(Array.from(root), await currentRoot());
+  });
+
+  it("The maker can then claim the proceeds", async () => {
+    await program.methods.claimProceeds()
+      .accountsPartial({
+        signer: auctioneer.publicKey,
+        mint: assetId,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsAuctioneerSigner])
+      .rpc();
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.deepStrictEqual(auctionAccount.status, { settled: {} });
+  });
+});
+
+describe("Abort of an undelivered compressed NFT", () => {
+  const BID = LAMPORTS_PER_SOL / 10;
+
+  before(async () => {
+    const time = Math.round(new Date().getTime() / 1000);
+    await createCompressedAuction(time, time + 10);
+  });
+
+  after(async () => {
+    await updateGracePeriod(DELIVERY_GRACE_PERIOD);
+  });
+
+  it("The maker can move the leaf during the auction", async () => {
+    await waitUntil(auctionStart);
+    await program.methods.bid(new BN(BID))
+      .accountsPartial({
+        bidder: bidder.publicKey,
+        mint: assetId,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsBidderSigner])
+      .rpc();
+
+    await transfer(umi, {
+      leafOwner: auctioneer,
+      leafDelegate: fromWeb3JsPublicKey(auction),
+      newLeafOwner: accomplice.publicKey,
+      merkleTree: merkleTree.publicKey,
+      root: new Uint8Array(await currentRoot()),
+      dataHash: hashMetadataData(metadata),
+      creatorHash: hashMetadataCreators(metadata.creators),
+      nonce: 0,
+      index: 0,
+      proof: proof().map(({ pubkey }) => fromWeb3JsPublicKey(pubkey)),
+    }).sendAndConfirm(umi);
+
+    const root = getMerkleRoot([leafHash(accomplice.publicKey, accomplice.publicKey)], MAX_DEPTH);
+    assert.deepStrictEqual(Array.from(root), await currentRoot());
+  });
+
+  it("The winner cannot abort during the delivery grace period", async () => {
+    await waitUntil(auctionEnd);
+    await assert.rejects(async () => {
+      await abortAuction();
+    }, failsWith("DeliveryPending"), "Abort should fail");
+  });
+
+  it("The winner can abort after the grace period and withdraw the bid", async () => {
+    await updateGracePeriod(0);
+    await abortAuction();
+    let auctionAccount = await program.account.auction.fetch(auction);
+    assert.deepStrictEqual(auctionAccount.status, { cancelled: {} });
+
+    const balance = await provider.connection.getBalance(toWeb3JsPublicKey(bidder.publicKey));
+    await program.methods.withdrawRefund()
+      .accountsPartial({
+        signer: provider.wallet.publicKey,
+        bidder: bidder.publicKey,
+        mint: assetId,
+        auction,
+        vault,
+        vaultState,
+      })
+      .rpc();
+    assert((await provider.connection.getBalance(toWeb3JsPublicKey(bidder.publicKey))) >= balance + BID);
+    auctionAccount = await program.account.auction.fetch(auction);
+    assert.strictEqual(auctionAccount.openEscrows, 0);
+  });
+});
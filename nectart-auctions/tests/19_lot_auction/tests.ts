@@ -0,0 +1,280 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, signerIdentity } from "@metaplex-foundation/umi";
+import { findMasterEditionPda, findMetadataPda, mplTokenMetadata, TokenStandard, transferV1 } from "@metaplex-foundation/mpl-token-metadata";
+import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
+import { addCollection, airdrop_if_needed, createCollectionItem, createNft, findApprovedCollection, createAuction, waitUntil } from '../lib';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { getAccount, getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+const bidder = generateSigner({ eddsa: umi.eddsa });
+const web3JsBidderSigner = Keypair.fromSecretKey(bidder.secretKey);
+
+const somebody = generateSigner({ eddsa: umi.eddsa });
+
+umi.use(mplTokenMetadata());
+umi.use(mockStorage());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+let collectionMint: KeypairSigner;
+let nftMint: KeypairSigner;
+let items: KeypairSigner[];
+let auction: PublicKey;
+let vault: PublicKey;
+let vaultState: PublicKey;
+let lot: PublicKey;
+let auctionStart: number;
+let auctionEnd: number;
+
+const ata = (mint: KeypairSigner, owner: KeypairSigner) => getAssociatedTokenAddressSync(toWeb3JsPublicKey(mint.publicKey), toWeb3JsPublicKey(owner.publicKey));
+
+const addLotItem = async (mint: KeypairSigner, collection: KeypairSigner) => {
+  await program.methods.addLotItem()
+    .accountsPartial({
+      maker: auctioneer.publicKey,
+      auctionMint: nftMint.publicKey,
+      auction,
+      lot,
+      mint: mint.publicKey,
+      mintAta: ata(mint, auctioneer),
+      metadata: toWeb3JsPublicKey(findMetadataPda(umi, { mint: mint.publicKey })[0]),
+      edition: toWeb3JsPublicKey(findMasterEditionPda(umi, { mint: mint.publicKey })[0]),
+      approvedCollection: findApprovedCollection(program, collection),
+    })
+    .signers([web3JsAuctioneerSigner])
+    .rpc();
+}
+
+// every lot item is passed as its mint, its edition, the token account of the maker and the one of the winner
+const claimNft = async (claimed: KeypairSigner[]) => {
+  await program.methods.claimNft(null)
+    .accountsPartial({
+      signer: bidder.publicKey,
+      signerAta: ata(nftMint, bidder),
+      auctioneer: auctioneer.publicKey,
+      auctioneerAta: ata(nftMint, auctioneer),
+      mint: nftMint.publicKey,
+      edition: toWeb3JsPublicKey(findMasterEditionPda(umi, { mint: nftMint.publicKey })[0]),
+      auction,
+      vault,
+      vaultState,
+      lot,
+    })
+    .remainingAccounts(claimed.flatMap((item) => [
+      { pubkey: toWeb3JsPublicKey(item.publicKey), isSigner: false, isWritable: false },
+      { pubkey: toWeb3JsPublicKey(findMasterEditionPda(umi, { mint: item.publicKey })[0]), isSigner: false, isWritable: false },
+      { pubkey: ata(item, auctioneer), isSigner: false, isWritable: true },
+      { pubkey: ata(item, bidder), isSigner: false, isWritable: true },
+    ]))
+    .signers([web3JsBidderSigner])
+    .rpc();
+}
+
+// every lot item is passed as its mint, its edition and the token account of the maker
+const cancelAuction = async (released: KeypairSigner[]) => {
+  await program.methods.cancelAuction(null)
+    .accountsPartial({
+      maker: auctioneer.publicKey,
+      mint: nftMint.publicKey,
+      mintAta: ata(nftMint, auctioneer),
+      edition: toWeb3JsPublicKey(findMasterEditionPda(umi, { mint: nftMint.publicKey })[0]),
+      auction,
+      lot,
+    })
+    .remainingAccounts(released.flatMap((item) => [
+      { pubkey: toWeb3JsPublicKey(item.publicKey), isSigner: false, isWritable: false },
+      { pubkey: toWeb3JsPublicKey(findMasterEditionPda(umi, { mint: item.publicKey })[0]), isSigner: false, isWritable: false },
+      { pubkey: ata(item, auctioneer), isSigner: false, isWritable: true },
+    ]))
+    .signers([web3JsAuctioneerSigner])
+    .rpc();
+}
+
+const balance = async (mint: KeypairSigner, owner: KeypairSigner): Promise<number> => {
+  return Number((await getAccount(provider.connection, ata(mint, owner))).amount);
+}
+
+const openAuction = async (start: number, end: number) => {
+  auctionStart = start;
+  auctionEnd = end;
+  ({ auction, vault, vaultState, lot } = await createAuction(program, umi, web3JsAuctioneerSigner, nftMint, collectionMint, {start, deadline: end}));
+}
+
+describe("Lot auctions", () => {
+  before(async () => {
+    umi.use(signerIdentity(auctioneer));
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(bidder.publicKey), 5);
+    const mint = await createNft(umi);
+    collectionMint = mint.collectionMint;
+    nftMint = mint.nftMint;
+    await addCollection(program, collectionMint);
+    items = [
+      await createCollectionItem(umi, collectionMint),
+      await createCollectionItem(umi, collectionMint),
+    ];
+
+
+    const time = Math.round(new Date().getTime() / 1000);
+    await openAuction(time + 15, time + 25);
+  });
+
+  it("The maker can bundle NFTs of an approved collection before the start", async () => {
+    for (const item of items) {
+      await addLotItem(item, collectionMint);
+    }
+    const lotAccount = await program.account.lot.fetch(lot);
+    assert.strictEqual(lotAccount.items.length, 2);
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.strictEqual(auctionAccount.lotItems, 2);
+  });
+
+  it("An NFT of a collection not approved by the admin cannot be bundled", async () => {
+    const other = await createNft(umi);
+    await assert.rejects(async () => {
+      await addLotItem(other.nftMint, other.collectionMint);
+    }, () => true, "Adding the item should fail");
+  });
+
+  it("An NFT cannot be bundled twice", async () => {
+    await assert.rejects(async () => {
+      await addLotItem(items[0], collectionMint);
+    }, () => true, "Adding the item should fail");
+  });
+
+  it("The items of the lot are frozen", async () => {
+    await assert.rejects(async () => {
+      await (transferV1(umi, {
+        mint: items[0].publicKey,
+        authority: auctioneer,
+        tokenOwner: auctioneer.publicKey,
+        destinationOwner: somebody.publicKey,
+        tokenStandard: TokenStandard.NonFungible,
+      }).sendAndConfirm(umi));
+    }, () => true, "Token transfer should fail");
+  });
+
+  describe("Once the auction is won,", () => {
+    before(async () => {
+      await waitUntil(auctionStart);
+      await program.methods.bid(new BN(1))
+        .accountsPartial({
+          bidder: bidder.publicKey,
+          mint: nftMint.publicKey,
+          auction,
+          vault,
+          vaultState,
+        })
+        .signers([web3JsBidderSigner])
+        .rpc();
+      await waitUntil(auctionEnd);
+    });
+
+    it("the winner can claim part of the lot", async () => {
+      await claimNft([items[0]]);
+      assert.strictEqual(await balance(nftMint, bidder), 1);
+      assert.strictEqual(await balance(items[0], bidder), 1);
+      const auctionAccount = await program.account.auction.fetch(auction);
+      assert.deepStrictEqual(auctionAccount.status, { ended: {} });
+    });
+
+    it("an item cannot be claimed twice", async () => {
+      await assert.rejects(async () => {
+        await claimNft([items[0]]);
+      }, () => true, "Claim should fail");
+    });
+
+    it("the winner can claim the rest of the lot", async () => {
+      await claimNft([items[1]]);
+      assert.strictEqual(await balance(items[1], bidder), 1);
+      const lotAccount = await program.account.lot.fetch(lot);
+      assert(lotAccount.leadDelivered);
+      assert(lotAccount.items.every((item) => item.delivered));
+      const auctionAccount = await program.account.auction.fetch(auction);
+      assert.deepStrictEqual(auctionAccount.status, { nftClaimed: {} });
+    });
+  });
+});
+
+describe("Cancellation of a lot auction", () => {
+  before(async () => {
+    const mint = await createNft(umi);
+    collectionMint = mint.collectionMint;
+    nftMint = mint.nftMint;
+    await addCollection(program, collectionMint);
+    items = [
+      await createCollectionItem(umi, collectionMint),
+      await createCollectionItem(umi, collectionMint),
+    ];
+    const time = Math.round(new Date().getTime() / 1000);
+    await openAuction(time + 60, time + 120);
+    for (const item of items) {
+      await addLotItem(item, collectionMint);
+    }
+  });
+
+  it("The maker can hand part of the lot back, which closes the auction to bids", async () => {
+    await cancelAuction([items[0]]);
+    const lotAccount = await program.account.lot.fetch(lot);
+    assert(lotAccount.leadDelivered);
+    assert(lotAccount.items[0].delivered);
+    assert(!lotAccount.items[1].delivered);
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.deepStrictEqual(auctionAccount.status, { cancelling: {} });
+
+    await assert.rejects(async () => {
+      await program.methods.bid(new BN(1))
+        .accountsPartial({
+          bidder: bidder.publicKey,
+          mint: nftMint.publicKey,
+          auction,
+          vault,
+          vaultState,
+        })
+        .signers([web3JsBidderSigner])
+        .rpc();
+    }, (err: anchor.AnchorError) => err.error.errorCode.code === "AuctionCancelled", "Bid should fail");
+  });
+
+  it("An item cannot be handed back twice", async () => {
+    await assert.rejects(async () => {
+      await cancelAuction([items[0]]);
+    }, () => true, "Cancellation should fail");
+  });
+
+  it("The auction is cancelled once the rest of the lot is handed back", async () => {
+    await cancelAuction([items[1]]);
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.deepStrictEqual(auctionAccount.status, { cancelled: {} });
+
+    await transferV1(umi, {
+      mint: items[1].publicKey,
+      authority: auctioneer,
+      tokenOwner: auctioneer.publicKey,
+      destinationOwner: somebody.publicKey,
+      tokenStandard: TokenStandard.NonFungible,
+    }).sendAndConfirm(umi);
+    assert.strictEqual(await balance(items[1], somebody), 1);
+  });
+});
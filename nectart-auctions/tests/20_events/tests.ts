@@ -0,0 +1,229 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BorshCoder, EventParser, Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, signerIdentity } from "@metaplex-foundation/umi";
+import { findMasterEditionPda, findMetadataPda, mplTokenMetadata } from "@metaplex-foundation/mpl-token-metadata";
+import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
+import { addCollection, airdrop_if_needed, createNft, findApprovedCollection, findConfig, findTreasury, findApprovedCreator, findAuctionAccounts, waitUntil } from '../lib';
+import { admin } from '../nectart-auctions';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { getAssociatedTokenAddressSync} from "@solana/spl-token";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+const bidder1 = generateSigner({ eddsa: umi.eddsa });
+const web3JsBidder1Signer = Keypair.fromSecretKey(bidder1.secretKey);
+
+const bidder2 = generateSigner({ eddsa: umi.eddsa });
+const web3JsBidder2Signer = Keypair.fromSecretKey(bidder2.secretKey);
+
+umi.use(mplTokenMetadata());
+umi.use(mockStorage());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+const parser = new EventParser(program.programId, new BorshCoder(program.idl));
+
+let collectionMint: KeypairSigner;
+let nftMint: KeypairSigner;
+let auction: anchor.web3.PublicKey;
+let vault: anchor.web3.PublicKey;
+let vaultState: anchor.web3.PublicKey;
+let auctionStart: number;
+let auctionEnd: number;
+
+const eventsOf = async (signature: string) => {
+  const tx = await provider.connection.getTransaction(signature, { commitment: 'confirmed', maxSupportedTransactionVersion: 0 });
+  return Array.from(parser.parseLogs(tx.meta.logMessages));
+}
+
+const bid = async (bidder: Keypair, amount: number) => {
+  return program.methods.bid(new BN(amount))
+    .accountsPartial({
+      bidder: bidder.publicKey,
+      mint: nftMint.publicKey,
+      auction,
+      vault,
+      vaultState,
+    })
+    .signers([bidder])
+    .rpc();
+}
+
+describe("Events", () => {
+  before(async () => {
+    umi.use(signerIdentity(auctioneer));
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(bidder1.publicKey), 5);
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(bidder2.publicKey), 5);
+    const mint = await createNft(umi);
+    collectionMint = mint.collectionMint;
+    await addCollection(program, collectionMint);
+    nftMint = mint.nftMint;
+    ({ auction, vault, vaultState } = findAuctionAccounts(program, toWeb3JsPublicKey(nftMint.publicKey)));
+  });
+
+  it("Creating an auction emits AuctionCreated", async () => {
+    const mintAta = getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey));
+    const nftEdition = findMasterEditionPda(umi, { mint: nftMint.publicKey });
+    const nftMetadata = findMetadataPda(umi, { mint: nftMint.publicKey });
+
+    const time = Math.round(new Date().getTime() / 1000);
+    auctionStart = time + 1;
+    auctionEnd = time + 10;
+    const signature = await program.methods.createAuction(new BN(auctionStart), new BN(auctionEnd), new BN(0), new BN(0), null, { english: {} }, null, null)
+      .accountsPartial({
+        payer: auctioneer.publicKey,
+        mint: nftMint.publicKey,
+        approvedCollection: findApprovedCollection(program, collectionMint),
+        mintAta,
+        metadata: toWeb3JsPublicKey(nftMetadata[0]),
+        edition: toWeb3JsPublicKey(nftEdition[0]),
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsAuctioneerSigner])
+      .rpc();
+
+    const [event] = await eventsOf(signature);
+    assert.strictEqual(event.name, "auctionCreated");
+    assert(event.data.auction.equals(auction));
+    assert(event.data.maker.equals(toWeb3JsPublicKey(auctioneer.publicKey)));
+    assert.strictEqual(event.data.deadline.toNumber(), auctionEnd);
+  });
+
+  it("Bidding emits BidPlaced with the outbid bidder and its refund", async () => {
+    await waitUntil(auctionStart);
+
+    const [first] = await eventsOf(await bid(web3JsBidder1Signer, 1));
+    assert.strictEqual(first.name, "bidPlaced");
+    assert.strictEqual(first.data.previousBidder, null);
+    assert.strictEqual(first.data.refund.toNumber(), 0);
+
+    const [second] = await eventsOf(await bid(web3JsBidder2Signer, 2));
+    assert.strictEqual(second.name, "bidPlaced");
+    assert(second.data.bidder.equals(web3JsBidder2Signer.publicKey));
+    assert(second.data.previousBidder.equals(web3JsBidder1Signer.publicKey));
+    assert.strictEqual(second.data.refund.toNumber(), 1);
+  });
+
+  it("Claiming the proceeds emits ProceedsClaimed", async () => {
+    await waitUntil(auctionEnd);
+
+    const signature = await program.methods.claimProceeds()
+      .accountsPartial({
+        signer: web3JsAuctioneerSigner.publicKey,
+        mint: nftMint.publicKey,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([web3JsAuctioneerSigner])
+      .rpc();
+
+    const [event] = await eventsOf(signature);
+    assert.strictEqual(event.name, "proceedsClaimed");
+    assert.strictEqual(event.data.proceeds.toNumber(), 2);
+  });
+});
+
+describe("Admin events", () => {
+  const config = findConfig(program);
+  const treasury = findTreasury(program);
+
+  it("Updating the config emits ConfigUpdated with the new settings", async () => {
+    const { feeBps } = await program.account.config.fetch(config);
+    const signature = await program.methods.updateConfig(null, null, null, null, null)
+      .accountsPartial({
+        admin: admin.publicKey,
+        config,
+      })
+      .signers([admin])
+      .rpc();
+
+    const [event] = await eventsOf(signature);
+    assert.strictEqual(event.name, "configUpdated");
+    assert(event.data.admin.equals(admin.publicKey));
+    assert.strictEqual(event.data.feeBps, feeBps);
+  });
+
+  it("Withdrawing from the treasury emits TreasuryWithdrawn", async () => {
+    const signature = await program.methods.withdrawTreasury(new BN(0))
+      .accountsPartial({
+        admin: admin.publicKey,
+        config,
+        treasury,
+      })
+      .signers([admin])
+      .rpc();
+
+    const [event] = await eventsOf(signature);
+    assert.strictEqual(event.name, "treasuryWithdrawn");
+    assert.strictEqual(event.data.currency, null);
+    assert.strictEqual(event.data.amount.toNumber(), 0);
+  });
+
+  it("Approving and removing a collection emits CollectionAdded and CollectionRemoved", async () => {
+    const { collectionMint } = await createNft(umi);
+    const signature = await program.methods.addCollection()
+      .accountsPartial({
+        admin: admin.publicKey,
+        collectionMint: toWeb3JsPublicKey(collectionMint.publicKey),
+      })
+      .signers([admin])
+      .rpc();
+    const [added] = await eventsOf(signature);
+    assert.strictEqual(added.name, "collectionAdded");
+    assert(added.data.collectionMint.equals(toWeb3JsPublicKey(collectionMint.publicKey)));
+
+    const [removed] = await eventsOf(await program.methods.removeCollection()
+      .accountsPartial({
+        admin: admin.publicKey,
+        approvedCollection: findApprovedCollection(program, collectionMint),
+      })
+      .signers([admin])
+      .rpc());
+    assert.strictEqual(removed.name, "collectionRemoved");
+    assert(removed.data.collectionMint.equals(toWeb3JsPublicKey(collectionMint.publicKey)));
+  });
+
+  it("Approving and removing a creator emits CreatorAdded and CreatorRemoved", async () => {
+    const creator = Keypair.generate().publicKey;
+    const [added] = await eventsOf(await program.methods.addCreator(creator)
+      .accountsPartial({
+        admin: admin.publicKey,
+      })
+      .signers([admin])
+      .rpc());
+    assert.strictEqual(added.name, "creatorAdded");
+    assert(added.data.creator.equals(creator));
+
+    const approvedCreator = findApprovedCreator(program, creator);
+    const [removed] = await eventsOf(await program.methods.removeCreator()
+      .accountsPartial({
+        admin: admin.publicKey,
+        approvedCreator,
+      })
+      .signers([admin])
+      .rpc());
+    assert.strictEqual(removed.name, "creatorRemoved");
+    assert(removed.data.creator.equals(creator));
+  });
+});
@@ -0,0 +1,222 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, signerIdentity } from "@metaplex-foundation/umi";
+import { findMasterEditionPda, findMetadataPda, mplTokenMetadata } from "@metaplex-foundation/mpl-token-metadata";
+import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
+import { addCollection, airdrop_if_needed, createCollectionItem, createNft, findApprovedCollection, findConfig, createAuction, waitUntil } from '../lib';
+import { admin } from '../nectart-auctions';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { getAccount, getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+const bidder = Keypair.generate();
+const cranker = Keypair.generate();
+
+umi.use(mplTokenMetadata());
+umi.use(mockStorage());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+const config = findConfig(program);
+
+let collectionMint: KeypairSigner;
+let nftMint: KeypairSigner;
+let auction: anchor.web3.PublicKey;
+let lot: anchor.web3.PublicKey;
+let vault: anchor.web3.PublicKey;
+let vaultState: anchor.web3.PublicKey;
+let auctionStart: number;
+let auctionEnd: number;
+
+const CRANK_REWARD_BPS = 100;
+const BID = LAMPORTS_PER_SOL;
+const CRANK_REWARD = BID * CRANK_REWARD_BPS / 10_000;
+const CRANK_DELAY = 24 * 60 * 60;
+
+const updateCrankReward = async (crankRewardBps: number | null, crankDelay: number | null) => {
+  await program.methods.updateConfig(null, crankRewardBps, crankDelay === null ? null : new BN(crankDelay), null, null)
+    .accountsPartial({
+      admin: admin.publicKey,
+      config,
+    })
+    .signers([admin])
+    .rpc();
+}
+
+const settle = async () => {
+  await program.methods.settle(null)
+    .accountsPartial({
+      cranker: cranker.publicKey,
+      winner: bidder.publicKey,
+      winnerAta: getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), bidder.publicKey),
+      maker: auctioneer.publicKey,
+      makerAta: getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), toWeb3JsPublicKey(auctioneer.publicKey)),
+      mint: nftMint.publicKey,
+      edition: toWeb3JsPublicKey(findMasterEditionPda(umi, { mint: nftMint.publicKey })[0]),
+      auction,
+      vault,
+      vaultState,
+    })
+    .signers([cranker])
+    .rpc();
+}
+
+const addLotItem = async (item: KeypairSigner) => {
+  await program.methods.addLotItem()
+    .accountsPartial({
+      maker: auctioneer.publicKey,
+      auctionMint: nftMint.publicKey,
+      auction,
+      lot,
+      mint: item.publicKey,
+      mintAta: getAssociatedTokenAddressSync(toWeb3JsPublicKey(item.publicKey), toWeb3JsPublicKey(auctioneer.publicKey)),
+      metadata: toWeb3JsPublicKey(findMetadataPda(umi, { mint: item.publicKey })[0]),
+      edition: toWeb3JsPublicKey(findMasterEditionPda(umi, { mint: item.publicKey })[0]),
+      approvedCollection: findApprovedCollection(program, collectionMint),
+    })
+    .signers([web3JsAuctioneerSigner])
+    .rpc();
+}
+
+// creates an auction of a new NFT, optionally with a lot, and bids on it once it starts
+const startAuction = async (withLot: boolean) => {
+  const mint = await createNft(umi);
+  collectionMint = mint.collectionMint;
+  await addCollection(program, collectionMint);
+  nftMint = mint.nftMint;
+  const time = Math.round(new Date().getTime() / 1000);
+  auctionStart = time + 5;
+  auctionEnd = time + 10;
+  ({ auction, vault, vaultState, lot } = await createAuction(program, umi, web3JsAuctioneerSigner, nftMint, collectionMint, {start: auctionStart, deadline: auctionEnd}));
+  if (withLot) {
+    await addLotItem(await createCollectionItem(umi, collectionMint));
+  }
+
+  await waitUntil(auctionStart);
+  await program.methods.bid(new BN(BID))
+    .accountsPartial({
+      bidder: bidder.publicKey,
+      mint: nftMint.publicKey,
+      auction,
+      vault,
+      vaultState,
+    })
+    .signers([bidder])
+    .rpc();
+}
+
+const failsWith = (code: string) => (err: anchor.AnchorError) => err.error.errorCode.code === code;
+
+describe("Settlement crank", () => {
+  before(async () => {
+    umi.use(signerIdentity(auctioneer));
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+    await airdrop_if_needed(provider, bidder.publicKey, 5);
+    await airdrop_if_needed(provider, cranker.publicKey, 5);
+    await startAuction(false);
+  });
+
+  after(async () => {
+    await updateCrankReward(0, CRANK_DELAY);
+  });
+
+  it("The crank reward cannot exceed 5%", async () => {
+    await assert.rejects(async () => {
+      await updateCrankReward(501, null);
+    }, () => true, "Update should fail");
+    await updateCrankReward(CRANK_REWARD_BPS, null);
+    const configAccount = await program.account.config.fetch(config);
+    assert.strictEqual(configAccount.crankRewardBps, CRANK_REWARD_BPS);
+  });
+
+  it("The crank delay cannot be negative", async () => {
+    await assert.rejects(async () => {
+      await updateCrankReward(null, -1);
+    }, failsWith("InvalidCrankDelay"), "Update should fail");
+    await updateCrankReward(null, 0);
+    const configAccount = await program.account.config.fetch(config);
+    assert.strictEqual(configAccount.crankDelay.toNumber(), 0);
+  });
+
+  it("The auction cannot be settled before its end", async () => {
+    await assert.rejects(async () => {
+      await settle();
+    }, () => true, "Settlement should fail");
+  });
+
+  it("Anyone can settle the auction after its end", async () => {
+    await waitUntil(auctionEnd);
+    const makerBefore = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+    const crankerBefore = await provider.connection.getBalance(cranker.publicKey);
+    await settle();
+
+    const winnerAta = await getAccount(provider.connection, getAssociatedTokenAddressSync(toWeb3JsPublicKey(nftMint.publicKey), bidder.publicKey));
+    assert.strictEqual(winnerAta.amount, BigInt(1));
+    const makerAfter = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+    assert.strictEqual(makerAfter - makerBefore, BID - CRANK_REWARD);
+    const crankerAfter = await provider.connection.getBalance(cranker.publicKey);
+    assert(crankerAfter > crankerBefore, "The cranker should have been rewarded");
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.deepStrictEqual(auctionAccount.status, { settled: {} });
+  });
+
+  it("A settled auction cannot be settled again", async () => {
+    await assert.rejects(async () => {
+      await settle();
+    }, () => true, "Settlement should fail");
+  });
+});
+
+describe("Settlement within the crank delay", () => {
+  before(async () => {
+    await updateCrankReward(CRANK_REWARD_BPS, CRANK_DELAY);
+    await startAuction(false);
+  });
+
+  after(async () => {
+    await updateCrankReward(0, null);
+  });
+
+  it("The auction is settled without crank reward", async () => {
+    await waitUntil(auctionEnd);
+    const makerBefore = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+    await settle();
+
+    const makerAfter = await provider.connection.getBalance(toWeb3JsPublicKey(auctioneer.publicKey));
+    assert.strictEqual(makerAfter - makerBefore, BID);
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.deepStrictEqual(auctionAccount.status, { settled: {} });
+  });
+});
+
+describe("Settlement of a lot auction", () => {
+  before(async () => {
+    await startAuction(true);
+  });
+
+  it("Lots are left to the parties", async () => {
+    await waitUntil(auctionEnd);
+    await assert.rejects(async () => {
+      await settle();
+    }, failsWith("NotSettleable"), "Settlement should fail");
+  });
+});
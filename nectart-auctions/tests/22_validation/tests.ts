@@ -0,0 +1,129 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, signerIdentity } from "@metaplex-foundation/umi";
+import { mplTokenMetadata } from "@metaplex-foundation/mpl-token-metadata";
+import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
+import { addCollection, airdrop_if_needed, createNft, findConfig, findAuctionAccounts, createAuction } from '../lib';
+import { admin } from '../nectart-auctions';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+umi.use(mplTokenMetadata());
+umi.use(mockStorage());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+const config = findConfig(program);
+
+let collectionMint: KeypairSigner;
+let nftMint: KeypairSigner;
+let auction: anchor.web3.PublicKey;
+
+const ONE_DAY = 24 * 60 * 60;
+const BOUNDS = {
+  minDuration: new BN(60),
+  maxDuration: new BN(ONE_DAY),
+  maxLeadTime: new BN(ONE_DAY),
+  minIncrement: new BN(1000),
+};
+const DEFAULT_BOUNDS = {
+  minDuration: new BN(1),
+  maxDuration: new BN(365 * ONE_DAY),
+  maxLeadTime: new BN(365 * ONE_DAY),
+  minIncrement: new BN(0),
+};
+
+const updateBounds = async (bounds: typeof BOUNDS) => {
+  await program.methods.updateConfig(null, null, null, bounds, null)
+    .accountsPartial({
+      admin: admin.publicKey,
+      config,
+    })
+    .signers([admin])
+    .rpc();
+}
+
+const tryCreateAuction = async (start: number, end: number, minIncrement: number, revealEnd: number | null = null) => {
+  const kind = revealEnd === null
+    ? { english: {} }
+    : { sealed: { revealDeadline: new BN(revealEnd), forfeitUnrevealed: false } };
+  await createAuction(program, umi, web3JsAuctioneerSigner, nftMint, collectionMint, {start, deadline: end, minIncrement, kind});
+}
+
+const failsWith = (code: string) => (err: anchor.AnchorError) => err.error.errorCode.code === code;
+
+describe("Auction parameter validation", () => {
+  before(async () => {
+    umi.use(signerIdentity(auctioneer));
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+    const mint = await createNft(umi);
+    collectionMint = mint.collectionMint;
+    await addCollection(program, collectionMint);
+    nftMint = mint.nftMint;
+    ({ auction } = findAuctionAccounts(program, toWeb3JsPublicKey(nftMint.publicKey)));
+  });
+
+  after(async () => {
+    await updateBounds(DEFAULT_BOUNDS);
+  });
+
+  it("The admin cannot set inconsistent bounds", async () => {
+    await assert.rejects(async () => {
+      await updateBounds({ ...BOUNDS, minDuration: new BN(ONE_DAY + 1) });
+    }, failsWith("InvalidBounds"), "Update should fail");
+    await updateBounds(BOUNDS);
+    const configAccount = await program.account.config.fetch(config);
+    assert(configAccount.bounds.minIncrement.eq(BOUNDS.minIncrement));
+  });
+
+  it("Auctions out of bounds are rejected", async () => {
+    const time = Math.round(new Date().getTime() / 1000);
+    await assert.rejects(async () => {
+      await tryCreateAuction(time + 10, time + 10, 1000);
+    }, failsWith("DeadlineBeforeStart"), "Creation should fail");
+    await assert.rejects(async () => {
+      await tryCreateAuction(time, time + 10, 1000);
+    }, failsWith("AuctionTooShort"), "Creation should fail");
+    await assert.rejects(async () => {
+      await tryCreateAuction(time, time + 2 * ONE_DAY, 1000);
+    }, failsWith("AuctionTooLong"), "Creation should fail");
+    await assert.rejects(async () => {
+      await tryCreateAuction(time + 2 * ONE_DAY, time + 2 * ONE_DAY + 60, 1000);
+    }, failsWith("StartTooFar"), "Creation should fail");
+    await assert.rejects(async () => {
+      await tryCreateAuction(time, time + 60, 999);
+    }, failsWith("IncrementTooLow"), "Creation should fail");
+    await assert.rejects(async () => {
+      await tryCreateAuction(time - 120, time - 30, 1000);
+    }, failsWith("DeadlineInPast"), "Creation should fail");
+    await assert.rejects(async () => {
+      await tryCreateAuction(time, time + 60, 1000, time + 60 + ONE_DAY + 1);
+    }, failsWith("RevealTooLong"), "Creation should fail");
+  });
+
+  it("Auctions within bounds are created", async () => {
+    const time = Math.round(new Date().getTime() / 1000);
+    await tryCreateAuction(time, time + 60, 1000);
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert(auctionAccount.minIncrement.eq(BOUNDS.minIncrement));
+  });
+});
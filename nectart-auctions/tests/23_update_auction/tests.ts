@@ -0,0 +1,130 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BorshCoder, EventParser, Program } from "@coral-xyz/anchor";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, KeypairSigner, signerIdentity } from "@metaplex-foundation/umi";
+import { mplTokenMetadata } from "@metaplex-foundation/mpl-token-metadata";
+import { mockStorage } from '@metaplex-foundation/umi-storage-mock';
+import { addCollection, airdrop_if_needed, createNft, createAuction, waitUntil } from '../lib';
+import { NectartAuctions } from "../../target/types/nectart_auctions";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { BN } from "bn.js";
+import assert from "node:assert/strict";
+
+const provider_ = anchor.AnchorProvider.env();
+
+const provider = new anchor.AnchorProvider(
+  provider_.connection,
+  provider_.wallet,
+  {
+    commitment: 'confirmed',
+  }
+);
+
+const umi = createUmi(provider.connection);
+const auctioneer = generateSigner({ eddsa: umi.eddsa });
+const web3JsAuctioneerSigner = Keypair.fromSecretKey(auctioneer.secretKey);
+
+const bidder = Keypair.generate();
+
+umi.use(mplTokenMetadata());
+umi.use(mockStorage());
+anchor.setProvider(provider);
+
+const program = anchor.workspace.NectartAuctions as Program<NectartAuctions>;
+
+const parser = new EventParser(program.programId, new BorshCoder(program.idl));
+
+let collectionMint: KeypairSigner;
+let nftMint: KeypairSigner;
+let auction: anchor.web3.PublicKey;
+let auctionStart: number;
+let auctionEnd: number;
+let vault: anchor.web3.PublicKey;
+let vaultState: anchor.web3.PublicKey;
+
+const MIN_PRICE = LAMPORTS_PER_SOL / 10;
+
+const updateAuction = async (signer: Keypair, startTime: number | null, deadline: number | null, minPrice: number | null) => {
+  return await program.methods.updateAuction(
+    startTime === null ? null : new BN(startTime),
+    deadline === null ? null : new BN(deadline),
+    minPrice === null ? null : new BN(minPrice),
+    null,
+  )
+    .accountsPartial({
+      maker: signer.publicKey,
+      auction,
+    })
+    .signers([signer])
+    .rpc();
+}
+
+const eventsOf = async (signature: string) => {
+  const tx = await provider.connection.getTransaction(signature, { commitment: 'confirmed', maxSupportedTransactionVersion: 0 });
+  return Array.from(parser.parseLogs(tx.meta.logMessages));
+}
+
+const failsWith = (code: string) => (err: anchor.AnchorError) => err.error.errorCode.code === code;
+
+describe("Auction update", () => {
+  before(async () => {
+    umi.use(signerIdentity(auctioneer));
+    await airdrop_if_needed(provider, toWeb3JsPublicKey(auctioneer.publicKey), 5);
+    await airdrop_if_needed(provider, bidder.publicKey, 5);
+    const mint = await createNft(umi);
+    collectionMint = mint.collectionMint;
+    await addCollection(program, collectionMint);
+    nftMint = mint.nftMint;
+    const time = Math.round(new Date().getTime() / 1000);
+    auctionStart = time + 60;
+    auctionEnd = time + 120;
+    ({ auction, vault, vaultState } = await createAuction(program, umi, web3JsAuctioneerSigner, nftMint, collectionMint, {start: auctionStart, deadline: auctionEnd}));
+  });
+
+  it("Only the maker can update the auction", async () => {
+    await assert.rejects(async () => {
+      await updateAuction(bidder, null, null, MIN_PRICE);
+    }, () => true, "Update should fail");
+  });
+
+  it("The new terms are validated", async () => {
+    await assert.rejects(async () => {
+      await updateAuction(web3JsAuctioneerSigner, null, auctionStart, null);
+    }, failsWith("DeadlineBeforeStart"), "Update should fail");
+  });
+
+  it("The maker can change the reserve and the schedule before the start", async () => {
+    const time = Math.round(new Date().getTime() / 1000);
+    auctionStart = time;
+    auctionEnd = time + 10;
+    const signature = await updateAuction(web3JsAuctioneerSigner, auctionStart, auctionEnd, MIN_PRICE);
+
+    const auctionAccount = await program.account.auction.fetch(auction);
+    assert.strictEqual(auctionAccount.startTime.toNumber(), auctionStart);
+    assert.strictEqual(auctionAccount.deadline.toNumber(), auctionEnd);
+    assert.strictEqual(auctionAccount.minPrice.toNumber(), MIN_PRICE);
+    const [event] = await eventsOf(signature);
+    assert.strictEqual(event.name, "auctionUpdated");
+    assert.strictEqual(event.data.old.minPrice.toNumber(), 0);
+    assert.strictEqual(event.data.new.minPrice.toNumber(), MIN_PRICE);
+    assert.strictEqual(event.data.new.deadline.toNumber(), auctionEnd);
+  });
+
+  it("The auction cannot be updated after the first bid", async () => {
+    await waitUntil(auctionStart);
+    await program.methods.bid(new BN(2 * MIN_PRICE))
+      .accountsPartial({
+        bidder: bidder.publicKey,
+        mint: nftMint.publicKey,
+        auction,
+        vault,
+        vaultState,
+      })
+      .signers([bidder])
+      .rpc();
+    await assert.rejects(async () => {
+      await updateAuction(web3JsAuctioneerSigner, null, null, 3 * MIN_PRICE);
+    }, failsWith("AuctionHasBids"), "Update should fail");
+  });
+});
@@ -1,10 +1,15 @@
 import fs from 'fs';
-import { createGenericFile, KeypairSigner, percentAmount } from "@metaplex-foundation/umi";
+import { createGenericFile, KeypairSigner, OptionOrNullable, percentAmount } from "@metaplex-foundation/umi";
 import type { Umi } from "@metaplex-foundation/umi";
 import { generateSigner } from "@metaplex-foundation/umi";
-import { LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
-import { AnchorProvider } from "@coral-xyz/anchor";
-import { createNft as metaplexCreateNft } from "@metaplex-foundation/mpl-token-metadata";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { AnchorProvider, Program } from "@coral-xyz/anchor";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { BN } from "bn.js";
+import { Creator, createNft as metaplexCreateNft } from "@metaplex-foundation/mpl-token-metadata";
+import { toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { admin } from "./nectart-auctions";
+import { NectartAuctions } from "../target/types/nectart_auctions";
 import { findMasterEditionPda, findMetadataPda, verifySizedCollectionItem } from "@metaplex-foundation/mpl-token-metadata";
 
 export const ONE_SECOND = 1000;
@@ -75,7 +80,7 @@ export async function uploadImage(umi: Umi, nftDetail: NFTDetail): Promise<strin
   }
 }
 
-export async function createNft(umi: Umi): Promise<{
+export async function createNft(umi: Umi, creators: OptionOrNullable<Creator[]> = null): Promise<{
   collectionMint: KeypairSigner,
   nftMint: KeypairSigner
 }> {
@@ -94,29 +99,7 @@ export async function createNft(umi: Umi): Promise<{
         }
     }).sendAndConfirm(umi)
 
-    // creates an NFT
-    const nftMint = generateSigner(umi);
-    await metaplexCreateNft(umi, {
-      mint: nftMint,
-      name: "GM",
-      symbol: "GM",
-      uri: "https://arweave.net/123",
-      sellerFeeBasisPoints: percentAmount(5.5),
-      collection: {verified: false, key: collectionMint.publicKey},
-      creators: null,
-    }).sendAndConfirm(umi);
-
-    // verifies metadata
-    const collectionMetadata = findMetadataPda(umi, {mint: collectionMint.publicKey});
-    const collectionMasterEdition = findMasterEditionPda(umi, {mint: collectionMint.publicKey});
-    const nftMetadata = findMetadataPda(umi, {mint: nftMint.publicKey});
-    await verifySizedCollectionItem(umi, {
-      metadata: nftMetadata,
-      collectionAuthority: umi.identity,
-      collectionMint: collectionMint.publicKey,
-      collection: collectionMetadata,
-      collectionMasterEditionAccount: collectionMasterEdition,
-     }).sendAndConfirm(umi)
+    const nftMint = await createCollectionItem(umi, collectionMint, creators);
     return {
       collectionMint,
       nftMint,
@@ -127,6 +110,32 @@ export async function createNft(umi: Umi): Promise<{
   }
 }
 
+// creates an NFT in the collection of `collectionMint` and verifies it.
+export async function createCollectionItem(umi: Umi, collectionMint: KeypairSigner, creators: OptionOrNullable<Creator[]> = null): Promise<KeypairSigner> {
+  const nftMint = generateSigner(umi);
+  await metaplexCreateNft(umi, {
+    mint: nftMint,
+    name: "GM",
+    symbol: "GM",
+    uri: "https://arweave.net/123",
+    sellerFeeBasisPoints: percentAmount(5.5),
+    collection: {verified: false, key: collectionMint.publicKey},
+    creators,
+  }).sendAndConfirm(umi);
+
+  const collectionMetadata = findMetadataPda(umi, {mint: collectionMint.publicKey});
+  const collectionMasterEdition = findMasterEditionPda(umi, {mint: collectionMint.publicKey});
+  const nftMetadata = findMetadataPda(umi, {mint: nftMint.publicKey});
+  await verifySizedCollectionItem(umi, {
+    metadata: nftMetadata,
+    collectionAuthority: umi.identity,
+    collectionMint: collectionMint.publicKey,
+    collection: collectionMetadata,
+    collectionMasterEditionAccount: collectionMasterEdition,
+   }).sendAndConfirm(umi)
+  return nftMint;
+}
+
 export async function airdrop_if_needed(provider: AnchorProvider, publicKey: PublicKey, amount: number) {
   const balance = await provider.connection.getBalance(publicKey);
   if (balance === 0) {
@@ -134,3 +143,111 @@ export async function airdrop_if_needed(provider: AnchorProvider, publicKey: Pub
     await provider.connection.confirmTransaction(signature, "finalized");
   }
 }
+
+// lets NFTs of the collection of `collectionMint` be auctioned.
+export async function addCollection(program: Program<NectartAuctions>, collectionMint: KeypairSigner) {
+  await program.methods.addCollection()
+    .accountsPartial({
+      admin: admin.publicKey,
+      collectionMint: toWeb3JsPublicKey(collectionMint.publicKey),
+    })
+    .signers([admin])
+    .rpc();
+}
+
+export function findApprovedCollection(program: Program<NectartAuctions>, collectionMint: KeypairSigner): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from('collection'), toWeb3JsPublicKey(collectionMint.publicKey).toBuffer()], program.programId)[0];
+}
+
+export function findConfig(program: Program<NectartAuctions>): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from('config')], program.programId)[0];
+}
+
+export function findTreasury(program: Program<NectartAuctions>): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from('treasury')], program.programId)[0];
+}
+
+export function findApprovedCreator(program: Program<NectartAuctions>, creator: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from('creator'), creator.toBuffer()], program.programId)[0];
+}
+
+export type AuctionAccounts = {
+  auction: PublicKey,
+  vault: PublicKey,
+  vaultState: PublicKey,
+  lot: PublicKey,
+};
+
+// derives the auction `id` of `mint`, the NFT, Core asset or compressed asset id, and its accounts.
+export function findAuctionAccounts(program: Program<NectartAuctions>, mint: PublicKey, id: number = 0): AuctionAccounts {
+  const auction = PublicKey.findProgramAddressSync([Buffer.from('auction'), mint.toBuffer(), new BN(id).toArrayLike(Buffer, 'le', 8)], program.programId)[0];
+  return {
+    auction,
+    vault: PublicKey.findProgramAddressSync([Buffer.from('vault'), auction.toBuffer()], program.programId)[0],
+    vaultState: PublicKey.findProgramAddressSync([Buffer.from('state'), auction.toBuffer()], program.programId)[0],
+    lot: PublicKey.findProgramAddressSync([Buffer.from('lot'), auction.toBuffer()], program.programId)[0],
+  };
+}
+
+export function findRefundLedger(program: Program<NectartAuctions>, auction: PublicKey, bidder: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from('refund'), auction.toBuffer(), bidder.toBuffer()], program.programId)[0];
+}
+
+type CreateAuctionArgs = Parameters<Program<NectartAuctions>["methods"]["createAuction"]>;
+
+export type AuctionParams = {
+  start: number,
+  deadline: number,
+  minPrice?: number,
+  minIncrement?: number,
+  softClose?: CreateAuctionArgs[4],
+  kind?: CreateAuctionArgs[5],
+  buyNowPrice?: number,
+};
+
+// puts the Token Metadata NFT `nftMint` of `maker` up for auction, eligible through the collection
+// `collectionMint`. `accounts` adds accounts, such as those of a currency or of a programmable NFT,
+// or replaces derived ones.
+export async function createAuction(
+  program: Program<NectartAuctions>,
+  umi: Umi,
+  maker: Keypair,
+  nftMint: KeypairSigner,
+  collectionMint: KeypairSigner,
+  params: AuctionParams,
+  accounts: object = {},
+): Promise<AuctionAccounts> {
+  const mint = toWeb3JsPublicKey(nftMint.publicKey);
+  const addresses = findAuctionAccounts(program, mint);
+  await program.methods.createAuction(
+    new BN(params.start),
+    new BN(params.deadline),
+    new BN(params.minPrice ?? 0),
+    new BN(params.minIncrement ?? 0),
+    params.softClose ?? null,
+    params.kind ?? { english: {} },
+    params.buyNowPrice === undefined ? null : new BN(params.buyNowPrice),
+    null,
+  )
+    .accountsPartial({
+      payer: maker.publicKey,
+      mint,
+      approvedCollection: findApprovedCollection(program, collectionMint),
+      mintAta: getAssociatedTokenAddressSync(mint, maker.publicKey),
+      metadata: toWeb3JsPublicKey(findMetadataPda(umi, { mint: nftMint.publicKey })[0]),
+      edition: toWeb3JsPublicKey(findMasterEditionPda(umi, { mint: nftMint.publicKey })[0]),
+      auction: addresses.auction,
+      vault: addresses.vault,
+      vaultState: addresses.vaultState,
+      ...accounts,
+    } as any)
+    .signers([maker])
+    .rpc();
+  return addresses;
+}
+
+// waits until `time`, in seconds, has passed on the validator clock, with a margin.
+export async function waitUntil(time: number) {
+  const now = new Date().getTime();
+  await new Promise((resolve) => setTimeout(resolve, time * 1000 - now + 1500));
+}